@@ -70,7 +70,7 @@ trait IStarksqueeze<TContractState> {
     
     fn store_compression_mapping(
         ref self: TContractState,
-        uri: felt252,
+        uri: Array<felt252>,
         file_format: felt252,
         compressed_by: u8,
         original_size: u32,
@@ -152,11 +152,12 @@ mod Starksqueeze {
     #[derive(Drop, starknet::Event)]
     struct CompressionMappingStoredEvent {
         #[key]
-        uri: felt252,
+        uri: Array<felt252>,
         file_format: felt252,
         compressed_by: u8,
         original_size: u32,
         final_size: u32,
+        metadata: Array<felt252>,
     }
 
     #[constructor]
@@ -380,7 +381,7 @@ mod Starksqueeze {
         
         fn store_compression_mapping(
             ref self: ContractState,
-            uri: felt252,
+            uri: Array<felt252>,
             file_format: felt252,
             compressed_by: u8,
             original_size: u32,
@@ -401,6 +402,7 @@ mod Starksqueeze {
                 compressed_by,
                 original_size,
                 final_size,
+                metadata,
             });
         }
     }
@@ -523,7 +523,7 @@ mod tests {
         let metadata = array![16, 17, 18];
         
         contract.store_compression_mapping(
-            123, // uri
+            array![123], // uri
             'txt', // file_format
             50, // compressed_by
             1000, // original_size
@@ -560,7 +560,7 @@ mod tests {
         
         // Try to store with invalid compression percentage (>100)
         contract.store_compression_mapping(
-            123, // uri
+            array![123], // uri
             'txt', // file_format
             101, // compressed_by - invalid
             1000, // original_size
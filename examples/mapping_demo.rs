@@ -0,0 +1,44 @@
+// Mapping Demo
+// Chunks a sample string, saves a complete mapping for it, loads it back and
+// reverses the compression to recover the original bytes.
+//
+// Run with `cargo run --example mapping_demo`.
+
+use stark_squeeze::compression::{compress_data, create_chunk_mapping};
+use stark_squeeze::mapping::{create_complete_mapping, load_mapping, reverse_compression, save_mapping, MappingFile};
+
+const DATA: &[u8] = b"StarkSqueeze maps repeated chunks to short codes. StarkSqueeze maps them back.";
+const CHUNK_SIZE: usize = 4;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Original: {} bytes", DATA.len());
+
+    let chunk_mapping = create_chunk_mapping(DATA, CHUNK_SIZE)?;
+    let codes = compress_data(DATA, &chunk_mapping)?;
+    println!(
+        "Chunk mapping: {} codes for {} unique {}-byte chunks",
+        codes.len(),
+        chunk_mapping.code_to_chunk.len(),
+        CHUNK_SIZE
+    );
+
+    let mapping = create_complete_mapping(DATA, "demo.txt", CHUNK_SIZE, false)?;
+    println!("File info: {} ({} bytes)", mapping.metadata.file_name, mapping.metadata.original_size);
+    println!("ASCII conversion needed: {}", mapping.ascii_conversion.is_some());
+    for (step, instruction) in mapping.reversal_instructions.iter().enumerate() {
+        println!("  {}. {}", step + 1, instruction);
+    }
+
+    let mapping_path = std::env::temp_dir().join("stark_squeeze_mapping_demo.map");
+    save_mapping(&MappingFile::Complete(mapping), &mapping_path)?;
+    let loaded = match load_mapping(&mapping_path.to_string_lossy())? {
+        MappingFile::Complete(mapping) => mapping,
+        MappingFile::Minimal(_) => return Err("expected a complete mapping".into()),
+    };
+    let _ = std::fs::remove_file(&mapping_path);
+
+    let restored = reverse_compression(loaded)?;
+    assert_eq!(restored, DATA);
+    println!("Restored {} bytes, identical to the original", restored.len());
+    Ok(())
+}
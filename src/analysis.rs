@@ -0,0 +1,112 @@
+// Analysis Module
+// Byte statistics that predict how well a file will compress, cheap enough to run
+// before committing to a long compression
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Entropy, in bits per byte, at or above which a file is flagged as likely incompressible
+pub const LIKELY_INCOMPRESSIBLE_BITS: f64 = 7.5;
+
+/// Byte-value histogram of some data and the statistics derived from it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ByteAnalysis {
+    pub total_bytes: u64,
+    /// Occurrences of each byte value
+    pub histogram: [u64; 256],
+}
+
+impl ByteAnalysis {
+    /// Counts the bytes of `data`
+    pub fn of(data: &[u8]) -> Self {
+        let mut analysis = Self { total_bytes: 0, histogram: [0; 256] };
+        analysis.add(data);
+        analysis
+    }
+
+    fn add(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.histogram[byte as usize] += 1;
+        }
+        self.total_bytes += data.len() as u64;
+    }
+
+    /// Shannon entropy in bits per byte, from 0 (one repeated value) to 8 (uniform noise)
+    pub fn entropy(&self) -> f64 {
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        let total = self.total_bytes as f64;
+        self.histogram
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / total;
+                -p * p.log2()
+            })
+            .sum()
+    }
+
+    /// Compressed size over original size an ideal byte-by-byte coder would reach
+    pub fn predicted_ratio(&self) -> f64 {
+        self.entropy() / 8.0
+    }
+
+    pub fn likely_incompressible(&self) -> bool {
+        self.entropy() >= LIKELY_INCOMPRESSIBLE_BITS
+    }
+
+    /// Number of byte values that occur at all
+    pub fn distinct_bytes(&self) -> usize {
+        self.histogram.iter().filter(|&&count| count > 0).count()
+    }
+
+    /// Byte values that occur, most frequent first, ties in value order
+    pub fn most_common(&self) -> Vec<(u8, u64)> {
+        let mut counts: Vec<(u8, u64)> = (0..=255u8)
+            .map(|byte| (byte, self.histogram[byte as usize]))
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        counts
+    }
+}
+
+/// Analyzes the file at `path`, reading it in blocks rather than all at once
+pub fn analyze_file(path: &Path) -> io::Result<ByteAnalysis> {
+    let mut file = File::open(path)?;
+    let mut analysis = ByteAnalysis::of(&[]);
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(analysis);
+        }
+        analysis.add(&buffer[..read]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_bytes_have_zero_entropy() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("zeros.bin");
+        std::fs::write(&path, vec![0x41u8; 200_000]).unwrap();
+
+        let analysis = analyze_file(&path).unwrap();
+        assert_eq!(analysis.total_bytes, 200_000);
+        assert!(analysis.entropy().abs() < 1e-9);
+        assert!(!analysis.likely_incompressible());
+        assert_eq!(analysis.most_common(), vec![(0x41, 200_000)]);
+
+        let every_byte: Vec<u8> = (0..=255u8).cycle().take(256 * 16).collect();
+        let uniform = ByteAnalysis::of(&every_byte);
+        assert!((uniform.entropy() - 8.0).abs() < 1e-9);
+        assert!(uniform.likely_incompressible());
+        assert_eq!(uniform.distinct_bytes(), 256);
+    }
+}
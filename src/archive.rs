@@ -0,0 +1,205 @@
+// Archive Module
+// `.sqza` bundles: a framed JSON manifest naming each file, followed by one framed
+// `compress_file` blob per file
+
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::compression::{compress_file, decompress_file, read_frame, write_frame, CompressionError};
+
+/// Version written into every archive manifest
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// One file in an archive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    /// File name the entry extracts to, without any directory
+    pub file_name: String,
+    pub original_size: u64,
+    pub compressed_size: u64,
+    /// Position of the entry's frame, counted from the end of the manifest frame
+    pub offset: u64,
+}
+
+/// First frame of an archive, listing its entries in blob order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub version: u32,
+    pub files: Vec<ArchiveEntry>,
+}
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    Io(std::io::Error),
+    Compression(CompressionError),
+    Manifest(serde_json::Error),
+    /// The manifest and the blobs after it disagree
+    Mismatch(String),
+    InvalidName(String),
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArchiveError::Io(e) => write!(f, "IO error: {}", e),
+            ArchiveError::Compression(e) => write!(f, "Compression error: {}", e),
+            ArchiveError::Manifest(e) => write!(f, "Invalid archive manifest: {}", e),
+            ArchiveError::Mismatch(msg) => write!(f, "Archive does not match its manifest: {}", msg),
+            ArchiveError::InvalidName(name) => write!(f, "Invalid file name in archive: '{}'", name),
+        }
+    }
+}
+
+impl Error for ArchiveError {}
+
+impl From<std::io::Error> for ArchiveError {
+    fn from(err: std::io::Error) -> Self {
+        ArchiveError::Io(err)
+    }
+}
+
+impl From<CompressionError> for ArchiveError {
+    fn from(err: CompressionError) -> Self {
+        ArchiveError::Compression(err)
+    }
+}
+
+impl From<serde_json::Error> for ArchiveError {
+    fn from(err: serde_json::Error) -> Self {
+        ArchiveError::Manifest(err)
+    }
+}
+
+/// Fails unless `name` is a plain file name that can't escape the extraction directory
+fn check_file_name(name: &str) -> Result<(), ArchiveError> {
+    if Path::new(name).file_name().and_then(|n| n.to_str()) == Some(name) {
+        Ok(())
+    } else {
+        Err(ArchiveError::InvalidName(name.to_string()))
+    }
+}
+
+/// Compresses `files` into an archive at `out`. Files are stored under their file
+/// names, which must be distinct.
+pub fn create_archive(out: &Path, files: &[PathBuf]) -> Result<ArchiveManifest, ArchiveError> {
+    let mut blobs = Vec::with_capacity(files.len());
+    let mut entries: Vec<ArchiveEntry> = Vec::with_capacity(files.len());
+    let mut offset = 0u64;
+    for path in files {
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| ArchiveError::InvalidName(path.display().to_string()))?
+            .to_string();
+        if entries.iter().any(|entry| entry.file_name == file_name) {
+            return Err(ArchiveError::InvalidName(format!("{} (more than one file has this name)", file_name)));
+        }
+        let data = fs::read(path)?;
+        let blob = compress_file(&data)?;
+        entries.push(ArchiveEntry {
+            file_name,
+            original_size: data.len() as u64,
+            compressed_size: blob.len() as u64,
+            offset,
+        });
+        offset += 4 + blob.len() as u64;
+        blobs.push(blob);
+    }
+    let manifest = ArchiveManifest { version: ARCHIVE_VERSION, files: entries };
+
+    let mut writer = BufWriter::new(File::create(out)?);
+    write_frame(&mut writer, &serde_json::to_vec(&manifest)?)?;
+    for blob in &blobs {
+        write_frame(&mut writer, blob)?;
+    }
+    writer.flush()?;
+    Ok(manifest)
+}
+
+/// Restores every file of the archive at `archive` into `dir`, creating it if needed.
+/// Nothing is written unless every blob matches the manifest.
+pub fn extract_archive(archive: &Path, dir: &Path) -> Result<ArchiveManifest, ArchiveError> {
+    let mut reader = BufReader::new(File::open(archive)?);
+    let manifest_frame = read_frame(&mut reader)?.ok_or_else(|| ArchiveError::Mismatch("archive is empty".to_string()))?;
+    let manifest: ArchiveManifest = serde_json::from_slice(&manifest_frame)?;
+    if manifest.version != ARCHIVE_VERSION {
+        return Err(ArchiveError::Mismatch(format!("unsupported archive version {}", manifest.version)));
+    }
+
+    let mut restored = Vec::with_capacity(manifest.files.len());
+    let mut offset = 0u64;
+    for entry in &manifest.files {
+        check_file_name(&entry.file_name)?;
+        if entry.offset != offset {
+            return Err(ArchiveError::Mismatch(format!(
+                "{} is listed at offset {} but its blob starts at {}",
+                entry.file_name, entry.offset, offset
+            )));
+        }
+        let blob = read_frame(&mut reader)?
+            .ok_or_else(|| ArchiveError::Mismatch(format!("blob for {} is missing", entry.file_name)))?;
+        if blob.len() as u64 != entry.compressed_size {
+            return Err(ArchiveError::Mismatch(format!(
+                "{} is listed as {} compressed bytes but its blob has {}",
+                entry.file_name,
+                entry.compressed_size,
+                blob.len()
+            )));
+        }
+        let data = decompress_file(&blob)?;
+        if data.len() as u64 != entry.original_size {
+            return Err(ArchiveError::Mismatch(format!(
+                "{} is listed as {} bytes but decompresses to {}",
+                entry.file_name,
+                entry.original_size,
+                data.len()
+            )));
+        }
+        offset += 4 + blob.len() as u64;
+        restored.push(data);
+    }
+    if read_frame(&mut reader)?.is_some() {
+        return Err(ArchiveError::Mismatch("blobs follow the last listed file".to_string()));
+    }
+
+    fs::create_dir_all(dir)?;
+    for (entry, data) in manifest.files.iter().zip(restored) {
+        fs::write(dir.join(&entry.file_name), data)?;
+    }
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_round_trips_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("notes.txt");
+        let second = dir.path().join("zeros.bin");
+        fs::write(&first, b"some notes, some notes, some notes").unwrap();
+        fs::write(&second, vec![0u8; 5000]).unwrap();
+        let bundle = dir.path().join("bundle.sqza");
+
+        let manifest = create_archive(&bundle, &[first.clone(), second.clone()]).unwrap();
+        assert_eq!(manifest.files.len(), 2);
+        assert_eq!(manifest.files[1].offset, 4 + manifest.files[0].compressed_size);
+
+        let out = dir.path().join("out");
+        extract_archive(&bundle, &out).unwrap();
+        assert_eq!(fs::read(out.join("notes.txt")).unwrap(), fs::read(&first).unwrap());
+        assert_eq!(fs::read(out.join("zeros.bin")).unwrap(), fs::read(&second).unwrap());
+
+        // Dropping the last blob no longer matches the manifest
+        let bytes = fs::read(&bundle).unwrap();
+        let cut = bytes.len() - 4 - manifest.files[1].compressed_size as usize;
+        fs::write(&bundle, &bytes[..cut]).unwrap();
+        assert!(matches!(extract_archive(&bundle, &dir.path().join("cut")), Err(ArchiveError::Mismatch(_))));
+        assert!(!dir.path().join("cut").exists());
+    }
+}
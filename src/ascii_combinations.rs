@@ -0,0 +1,80 @@
+// ASCII Combinations Module
+// Lazily enumerates fixed-length combinations of the 128 ASCII characters in
+// index order, so generators can write them out without building chunks in memory
+
+/// Number of characters each position of a combination ranges over
+pub const ASCII_CHARS: u8 = 128;
+
+/// Yields `(index, combination)` for every index in `start_index..end_index`, where
+/// index `i` is `i` written in base 128 with `length` digits, most significant first
+#[derive(Debug, Clone)]
+pub struct AsciiCombinationIterator {
+    digits: Vec<u8>,
+    next_index: u128,
+    end_index: u128,
+}
+
+impl AsciiCombinationIterator {
+    /// Iterates from `start_index` up to, but not including, `end_index`. The caller
+    /// keeps `end_index` within `128^length`.
+    pub fn new(length: usize, start_index: u128, end_index: u128) -> Self {
+        let mut digits = vec![0u8; length];
+        let mut index = start_index;
+        for digit in digits.iter_mut().rev() {
+            *digit = (index % ASCII_CHARS as u128) as u8;
+            index /= ASCII_CHARS as u128;
+        }
+        Self { digits, next_index: start_index, end_index }
+    }
+
+    /// Index of the next combination to be yielded
+    pub fn next_index(&self) -> u128 {
+        self.next_index
+    }
+}
+
+impl Iterator for AsciiCombinationIterator {
+    type Item = (u128, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_index >= self.end_index {
+            return None;
+        }
+        let item = (self.next_index, self.digits.iter().map(|&d| d as char).collect());
+        self.next_index += 1;
+        // Add one, carrying from the last position
+        for digit in self.digits.iter_mut().rev() {
+            if *digit < ASCII_CHARS - 1 {
+                *digit += 1;
+                break;
+            }
+            *digit = 0;
+        }
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end_index.saturating_sub(self.next_index);
+        match usize::try_from(remaining) {
+            Ok(n) => (n, Some(n)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterates_in_index_order_across_carries() {
+        let items: Vec<_> = AsciiCombinationIterator::new(2, 126, 130).collect();
+        let expected: Vec<(u128, String)> = [(126, [0u8, 126]), (127, [0, 127]), (128, [1, 0]), (129, [1, 1])]
+            .into_iter()
+            .map(|(i, d)| (i, d.iter().map(|&b| b as char).collect()))
+            .collect();
+        assert_eq!(items, expected);
+        assert_eq!(AsciiCombinationIterator::new(3, 5, 5).next(), None);
+        assert_eq!(AsciiCombinationIterator::new(1, 0, 128).count(), 128);
+    }
+}
@@ -5,6 +5,10 @@
 use std::io;
 use std::collections::HashMap;
 use std::error::Error;
+use rayon::prelude::*;
+
+/// Bytes each worker converts at a time in the parallel variants
+const PARALLEL_CHUNK_SIZE: usize = 64 * 1024;
 
 // ASCII printable character range: 32 (space) to 126 (~)
 const ASCII_PRINTABLE_START: u8 = 32;
@@ -37,16 +41,40 @@ pub struct ConversionStats {
     pub total_bytes: usize,
     pub converted_bytes: usize,
     pub character_map: HashMap<u8, usize>,
+    /// Offsets of the converted bytes, in ascending order
+    pub positions_changed: Vec<usize>,
+}
+
+impl ConversionStats {
+    /// Lists each converted byte as `(original_byte, mapped_byte, count)`, most frequent first
+    /// (ties ordered by original byte).
+    ///
+    /// Several originals can share a mapped byte, so this summary alone cannot say which
+    /// original stood at a given position of the converted output.
+    pub fn change_summary(&self) -> Vec<(u8, u8, usize)> {
+        let mut summary: Vec<(u8, u8, usize)> = self
+            .character_map
+            .iter()
+            .map(|(&original, &count)| (original, map_byte(original), count))
+            .collect();
+        summary.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)));
+        summary
+    }
 }
 
-fn convert_byte_to_ascii(byte: u8, stats: &mut ConversionStats) -> u8 {
-    if byte >= ASCII_PRINTABLE_START && byte <= ASCII_PRINTABLE_END {
+fn convert_byte_to_ascii(byte: u8, position: usize, stats: &mut ConversionStats) -> u8 {
+    if (ASCII_PRINTABLE_START..=ASCII_PRINTABLE_END).contains(&byte) {
         return byte;
     }
 
     stats.converted_bytes += 1;
+    stats.positions_changed.push(position);
     *stats.character_map.entry(byte).or_insert(0) += 1;
+    map_byte(byte)
+}
 
+/// Printable replacement for a byte outside the printable range
+fn map_byte(byte: u8) -> u8 {
     for &(from, to) in CHAR_MAPPINGS {
         if byte == from {
             return to;
@@ -73,46 +101,117 @@ pub fn convert_to_printable_ascii(data: &[u8]) -> Result<(Vec<u8>, ConversionSta
     let mut result = Vec::with_capacity(data.len());
 
     // Convert each byte
-    for &byte in data {
-        result.push(convert_byte_to_ascii(byte, &mut stats));
+    for (position, &byte) in data.iter().enumerate() {
+        result.push(convert_byte_to_ascii(byte, position, &mut stats));
     }
 
     Ok((result, stats))
 }
 
+/// Converted bytes, their stats, and `(position, original)` for every altered byte
+pub type ReversibleConversion = (Vec<u8>, ConversionStats, Vec<(usize, u8)>);
+
+/// Like `convert_to_printable_ascii`, also returning the original of every altered byte
+/// by position, so `restore_from_reversible` can undo the conversion exactly
+pub fn convert_to_printable_ascii_reversible(
+    data: &[u8],
+) -> Result<ReversibleConversion, Box<dyn Error + Send + Sync>> {
+    let (result, stats) = convert_to_printable_ascii(data)?;
+    let originals = original_bytes(data, &stats);
+    Ok((result, stats, originals))
+}
+
+/// `(position, original)` for every byte of `data` that `stats` records as altered, in order
+pub fn original_bytes(data: &[u8], stats: &ConversionStats) -> Vec<(usize, u8)> {
+    stats.positions_changed.iter().map(|&pos| (pos, data[pos])).collect()
+}
+
+/// Puts the original bytes recorded by `convert_to_printable_ascii_reversible` back in place
+pub fn restore_from_reversible(converted: &[u8], originals: &[(usize, u8)]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let mut restored = converted.to_vec();
+    for &(position, byte) in originals {
+        let slot = restored.get_mut(position).ok_or_else(|| {
+            format!("Position {} is outside the converted data ({} bytes)", position, converted.len())
+        })?;
+        *slot = byte;
+    }
+    Ok(restored)
+}
+
+/// Like `convert_to_printable_ascii`, converting chunks on the rayon thread pool
+/// (sized by `--threads`). The output and stats are identical to the serial version.
+pub fn convert_to_printable_ascii_parallel(data: &[u8]) -> Result<(Vec<u8>, ConversionStats), Box<dyn Error + Send + Sync>> {
+    Ok(convert_chunks_parallel(data, PARALLEL_CHUNK_SIZE, |_| {}))
+}
+
+/// Converts `data` in `chunk_size` pieces across the thread pool, calling `on_chunk` with
+/// each piece's length as it finishes, and merges the results back in order
+fn convert_chunks_parallel(data: &[u8], chunk_size: usize, on_chunk: impl Fn(usize) + Sync) -> (Vec<u8>, ConversionStats) {
+    let pieces: Vec<(Vec<u8>, ConversionStats)> = data
+        .par_chunks(chunk_size)
+        .enumerate()
+        .map(|(chunk_index, chunk)| {
+            let mut stats = ConversionStats::default();
+            let converted = chunk
+                .iter()
+                .enumerate()
+                .map(|(offset, &byte)| convert_byte_to_ascii(byte, chunk_index * chunk_size + offset, &mut stats))
+                .collect();
+            on_chunk(chunk.len());
+            (converted, stats)
+        })
+        .collect();
+
+    let mut result = Vec::with_capacity(data.len());
+    let mut stats = ConversionStats {
+        total_bytes: data.len(),
+        ..Default::default()
+    };
+    for (converted, piece) in pieces {
+        result.extend_from_slice(&converted);
+        stats.converted_bytes += piece.converted_bytes;
+        stats.positions_changed.extend(piece.positions_changed);
+        for (byte, count) in piece.character_map {
+            *stats.character_map.entry(byte).or_insert(0) += count;
+        }
+    }
+    (result, stats)
+}
+
 // Wrapper function for file conversion with progress indication
-pub fn convert_file_to_ascii(file_data: Vec<u8>) -> io::Result<Vec<u8>> {
+// Returns the same conversion stats as convert_to_printable_ascii
+pub fn convert_file_to_ascii(file_data: &[u8]) -> io::Result<(Vec<u8>, ConversionStats)> {
     use indicatif::{ProgressBar, ProgressStyle};
 
-    let total_size = file_data.len();
-    let pb = ProgressBar::new(total_size as u64);
+    let pb = ProgressBar::new(file_data.len() as u64);
+    crate::output::hide_if_quiet(&pb);
     pb.set_style(
         ProgressStyle::with_template("🔤 [{bar:40.cyan/blue}] {percent}% ⏳ Converting to ASCII...")
             .unwrap()
             .progress_chars("█▉▊▋▌▍▎▏ "),
     );
 
-    let chunk_size = 8192;
-    let mut result = Vec::with_capacity(total_size);
-    let mut stats = ConversionStats {
-        total_bytes: total_size,
-        ..Default::default()
-    };
-
-    for chunk in file_data.chunks(chunk_size) {
-        for &byte in chunk {
-            result.push(convert_byte_to_ascii(byte, &mut stats));
-        }
-        pb.inc(chunk.len() as u64);
-    }
+    let (result, stats) = convert_chunks_parallel(file_data, 8192, |len| pb.inc(len as u64));
 
     pb.finish_with_message("✅ ASCII conversion complete!");
-    Ok(result)
+    Ok((result, stats))
+}
+
+/// Percentage of bytes that convert_to_printable_ascii would alter
+pub fn ascii_loss_percent(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let converted = data
+        .iter()
+        .filter(|&&b| !(ASCII_PRINTABLE_START..=ASCII_PRINTABLE_END).contains(&b))
+        .count();
+    converted as f64 / data.len() as f64 * 100.0
 }
 
 pub fn validate_printable_ascii(data: &[u8]) -> Result<(), String> {
     for (i, &byte) in data.iter().enumerate() {
-        if byte < ASCII_PRINTABLE_START || byte > ASCII_PRINTABLE_END {
+        if !(ASCII_PRINTABLE_START..=ASCII_PRINTABLE_END).contains(&byte) {
             return Err(format!(
                 "Non-printable character found at position {}: 0x{:02X}",
                 i, byte
@@ -143,14 +242,75 @@ mod tests {
         assert_eq!(stats.converted_bytes, 5);
     }
 
+    #[test]
+    fn test_reversible_conversion_round_trips() {
+        let input = vec![0x00, b'a', 0xFF, 0x0A, b'z', 0x0A];
+        let (converted, stats, originals) = convert_to_printable_ascii_reversible(&input).unwrap();
+        assert!(converted.iter().all(|&b| (32..=126).contains(&b)));
+        assert_eq!(stats.positions_changed, vec![0, 2, 3, 5]);
+        assert_eq!(originals, vec![(0, 0x00), (2, 0xFF), (3, 0x0A), (5, 0x0A)]);
+        assert_eq!(restore_from_reversible(&converted, &originals).unwrap(), input);
+
+        let out_of_range = [(10, 0u8)];
+        assert!(restore_from_reversible(&converted, &out_of_range).is_err());
+    }
+
     #[test]
     fn test_extended_ascii_conversion() {
         let input = vec![128, 200, 255];
         let (result, stats) = convert_to_printable_ascii(&input).unwrap();
-        assert!(result.iter().all(|&b| b >= 32 && b <= 126));
+        assert!(result.iter().all(|&b| (32..=126).contains(&b)));
         assert_eq!(stats.converted_bytes, 3);
     }
 
+    #[test]
+    fn test_progress_variant_stats_match() {
+        let input: Vec<u8> = (0..20_000u32).map(|i| (i * 7 % 256) as u8).collect();
+        let (expected, expected_stats) = convert_to_printable_ascii(&input).unwrap();
+        let (result, stats) = convert_file_to_ascii(&input).unwrap();
+        assert_eq!(result, expected);
+        assert_eq!(stats.total_bytes, expected_stats.total_bytes);
+        assert_eq!(stats.converted_bytes, expected_stats.converted_bytes);
+        assert_eq!(stats.character_map, expected_stats.character_map);
+        assert_eq!(stats.positions_changed, expected_stats.positions_changed);
+    }
+
+    #[test]
+    fn test_parallel_conversion_matches_serial_on_one_thread() {
+        let data: Vec<u8> = (0..300_000u32).map(|i| (i * 7 % 256) as u8).collect();
+        let pool = crate::threads::build_thread_pool(1).unwrap();
+        assert_eq!(pool.current_num_threads(), 1);
+        let (parallel, parallel_stats) = pool.install(|| convert_to_printable_ascii_parallel(&data)).unwrap();
+        let (serial, serial_stats) = convert_to_printable_ascii(&data).unwrap();
+        assert_eq!(parallel, serial);
+        assert_eq!(parallel_stats.total_bytes, serial_stats.total_bytes);
+        assert_eq!(parallel_stats.converted_bytes, serial_stats.converted_bytes);
+        assert_eq!(parallel_stats.character_map, serial_stats.character_map);
+        assert_eq!(parallel_stats.positions_changed, serial_stats.positions_changed);
+    }
+
+    #[test]
+    fn test_ascii_loss_percent_matches_stats() {
+        let input = b"ab\x00\x01\xff cd";
+        let (_, stats) = convert_to_printable_ascii(input).unwrap();
+        let expected = stats.converted_bytes as f64 / stats.total_bytes as f64 * 100.0;
+        assert_eq!(ascii_loss_percent(input), expected);
+        assert_eq!(ascii_loss_percent(b""), 0.0);
+    }
+
+    #[test]
+    fn test_change_summary_for_known_input() {
+        let input = b"a\n\nb\t\x00\n\x00c";
+        let (_, stats) = convert_to_printable_ascii(input).unwrap();
+        assert_eq!(
+            stats.change_summary(),
+            vec![(10, b' ', 3), (0, b'0', 2), (9, b' ', 1)]
+        );
+
+        let (_, stats) = convert_to_printable_ascii(b"plain text").unwrap();
+        assert!(stats.change_summary().is_empty());
+    }
+
     #[test]
     fn test_validation_function() {
         let valid = b"Valid ASCII!";
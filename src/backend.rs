@@ -0,0 +1,95 @@
+// Backend Module
+// Compression backends that can be selected by name, shared by the CLI and the benchmark
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::compression::CompressionError;
+use crate::mapping::{create_minimal_mapping, decode_minimal_mapping, MinimalMapping};
+
+/// A compression backend, addressable by its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum CompressionBackend {
+    /// Copies the input unchanged; the baseline every other backend is measured against
+    #[default]
+    Passthrough,
+    /// Chunk dictionary plus one code per chunk, serialized as a raw minimal mapping
+    ChunkMapping,
+}
+
+impl CompressionBackend {
+    /// Every backend, in the order benchmarks report them
+    pub const ALL: [CompressionBackend; 2] = [CompressionBackend::Passthrough, CompressionBackend::ChunkMapping];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CompressionBackend::Passthrough => "passthrough",
+            CompressionBackend::ChunkMapping => "chunk-mapping",
+        }
+    }
+
+    /// Compresses `data`; `chunk_size` is ignored by backends that don't chunk
+    pub fn compress(&self, data: &[u8], chunk_size: usize) -> Result<Vec<u8>, CompressionError> {
+        match self {
+            CompressionBackend::Passthrough => Ok(data.to_vec()),
+            CompressionBackend::ChunkMapping => {
+                let mapping = create_minimal_mapping(data, chunk_size, true)
+                    .map_err(|e| CompressionError::Custom(e.to_string()))?;
+                serde_json::to_vec(&mapping).map_err(|e| CompressionError::Custom(e.to_string()))
+            }
+        }
+    }
+
+    /// Reverses `compress`
+    pub fn decompress(&self, compressed: &[u8]) -> Result<Vec<u8>, CompressionError> {
+        match self {
+            CompressionBackend::Passthrough => Ok(compressed.to_vec()),
+            CompressionBackend::ChunkMapping => {
+                let mapping: MinimalMapping =
+                    serde_json::from_slice(compressed).map_err(|e| CompressionError::Custom(e.to_string()))?;
+                decode_minimal_mapping(&mapping).map_err(|e| CompressionError::Custom(e.to_string()))
+            }
+        }
+    }
+}
+
+impl fmt::Display for CompressionBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for CompressionBackend {
+    type Err = CompressionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CompressionBackend::ALL
+            .into_iter()
+            .find(|backend| backend.name() == s)
+            .ok_or_else(|| {
+                CompressionError::Custom(format!(
+                    "Unknown backend '{}' (expected one of: passthrough, chunk-mapping)",
+                    s
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backends_round_trip_binary_data() {
+        let data: Vec<u8> = (0..600u32).map(|i| (i % 7 * 31) as u8).collect();
+        for backend in CompressionBackend::ALL {
+            let compressed = backend.compress(&data, 4).unwrap();
+            assert_eq!(backend.decompress(&compressed).unwrap(), data, "{}", backend);
+            assert_eq!(backend.name().parse::<CompressionBackend>().unwrap(), backend);
+        }
+        assert!("gzip".parse::<CompressionBackend>().is_err());
+    }
+}
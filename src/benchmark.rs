@@ -0,0 +1,240 @@
+// Benchmark Module
+// Measures compress/decompress throughput and ratio of each compression backend
+// and codec on generated text, JSON, random and repetitive inputs, or on a corpus
+// of files
+
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::backend::CompressionBackend;
+use crate::compression::{compress_file, compress_file_with_options, decompress_file, gunzip_if_needed, CompressionError};
+
+/// A named benchmark input
+#[derive(Debug, Clone)]
+pub struct BenchInput {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// Measurements for one backend on one input
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchResult {
+    pub backend: String,
+    pub input: String,
+    pub original_size: usize,
+    pub compressed_size: usize,
+    /// Compressed size as a percentage of the original
+    pub ratio_percent: f64,
+    pub compress_mb_per_s: f64,
+    pub decompress_mb_per_s: f64,
+    pub compress_ms: f64,
+    pub decompress_ms: f64,
+}
+
+/// A way of compressing and restoring bytes that the benchmark can time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// One of the configurable compression backends
+    Backend(CompressionBackend),
+    /// `compress_file`'s run-length encoding
+    Rle,
+    /// Run-length encoding followed by the `--gzip` stage
+    RleGzip,
+}
+
+impl Codec {
+    /// Every codec, in the order the codec benchmark reports them
+    pub const ALL: [Codec; 4] = [
+        Codec::Backend(CompressionBackend::Passthrough),
+        Codec::Rle,
+        Codec::Backend(CompressionBackend::ChunkMapping),
+        Codec::RleGzip,
+    ];
+
+    pub fn compress(&self, data: &[u8], chunk_size: usize) -> Result<Vec<u8>, CompressionError> {
+        match self {
+            Codec::Backend(backend) => backend.compress(data, chunk_size),
+            Codec::Rle => compress_file(data),
+            Codec::RleGzip => compress_file_with_options(data, true),
+        }
+    }
+
+    pub fn decompress(&self, compressed: &[u8]) -> Result<Vec<u8>, CompressionError> {
+        match self {
+            Codec::Backend(backend) => backend.decompress(compressed),
+            Codec::Rle => decompress_file(compressed),
+            Codec::RleGzip => decompress_file(&gunzip_if_needed(compressed)?),
+        }
+    }
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Codec::Backend(backend) => write!(f, "{}", backend.name()),
+            Codec::Rle => write!(f, "rle"),
+            Codec::RleGzip => write!(f, "rle+gzip"),
+        }
+    }
+}
+
+/// Builds text, JSON and random inputs of roughly `size` bytes each
+pub fn sample_inputs(size: usize) -> Vec<BenchInput> {
+    const WORDS: [&str; 8] = ["stark", "squeeze", "felt", "chunk", "mapping", "ipfs", "the", "and"];
+
+    let mut text = String::with_capacity(size);
+    let mut i = 0usize;
+    while text.len() < size {
+        text.push_str(WORDS[i * 7 % WORDS.len()]);
+        text.push(if i % 12 == 11 { '\n' } else { ' ' });
+        i += 1;
+    }
+
+    let mut json = String::from("[");
+    let mut id = 0usize;
+    while json.len() < size {
+        json.push_str(&format!(
+            "{{\"id\":{},\"name\":\"file_{}.txt\",\"size\":{},\"public\":{}}},",
+            id,
+            id,
+            id * 131 % 9973,
+            id.is_multiple_of(2)
+        ));
+        id += 1;
+    }
+    json.push(']');
+
+    // xorshift keeps the "random" input reproducible between runs
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let random = (0..size)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect();
+
+    // Long runs of a few byte values, the best case for run-length encoding
+    let repetitive = (0..size).map(|i| b"aaaaaaaabbbbcc"[i % 14]).collect();
+
+    vec![
+        BenchInput { name: "text".to_string(), data: text.into_bytes() },
+        BenchInput { name: "json".to_string(), data: json.into_bytes() },
+        BenchInput { name: "random".to_string(), data: random },
+        BenchInput { name: "repetitive".to_string(), data: repetitive },
+    ]
+}
+
+/// Reads every regular file directly in `dir` as an input, in name order
+pub fn load_corpus(dir: &Path) -> std::io::Result<Vec<BenchInput>> {
+    let mut paths = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.retain(|path| path.is_file());
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            Ok(BenchInput { name, data: std::fs::read(&path)? })
+        })
+        .collect()
+}
+
+fn mb_per_s(bytes: usize, elapsed: Duration) -> f64 {
+    bytes as f64 / 1_000_000.0 / elapsed.as_secs_f64().max(1e-9)
+}
+
+/// Runs every backend over every input, failing if any backend does not round-trip
+pub fn run_benchmark(
+    backends: &[CompressionBackend],
+    inputs: &[BenchInput],
+    chunk_size: usize,
+) -> Result<Vec<BenchResult>, CompressionError> {
+    let codecs: Vec<Codec> = backends.iter().copied().map(Codec::Backend).collect();
+    run_codec_benchmark(&codecs, inputs, chunk_size)
+}
+
+/// Runs every codec over every input, failing if any codec does not round-trip
+pub fn run_codec_benchmark(
+    codecs: &[Codec],
+    inputs: &[BenchInput],
+    chunk_size: usize,
+) -> Result<Vec<BenchResult>, CompressionError> {
+    let mut results = Vec::new();
+    for codec in codecs {
+        for input in inputs {
+            let start = Instant::now();
+            let compressed = codec.compress(&input.data, chunk_size)?;
+            let compress_time = start.elapsed();
+
+            let start = Instant::now();
+            let restored = codec.decompress(&compressed)?;
+            let decompress_time = start.elapsed();
+
+            if restored != input.data {
+                return Err(CompressionError::Custom(format!(
+                    "{} did not round-trip the {} input",
+                    codec, input.name
+                )));
+            }
+
+            results.push(BenchResult {
+                backend: codec.to_string(),
+                input: input.name.clone(),
+                original_size: input.data.len(),
+                compressed_size: compressed.len(),
+                ratio_percent: if input.data.is_empty() {
+                    100.0
+                } else {
+                    compressed.len() as f64 / input.data.len() as f64 * 100.0
+                },
+                compress_mb_per_s: mb_per_s(input.data.len(), compress_time),
+                decompress_mb_per_s: mb_per_s(input.data.len(), decompress_time),
+                compress_ms: compress_time.as_secs_f64() * 1000.0,
+                decompress_ms: decompress_time.as_secs_f64() * 1000.0,
+            });
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_benchmark_reports_passthrough_throughput() {
+        let inputs = sample_inputs(4096);
+        assert!(inputs.iter().all(|input| input.data.len() >= 4096));
+
+        let results = run_benchmark(&CompressionBackend::ALL, &inputs, 3).unwrap();
+        assert_eq!(results.len(), CompressionBackend::ALL.len() * inputs.len());
+        for result in results.iter().filter(|r| r.backend == "passthrough") {
+            assert!(result.compress_mb_per_s > 0.0);
+            assert!(result.decompress_mb_per_s > 0.0);
+            assert_eq!(result.ratio_percent, 100.0);
+        }
+    }
+
+    #[test]
+    fn test_codec_benchmark_covers_rle_and_gzip() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("b.txt"), b"stark squeeze ".repeat(200)).unwrap();
+        std::fs::write(dir.path().join("a.bin"), vec![0u8; 4096]).unwrap();
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+        let corpus = load_corpus(dir.path()).unwrap();
+        assert_eq!(corpus.iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), ["a.bin", "b.txt"]);
+
+        let results = run_codec_benchmark(&Codec::ALL, &corpus, 3).unwrap();
+        assert_eq!(results.len(), Codec::ALL.len() * corpus.len());
+        let ratio = |codec: &str, input: &str| {
+            results.iter().find(|r| r.backend == codec && r.input == input).unwrap().ratio_percent
+        };
+        assert!(ratio("rle", "a.bin") < 5.0);
+        assert!(ratio("rle+gzip", "b.txt") < ratio("rle", "b.txt"));
+    }
+}
@@ -0,0 +1,87 @@
+use stark_squeeze::backend::CompressionBackend;
+use stark_squeeze::benchmark::{run_benchmark, sample_inputs};
+use stark_squeeze::config::get_config;
+use stark_squeeze::dictionary::{load_dictionary, load_dictionary_binary, save_dictionary_binary, Dictionary};
+use std::time::Instant;
+
+/// Benchmarks every compression backend on generated inputs.
+/// Usage: benchmark [--size <bytes>] [--json]
+///        benchmark --dictionary-load [<entries>]
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(pos) = args.iter().position(|a| a == "--dictionary-load") {
+        let entries = args.get(pos + 1).and_then(|v| v.parse::<usize>().ok()).unwrap_or(1_000_000);
+        if let Err(e) = dictionary_load_benchmark(entries) {
+            eprintln!("Benchmark failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let json = args.iter().any(|a| a == "--json");
+    let size = match args.iter().position(|a| a == "--size") {
+        Some(pos) => match args.get(pos + 1).and_then(|v| v.parse::<usize>().ok()) {
+            Some(size) if size > 0 => size,
+            _ => {
+                eprintln!("--size requires a positive number of bytes");
+                std::process::exit(2);
+            }
+        },
+        None => 1_000_000,
+    };
+
+    let inputs = sample_inputs(size);
+    let chunk_size = get_config().compression.chunk_size_range.default;
+    let results = match run_benchmark(&CompressionBackend::ALL, &inputs, chunk_size) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("Benchmark failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results).expect("benchmark results serialize"));
+        return;
+    }
+
+    println!(
+        "{:<14} {:<8} {:>12} {:>12} {:>9} {:>14} {:>16}",
+        "backend", "input", "original", "compressed", "ratio", "compress MB/s", "decompress MB/s"
+    );
+    for r in &results {
+        println!(
+            "{:<14} {:<8} {:>12} {:>12} {:>8.1}% {:>14.1} {:>16.1}",
+            r.backend, r.input, r.original_size, r.compressed_size, r.ratio_percent, r.compress_mb_per_s, r.decompress_mb_per_s
+        );
+    }
+}
+
+/// Times loading the same generated dictionary from JSON and from the binary format
+fn dictionary_load_benchmark(entries: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let length = entries.max(1).to_string().len();
+    let combinations = (0..entries)
+        .map(|i| (format!("{:0width$}", i, width = length), ((b'a' + (i % 26) as u8) as char).to_string()))
+        .collect();
+    let dir = std::env::temp_dir();
+    let json_path = dir.join(format!("stark_squeeze_bench_{}.json", std::process::id()));
+    let binary_path = json_path.with_extension("bin");
+    let dictionary = Dictionary { path: json_path.clone(), length, combinations };
+    dictionary.save_to_file(&json_path)?;
+    save_dictionary_binary(&dictionary, &binary_path)?;
+
+    let start = Instant::now();
+    let from_json = load_dictionary(&json_path)?;
+    let json_time = start.elapsed();
+    let start = Instant::now();
+    let from_binary = load_dictionary_binary(&binary_path)?;
+    let binary_time = start.elapsed();
+    assert_eq!(from_json.combinations, from_binary.combinations);
+
+    println!("{:<8} {:>12} {:>12}", "format", "size", "load ms");
+    for (format, path, time) in [("json", &json_path, json_time), ("binary", &binary_path, binary_time)] {
+        println!("{:<8} {:>12} {:>12.1}", format, std::fs::metadata(path)?.len(), time.as_secs_f64() * 1000.0);
+    }
+    let _ = std::fs::remove_file(&json_path);
+    let _ = std::fs::remove_file(&binary_path);
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use stark_squeeze::benchmark::{load_corpus, run_codec_benchmark, sample_inputs, Codec};
+use stark_squeeze::config::get_config;
+use std::path::Path;
+
+/// Compares compression ratio and encode/decode time of every codec (RLE, the
+/// chunk-mapping dictionary and RLE followed by gzip) on a corpus, exiting with an
+/// error if any codec fails to round-trip.
+/// Usage: compression_bench [<corpus-dir>] [--json]
+///
+/// Without a corpus directory, generated text, JSON, random and repetitive inputs are used.
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let json = args.iter().any(|a| a == "--json");
+    let inputs = match args.iter().find(|a| !a.starts_with("--")) {
+        Some(dir) => match load_corpus(Path::new(dir)) {
+            Ok(inputs) if !inputs.is_empty() => inputs,
+            Ok(_) => {
+                eprintln!("No files found in corpus directory {}", dir);
+                std::process::exit(2);
+            }
+            Err(e) => {
+                eprintln!("Could not read corpus directory {}: {}", dir, e);
+                std::process::exit(2);
+            }
+        },
+        None => sample_inputs(1_000_000),
+    };
+
+    let chunk_size = get_config().compression.chunk_size_range.default;
+    let results = match run_codec_benchmark(&Codec::ALL, &inputs, chunk_size) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("Benchmark failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results).expect("benchmark results serialize"));
+        return;
+    }
+
+    println!(
+        "{:<14} {:<20} {:>12} {:>12} {:>9} {:>12} {:>12}",
+        "codec", "input", "original", "compressed", "ratio", "encode ms", "decode ms"
+    );
+    for r in &results {
+        println!(
+            "{:<14} {:<20} {:>12} {:>12} {:>8.1}% {:>12.2} {:>12.2}",
+            r.backend, r.input, r.original_size, r.compressed_size, r.ratio_percent, r.compress_ms, r.decompress_ms
+        );
+    }
+}
@@ -0,0 +1,13 @@
+use stark_squeeze::config::config_schema;
+
+/// Prints the JSON Schema for config.json to stdout.
+/// Usage: cargo run --features schema --bin export-config-schema > config.schema.json
+fn main() {
+    match serde_json::to_string_pretty(&config_schema()) {
+        Ok(schema) => println!("{}", schema),
+        Err(e) => {
+            eprintln!("Failed to serialize config schema: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// Binary Chunks Module
+// Splits the `{:08b}` binary strings produced by the upload pipeline into 5-bit chunks
+
+/// Bits per chunk
+pub const CHUNK_BITS: usize = 5;
+
+/// Checks that `binary` holds only `0` and `1`
+pub fn validate_binary(binary: &str) -> Result<(), String> {
+    match binary.bytes().position(|b| b != b'0' && b != b'1') {
+        Some(i) => Err(format!("Non-binary character at position {}", i)),
+        None => Ok(()),
+    }
+}
+
+/// Lazily yields `binary` as slices of 5 characters, the last of which may be shorter.
+/// The whole input is validated before anything is yielded.
+pub fn split_by_5_iter(binary: &str) -> Result<impl Iterator<Item = &str>, String> {
+    validate_binary(binary)?;
+    // Every character is a single ASCII byte, so byte offsets are character boundaries
+    Ok((0..binary.len())
+        .step_by(CHUNK_BITS)
+        .map(move |start| &binary[start..binary.len().min(start + CHUNK_BITS)]))
+}
+
+/// `split_by_5_iter` collected into a JSON array of strings
+pub fn split_by_5(binary: &str) -> Result<String, String> {
+    let chunks: Vec<&str> = split_by_5_iter(binary)?.collect();
+    serde_json::to_string(&chunks).map_err(|e| e.to_string())
+}
+
+/// 5-bit chunks packed one per byte, with the bit count needed to undo the packing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Joined5 {
+    /// Value of each chunk. The last chunk may be shorter than 5 bits, in which case
+    /// its value covers only the bits it has
+    pub values: Vec<u8>,
+    /// Length of the original binary string
+    pub bit_len: usize,
+}
+
+/// Packs each 5-character chunk of `binary` into the value it spells
+pub fn join_by_5(binary: &str) -> Result<Joined5, String> {
+    let values = split_by_5_iter(binary)?
+        .map(|chunk| chunk.bytes().fold(0u8, |value, bit| (value << 1) | (bit - b'0')))
+        .collect();
+    Ok(Joined5 { values, bit_len: binary.len() })
+}
+
+/// Restores the exact binary string `join_by_5` was given, writing the last chunk
+/// with only as many bits as it had
+pub fn split_back(joined: &Joined5) -> Result<String, String> {
+    if joined.values.len() != joined.bit_len.div_ceil(CHUNK_BITS) {
+        return Err(format!("{} chunks cannot hold {} bits", joined.values.len(), joined.bit_len));
+    }
+    let mut binary = String::with_capacity(joined.bit_len);
+    for (i, &value) in joined.values.iter().enumerate() {
+        let width = CHUNK_BITS.min(joined.bit_len - i * CHUNK_BITS);
+        if value >> width != 0 {
+            return Err(format!("Chunk {} value {} does not fit in {} bits", i, value, width));
+        }
+        binary.push_str(&format!("{:0width$b}", value, width = width));
+    }
+    Ok(binary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterator_matches_json_chunks() {
+        let binary = "0100100001101001";
+        let json: Vec<String> = serde_json::from_str(&split_by_5(binary).unwrap()).unwrap();
+        let chunks: Vec<&str> = split_by_5_iter(binary).unwrap().collect();
+        assert_eq!(chunks, json);
+        assert_eq!(chunks, ["01001", "00001", "10100", "1"]);
+
+        assert_eq!(split_by_5_iter("").unwrap().count(), 0);
+        assert!(split_by_5_iter("0102").is_err());
+        assert!(split_by_5("01 1").is_err());
+    }
+
+    #[test]
+    fn test_join_and_split_back_keep_a_short_last_chunk() {
+        let binary = "1000000000001";
+        let joined = join_by_5(binary).unwrap();
+        assert_eq!(joined, Joined5 { values: vec![0b10000, 0b00000, 0b001], bit_len: 13 });
+        assert_eq!(split_back(&joined).unwrap(), binary);
+
+        // Leading zeros of the short chunk survive too
+        let joined = join_by_5("0000000").unwrap();
+        assert_eq!(split_back(&joined).unwrap(), "0000000");
+
+        assert!(split_back(&Joined5 { values: vec![1, 2], bit_len: 13 }).is_err());
+        assert!(split_back(&Joined5 { values: vec![0, 0, 8], bit_len: 13 }).is_err());
+    }
+}
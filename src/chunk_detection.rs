@@ -0,0 +1,268 @@
+// Chunk Size Detection Module
+// Picks an initial chunk size for create_chunk_mapping from the detected content
+// type and the dominant repetition period of a short prefix of the data, or searches
+// for the size the dictionary codec compresses best
+
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::sync::{Mutex, OnceLock};
+use crate::compression::{compress_file_with_chunk_size, Codec};
+use crate::config::{get_config, ChunkSizeRange};
+
+/// Number of leading bytes scanned for a repetition period
+pub const SCAN_PREFIX_LEN: usize = 64 * 1024;
+
+/// Largest repetition period (in bytes) the autocorrelation scan looks for
+pub const MAX_PERIOD: usize = 64;
+
+/// Minimum fraction of matching bytes at a lag for it to count as a period
+const MIN_PERIOD_SCORE: f64 = 0.5;
+
+/// Lags scoring within this margin of the best one are treated as equally good,
+/// so the shortest (fundamental) period wins over its multiples
+const PERIOD_SCORE_MARGIN: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentKind {
+    Empty,
+    Text,
+    Image,
+    Compressed,
+    Binary,
+}
+
+impl fmt::Display for ContentKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContentKind::Empty => write!(f, "empty"),
+            ContentKind::Text => write!(f, "text"),
+            ContentKind::Image => write!(f, "image"),
+            ContentKind::Compressed => write!(f, "compressed"),
+            ContentKind::Binary => write!(f, "binary"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkSizeSuggestion {
+    pub chunk_size: usize,
+    pub content_kind: ContentKind,
+    pub period: Option<usize>,
+    pub reason: String,
+}
+
+/// Classifies data from its magic bytes, falling back to a printable-byte ratio
+pub fn classify_content(data: &[u8]) -> ContentKind {
+    const IMAGE_MAGIC: &[&[u8]] = &[b"\x89PNG", b"\xFF\xD8\xFF", b"GIF8", b"BM"];
+    const COMPRESSED_MAGIC: &[&[u8]] = &[b"\x1F\x8B", b"PK\x03\x04", b"7z\xBC\xAF", b"BZh", b"\x28\xB5\x2F\xFD"];
+
+    if data.is_empty() {
+        return ContentKind::Empty;
+    }
+    if IMAGE_MAGIC.iter().any(|magic| data.starts_with(magic)) {
+        return ContentKind::Image;
+    }
+    if COMPRESSED_MAGIC.iter().any(|magic| data.starts_with(magic)) {
+        return ContentKind::Compressed;
+    }
+
+    let sample = &data[..data.len().min(SCAN_PREFIX_LEN)];
+    let printable = sample
+        .iter()
+        .filter(|&&b| (32..=126).contains(&b) || b == b'\n' || b == b'\r' || b == b'\t')
+        .count();
+    if printable as f64 / sample.len() as f64 >= 0.95 {
+        ContentKind::Text
+    } else {
+        ContentKind::Binary
+    }
+}
+
+/// Finds the dominant repetition period of `data` by autocorrelation.
+/// Returns the period and the fraction of bytes that repeat at that lag.
+pub fn detect_period(data: &[u8], max_period: usize) -> Option<(usize, f64)> {
+    let max_lag = max_period.min(data.len().saturating_sub(1));
+    let scores: Vec<(usize, f64)> = (1..=max_lag)
+        .map(|lag| {
+            let compared = data.len() - lag;
+            let matches = data.iter().zip(&data[lag..]).filter(|(a, b)| a == b).count();
+            (lag, matches as f64 / compared as f64)
+        })
+        .collect();
+
+    let best = scores.iter().map(|&(_, score)| score).fold(0.0, f64::max);
+    if best < MIN_PERIOD_SCORE {
+        return None;
+    }
+    scores.into_iter().find(|&(_, score)| score >= best - PERIOD_SCORE_MARGIN)
+}
+
+/// Picks a chunk size inside `range` that matches or divides `period`, or a multiple of it
+/// for periods shorter than the minimum chunk size
+fn fit_period_to_range(period: usize, range: &ChunkSizeRange) -> Option<usize> {
+    if (range.min..=range.max).contains(&period) {
+        return Some(period);
+    }
+    if period > range.max {
+        return (range.min..=range.max).rev().find(|&size| period.is_multiple_of(size));
+    }
+    (range.min..=range.max).find(|size| size.is_multiple_of(period))
+}
+
+/// Default chunk size for content without a usable repetition period
+fn default_for_kind(kind: ContentKind, range: &ChunkSizeRange) -> (usize, &'static str) {
+    match kind {
+        // High-entropy content rarely repeats, so short chunks keep the dictionary small
+        ContentKind::Compressed => (range.min, "default for already-compressed content"),
+        ContentKind::Text => (range.default, "default for text content"),
+        ContentKind::Image => (range.default, "default for image content"),
+        ContentKind::Binary => (range.default, "default for binary content"),
+        ContentKind::Empty => (range.default, "default for empty input"),
+    }
+}
+
+/// Suggests an initial chunk size for `data` within the configured range
+pub fn suggest_chunk_size(data: &[u8], range: &ChunkSizeRange) -> ChunkSizeSuggestion {
+    let content_kind = classify_content(data);
+    let prefix = &data[..data.len().min(SCAN_PREFIX_LEN)];
+
+    if let Some((period, score)) = detect_period(prefix, MAX_PERIOD) {
+        if let Some(chunk_size) = fit_period_to_range(period, range) {
+            return ChunkSizeSuggestion {
+                chunk_size,
+                content_kind,
+                period: Some(period),
+                reason: format!(
+                    "{} content repeats every {} bytes ({:.0}% autocorrelation)",
+                    content_kind, period, score * 100.0
+                ),
+            };
+        }
+    }
+
+    let (chunk_size, reason) = default_for_kind(content_kind, range);
+    ChunkSizeSuggestion {
+        chunk_size,
+        content_kind,
+        period: None,
+        reason: format!("no usable repetition period; {}", reason),
+    }
+}
+
+/// BLAKE3 of the searched data and the first and last chunk size tried
+type SearchKey = ([u8; 32], usize, usize);
+
+/// Results of `find_optimal_chunk_size`
+static OPTIMAL_CHUNK_SIZES: OnceLock<Mutex<HashMap<SearchKey, usize>>> = OnceLock::new();
+
+/// Compresses `data` with the dictionary codec at every chunk size in
+/// `performance.compression.optimal_chunk_search_range` and returns the size giving the
+/// smallest output. Results are cached, so asking again for the same data is free.
+pub fn find_optimal_chunk_size(data: &[u8]) -> usize {
+    let config = get_config();
+    let range = &config.compression.chunk_size_range;
+    let sizes = match config.performance.compression.optimal_chunk_search_range[..] {
+        [min, max] if 0 < min && min <= max => min..=max,
+        _ => range.min..=range.max,
+    };
+    let fallback = range.clamp(range.default).clamp(*sizes.start(), *sizes.end());
+    find_optimal_chunk_size_in(data, sizes, fallback)
+}
+
+/// `find_optimal_chunk_size` over `sizes`, returning `fallback` when every size ties
+fn find_optimal_chunk_size_in(data: &[u8], sizes: RangeInclusive<usize>, fallback: usize) -> usize {
+    let key = (*blake3::hash(data).as_bytes(), *sizes.start(), *sizes.end());
+    let cache = OPTIMAL_CHUNK_SIZES.get_or_init(Default::default);
+    if let Some(&size) = cache.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return size;
+    }
+    let trials: Vec<(usize, usize)> = sizes
+        .into_par_iter()
+        .filter_map(|size| compress_file_with_chunk_size(data, Codec::Dictionary, size).ok().map(|packed| (size, packed.len())))
+        .collect();
+    let best = match trials.iter().min_by_key(|&&(size, len)| (len, size)) {
+        // Tiny or incompressible input comes out the same whatever the chunk size
+        Some(&(size, len)) if trials.iter().any(|&(_, other)| other != len) => size,
+        _ => fallback,
+    };
+    cache.lock().unwrap_or_else(|e| e.into_inner()).insert(key, best);
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range() -> ChunkSizeRange {
+        ChunkSizeRange { min: 2, max: 8, default: 3 }
+    }
+
+    #[test]
+    fn test_eight_byte_period_detected() {
+        let record = [0x10u8, 0x00, 0xFF, 0x42, 0x07, 0x99, 0x00, 0x01];
+        let data: Vec<u8> = record.iter().cycle().take(4096).copied().collect();
+
+        let suggestion = suggest_chunk_size(&data, &range());
+        assert_eq!(suggestion.period, Some(8));
+        assert_eq!(8 % suggestion.chunk_size, 0, "chunk size must divide or match the period");
+        assert_eq!(suggestion.chunk_size, 8);
+    }
+
+    #[test]
+    fn test_search_finds_word_aligned_chunk_size() {
+        // 200 distinct 5-byte words in random order: only 5-byte chunks line up with them
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let words: Vec<[u8; 5]> = (0..200).map(|_| next().to_le_bytes()[..5].try_into().unwrap()).collect();
+        let data: Vec<u8> = (0..4000).flat_map(|_| words[next() as usize % words.len()]).collect();
+
+        assert_eq!(find_optimal_chunk_size_in(&data, 2..=8, 3), 5);
+        // Cached: a second search returns the same size
+        assert_eq!(find_optimal_chunk_size_in(&data, 2..=8, 3), 5);
+        // Too short for any size to matter
+        assert_eq!(find_optimal_chunk_size_in(b"ab", 2..=8, 3), 3);
+    }
+
+    #[test]
+    fn test_long_period_uses_divisor() {
+        let record: Vec<u8> = (0..12u8).map(|i| i.wrapping_mul(37)).collect();
+        let data: Vec<u8> = record.iter().cycle().take(12 * 200).copied().collect();
+
+        let suggestion = suggest_chunk_size(&data, &range());
+        assert_eq!(suggestion.period, Some(12));
+        assert_eq!(suggestion.chunk_size, 6);
+    }
+
+    #[test]
+    fn test_random_data_falls_back_to_default() {
+        let mut state = 0x2545F4914F6CDD1Du64;
+        let data: Vec<u8> = (0..4096)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 24) as u8
+            })
+            .collect();
+
+        let suggestion = suggest_chunk_size(&data, &range());
+        assert_eq!(suggestion.period, None);
+        assert_eq!(suggestion.chunk_size, 3);
+    }
+
+    #[test]
+    fn test_classify_content() {
+        assert_eq!(classify_content(b""), ContentKind::Empty);
+        assert_eq!(classify_content(b"\x89PNG\r\n\x1a\n...."), ContentKind::Image);
+        assert_eq!(classify_content(b"\x1F\x8B\x08\x00"), ContentKind::Compressed);
+        assert_eq!(classify_content(b"plain old text\n"), ContentKind::Text);
+        assert_eq!(classify_content(&[0u8, 1, 2, 3, 200, 201]), ContentKind::Binary);
+    }
+}
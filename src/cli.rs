@@ -1,32 +1,54 @@
-use crate::starknet_client::upload_data;
 use colored::*;
-use dialoguer::Input;
+use dialoguer::{Confirm, Input};
 use indicatif::{ProgressBar, ProgressStyle};
 use starknet::core::types::FieldElement;
 use std::path::Path;
 use std::time::Duration;
 use std::io::Write;
-use sha2::{Sha256, Digest};
+use std::future::Future;
+use std::sync::Arc;
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
-use crate::ascii_converter::convert_to_printable_ascii;
-use crate::mapping::{reconstruct_from_minimal_mapping, analyze_minimal_mapping};
+use crate::ascii_combinations::AsciiCombinationIterator;
+use crate::ascii_converter::{ascii_loss_percent, convert_file_to_ascii, ConversionStats};
+use crate::mapping::{reconstruct_from_mapping, analyze_minimal_mapping, create_mapping, create_stored_mapping, save_mapping, MappingFile, MappingFormat, MappingError};
 use hex;
-use crate::ipfs_client::pin_file_to_ipfs;
+use crate::ipfs_client::{fetch_file_from_ipfs, gateway_url, validate_cid, IpfsError, PinProgress};
 use std::fs;
+use serde::Serialize;
 use serde_json::{Value, json};
 use crate::config::get_config;
+use crate::chunk_detection::suggest_chunk_size;
+use crate::dictionary::{load_dictionary, resolve_dictionary_path, DictionaryError};
+use crate::sidecar::{compression_params_for, CompressionParams};
+use crate::flush::FlushTracker;
+use crate::generation_progress::{clear_progress, load_progress, save_progress, GenerationProgress};
+use crate::storage::{ChainUploader, PinataStorage, StarknetUploader, Storage};
+use crate::starknet_client::{OnChainUpload, UploadEstimate};
+use crate::hashing::{hash_data, HashMode};
+use crate::error::StarkSqueezeError;
+use crate::debug_files::DebugRun;
+use crate::output::{self, detail, status};
+use crate::progress::{IndicatifReporter, ProgressReporter};
+use crate::upload_index::{UploadIndex, UploadRecord};
+use crate::upload_manifest::{complete_manifest, manifest_path_for, resume_upload, save_manifest, UploadManifest, UploadStage};
 
 
 
 /// Prints a styled error message
 fn print_error(context: &str, error: &dyn std::fmt::Display) {
-    eprintln!("{} {}: {}", "Error".red().bold(), context, error);
+    output::error_line(format!("{} {}: {}", "Error".red().bold(), context, error));
 }
 
 /// Prints a styled info message
 fn print_info(label: &str, value: impl std::fmt::Display) {
-    println!("{} {}", label.blue().bold(), value);
+    status!("{} {}", label.blue().bold(), value);
+}
+
+/// Flush tracker for a generator producing `chunk_size` combinations per batch
+fn flush_tracker(chunk_size: usize) -> FlushTracker {
+    let generation = &get_config().dictionary.ascii_combinations.generation;
+    FlushTracker::new(generation.flush_strategy, chunk_size, generation.flush_interval)
 }
 
 /// Prompts the user for string input with optional validation
@@ -45,196 +67,607 @@ async fn prompt_string(prompt: &str) -> String {
     }
 }
 
-/// Uploads a file with compression metadata
-pub async fn upload_data_cli(file_path_arg: Option<std::path::PathBuf>) {
+/// Result of a completed upload, returned to callers instead of being printed
+#[derive(Debug, Clone, Serialize)]
+pub struct UploadSummary {
+    pub upload_id: FieldElement,
+    pub uri: String,
+    pub cid: Option<String>,
+    pub file_type: String,
+    pub original_size: usize,
+    pub encoded_size: usize,
+    pub compressed_size: usize,
+    pub compression_ratio: u64,
+    pub ascii_converted_bytes: usize,
+    pub hash_mode: HashMode,
+    /// `None` when the Starknet step was skipped
+    pub tx_hash: Option<FieldElement>,
+    /// Resumable manifest; removed once the upload has been submitted
+    pub manifest_path: std::path::PathBuf,
+    /// The metadata submitted on-chain, or that would be
+    #[serde(skip)]
+    pub manifest: UploadManifest,
+}
+
+/// Returns the configured size above which files are not read into memory without confirmation
+fn large_file_threshold() -> u64 {
+    get_config().file_processing.large_file_warning_mb.saturating_mul(1024 * 1024)
+}
+
+/// Decides the in-memory size limit for reading `file_path`.
+/// Returns `None` (no limit) when large files are allowed or the user confirms the warning.
+fn size_limit_for(file_path: &Path, allow_large: bool, interactive: bool) -> Option<u64> {
+    if allow_large {
+        return None;
+    }
+    let threshold = large_file_threshold();
+    let size = match std::fs::metadata(file_path) {
+        Ok(metadata) => metadata.len(),
+        // Missing files are reported by the pipeline itself
+        Err(_) => return Some(threshold),
+    };
+    if size <= threshold || !interactive {
+        return Some(threshold);
+    }
+
+    eprintln!("{}", StarkSqueezeError::FileTooLarge { size, threshold }.to_string().yellow());
+    let confirmed = Confirm::new()
+        .with_prompt("Read the whole file into memory anyway?")
+        .default(false)
+        .interact()
+        .unwrap_or(false);
+    if confirmed { None } else { Some(threshold) }
+}
+
+/// Fails with `FileTooLarge` if the file at `path` exceeds `size_limit`, without reading it
+pub(crate) fn check_file_size(path: &Path, size_limit: Option<u64>) -> Result<u64, StarkSqueezeError> {
+    let size = std::fs::metadata(path)?.len();
+    match size_limit {
+        Some(threshold) if size > threshold => Err(StarkSqueezeError::FileTooLarge { size, threshold }),
+        _ => Ok(size),
+    }
+}
+
+/// Upload size cap from `validation.file.max_size_mb`, or none when the check is disabled
+fn max_upload_size(validation: &crate::config::FileValidationConfig, disable_file_size_limit: bool) -> Option<u64> {
+    (!disable_file_size_limit).then(|| (validation.max_size_mb as u64).saturating_mul(1024 * 1024))
+}
+
+/// Mode and guards applied by the upload pipeline
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PipelineOptions {
+    /// Refuse files larger than this many bytes
+    pub size_limit: Option<u64>,
+    /// Reject files larger than this many bytes outright, from `validation.file.max_size_mb`
+    pub max_size: Option<u64>,
+    /// Abort when ASCII conversion alters more than this percentage of bytes
+    pub max_ascii_loss: Option<f64>,
+    /// Compress the file's bytes directly, skipping ASCII conversion
+    pub raw: bool,
+    /// Write the intermediate binary string into this directory
+    pub binary_string_dir: Option<std::path::PathBuf>,
+    /// Don't pin to IPFS
+    pub skip_ipfs: bool,
+    /// Stop before the Starknet submission, leaving a resumable manifest
+    pub skip_starknet: bool,
+    /// Dictionary codec chunk size; the configured default when unset
+    pub chunk_size: Option<ChunkSizeChoice>,
+    /// Where intermediate artifacts go; disabled unless debug files are enabled
+    pub debug_run: DebugRun,
+    /// Build the manifest in memory only: nothing is written, pinned or submitted
+    pub dry_run: bool,
+}
+
+/// Fails with `AsciiLossExceeded` if more than `max_ascii_loss` percent of bytes were converted
+pub(crate) fn check_ascii_loss(loss_percent: f64, max_ascii_loss: Option<f64>) -> Result<(), StarkSqueezeError> {
+    match max_ascii_loss {
+        Some(max_percent) if loss_percent > max_percent => {
+            Err(StarkSqueezeError::AsciiLossExceeded { loss_percent, max_percent })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Options for the upload command
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UploadCliOptions {
+    /// Read files above `large_file_warning_mb` without asking
+    pub allow_large: bool,
+    /// Don't pin to IPFS
+    pub skip_ipfs: bool,
+    /// Don't submit to Starknet; the upload can be finished later with `resume-upload`
+    pub skip_starknet: bool,
+    /// Upload files above `validation.file.max_size_mb`
+    pub disable_file_size_limit: bool,
+    /// Chunk size for the dictionary codec instead of `compression.chunk_size_range.default`
+    pub chunk_size: Option<ChunkSizeChoice>,
+}
+
+/// A `--chunk-size` value: a fixed size, or `auto` to search for the best one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkSizeChoice {
+    Fixed(usize),
+    Auto,
+}
+
+impl std::str::FromStr for ChunkSizeChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "auto" {
+            return Ok(ChunkSizeChoice::Auto);
+        }
+        s.parse().map(ChunkSizeChoice::Fixed).map_err(|_| format!("expected a chunk size or 'auto', got '{}'", s))
+    }
+}
+
+/// Uploads a file with compression metadata and returns a summary of the upload.
+/// Files above `large_file_warning_mb` are refused unless `allow_large` is set or the user confirms.
+pub async fn upload_data_cli(file_path_arg: Option<std::path::PathBuf>, allow_large: bool) -> Result<UploadSummary, StarkSqueezeError> {
+    let options = UploadCliOptions { allow_large, ..Default::default() };
+    upload_data_cli_with(file_path_arg, options, &PinataStorage, &StarknetUploader).await
+}
+
+/// `upload_data_cli` with the pinning and on-chain steps supplied by the caller.
+/// Given a file path, it never prompts.
+pub async fn upload_data_cli_with(
+    file_path_arg: Option<std::path::PathBuf>,
+    upload_options: UploadCliOptions,
+    storage: &impl Storage,
+    uploader: &impl ChainUploader,
+) -> Result<UploadSummary, StarkSqueezeError> {
     // Use the provided file path or prompt for one
+    let interactive = file_path_arg.is_none();
     let file_path = match file_path_arg {
-        Some(path) => path.to_string_lossy().to_string(),
-        None => prompt_string("Enter the file path").await,
+        Some(path) => path,
+        None => std::path::PathBuf::from(prompt_string("Enter the file path").await),
     };
+    let options = pipeline_options(&file_path, upload_options, interactive);
 
-    // Validate the file path with async file operations
-    let path = std::path::Path::new(&file_path);
-    if !tokio::fs::metadata(&path).await.map(|m| m.is_file()).unwrap_or(false) {
-        print_error("Invalid file path", &format!("File does not exist or is not a file: {}", file_path));
-        return;
+    let summary = upload_file_with(
+        &file_path,
+        options,
+        |data, filename, progress| storage.pin_with_progress(data, filename, progress),
+        |manifest| async move { uploader.submit(&manifest).await },
+    ).await?;
+    record_upload(&file_path, &summary);
+    Ok(summary)
+}
+
+/// Runs the upload pipeline without pinning or submitting, then asks `uploader` what
+/// submitting the result would cost. Nothing is broadcast or written to disk.
+pub async fn estimate_upload_cli_with(
+    file_path: &Path,
+    upload_options: UploadCliOptions,
+    storage: &impl Storage,
+    uploader: &impl ChainUploader,
+) -> Result<UploadEstimate, StarkSqueezeError> {
+    let options = PipelineOptions {
+        skip_ipfs: true,
+        skip_starknet: true,
+        dry_run: true,
+        binary_string_dir: None,
+        debug_run: DebugRun::Disabled,
+        ..pipeline_options(file_path, upload_options, false)
+    };
+    let summary = upload_file_with(
+        file_path,
+        options,
+        |data, filename, progress| storage.pin_with_progress(data, filename, progress),
+        |manifest| async move { uploader.submit(&manifest).await },
+    ).await?;
+    uploader
+        .estimate(&summary.manifest)
+        .await
+        .map_err(|e| StarkSqueezeError::UploadError(format!("Fee estimation failed: {}", e)))
+}
+
+/// Prints an upload estimate, warning when the calldata will not fit in one transaction
+pub fn print_upload_estimate(estimate: &UploadEstimate) {
+    print_info("Calldata:", format!("{} felts", estimate.calldata_felts));
+    print_info("Transactions:", estimate.transactions);
+    if estimate.exceeds_cap() {
+        status!(
+            "{}",
+            format!(
+                "\u{26A0}\u{FE0F} Calldata exceeds max_calldata_felts ({}); it will be split across {} transactions",
+                estimate.max_calldata_felts, estimate.transactions
+            )
+            .yellow()
+        );
     }
+    output::result(
+        format!("{} {} {}", "Estimated fee:".blue().bold(), estimate.overall_fee, estimate.fee_unit),
+        format!("{} {}", estimate.overall_fee, estimate.fee_unit),
+    );
+}
 
-    // Read file contents and generate hash asynchronously
-    let mut file = match File::open(&file_path).await {
-        Ok(f) => f,
-        Err(e) => {
-            print_error("Failed to open file", &e);
-            return;
+/// Pipeline options for an upload of `file_path` as configured
+fn pipeline_options(file_path: &Path, upload_options: UploadCliOptions, interactive: bool) -> PipelineOptions {
+    PipelineOptions {
+        size_limit: size_limit_for(file_path, upload_options.allow_large, interactive),
+        max_size: max_upload_size(&get_config().validation.file, upload_options.disable_file_size_limit),
+        max_ascii_loss: get_config().file_processing.ascii_conversion.max_loss_percent,
+        raw: get_config().file_processing.raw_mode,
+        binary_string_dir: crate::debug_files::binary_string_dir(&get_config().debug),
+        skip_ipfs: upload_options.skip_ipfs,
+        skip_starknet: upload_options.skip_starknet,
+        chunk_size: upload_options.chunk_size,
+        debug_run: DebugRun::start(),
+        dry_run: false,
+    }
+}
+
+/// Clamps a `--chunk-size` override to `compression.chunk_size_range`, saying so when it moves
+fn clamped_chunk_size(requested: usize) -> usize {
+    let range = &get_config().compression.chunk_size_range;
+    let chunk_size = range.clamp(requested);
+    if chunk_size != requested {
+        status!(
+            "{}",
+            format!("Chunk size {} is outside {}..={}; using {}", requested, range.min, range.max, chunk_size).yellow()
+        );
+    }
+    chunk_size
+}
+
+/// Chunk size for `data` under `choice`: `auto` searches for the best, a fixed size is clamped
+fn resolve_chunk_size(choice: ChunkSizeChoice, data: &[u8]) -> usize {
+    match choice {
+        ChunkSizeChoice::Fixed(requested) => clamped_chunk_size(requested),
+        ChunkSizeChoice::Auto => {
+            let chunk_size = crate::chunk_detection::find_optimal_chunk_size(data);
+            print_info("Chunk size (auto):", chunk_size);
+            chunk_size
         }
-    };
+    }
+}
 
-    let mut buffer = Vec::new();
-    if let Err(e) = file.read_to_end(&mut buffer).await {
-        print_error("Failed to read file", &e);
+/// Appends a finished upload to the local index when `upload_index` is enabled
+fn record_upload(file_path: &Path, summary: &UploadSummary) {
+    let local = &get_config().storage.local;
+    if !local.upload_index {
         return;
     }
-    std::fs::write("debug_original.bin", &buffer).expect("Failed to write debug_original.bin");
+    let record = UploadRecord {
+        file_name: file_path.file_name().unwrap_or(file_path.as_os_str()).to_string_lossy().into_owned(),
+        hash: summary.uri.clone(),
+        cid: summary.cid.clone(),
+        tx_hash: summary.tx_hash.map(|tx| format!("{:#x}", tx)).unwrap_or_else(|| "-".to_string()),
+        original_size: summary.original_size as u64,
+        compressed_size: summary.compressed_size as u64,
+        uploaded_at: chrono::Utc::now().to_rfc3339(),
+    };
+    if let Err(e) = UploadIndex::open(&local.upload_index_path).and_then(|index| index.insert(&record)) {
+        eprintln!("Warning: failed to record upload in {}: {}", local.upload_index_path, e);
+    }
+}
 
-    // Convert to printable ASCII with detailed tracking
-    let (ascii_buffer, ascii_stats) = match convert_to_printable_ascii(&buffer) {
-        Ok(result) => result,
+/// Lists past uploads from the local index, optionally filtered by `query`
+pub fn history_cli(query: Option<&str>) {
+    let local = &get_config().storage.local;
+    if !local.upload_index {
+        status!("{}", "Upload index is disabled; set storage.local.upload_index in config.json to record uploads".yellow());
+        return;
+    }
+    let records = match UploadIndex::open(&local.upload_index_path).and_then(|index| index.search(query)) {
+        Ok(records) => records,
         Err(e) => {
-            print_error("Failed to convert file to ASCII", &e);
+            print_error("Failed to read upload index", &e);
             return;
         }
     };
-    std::fs::write("debug_ascii.bin", &ascii_buffer).expect("Failed to write debug_ascii.bin");
+    if records.is_empty() {
+        status!("No uploads found");
+        return;
+    }
+    for record in &records {
+        let cid = record.cid.as_deref().unwrap_or("-");
+        output::result(
+            format!(
+                "{} {} {} {} {} {} {} {} {} → {} bytes",
+                record.uploaded_at,
+                record.file_name.bold(),
+                "URI:".blue(),
+                record.hash,
+                "CID:".blue(),
+                cid,
+                "Tx:".blue(),
+                record.tx_hash,
+                record.original_size,
+                record.compressed_size
+            ),
+            format!("{}\t{}\t{}\t{}", record.file_name, record.hash, cid, record.tx_hash),
+        );
+    }
+}
 
-    // Convert ASCII buffer to binary string
-    let binary_string: String = ascii_buffer.iter()
-        .map(|&byte| format!("{:08b}", byte))
-        .collect();
-    std::fs::write("debug_binary_string.txt", &binary_string).expect("Failed to write debug_binary_string.txt");
+/// Runs the upload pipeline, delegating IPFS pinning to `pin` and the Starknet call to `submit`.
+/// `pin` is given a callback to report `(bytes_sent, total)` through the progress bar.
+pub(crate) async fn upload_file_with<P, PFut, S, SFut>(
+    file_path: &Path,
+    options: PipelineOptions,
+    pin: P,
+    submit: S,
+) -> Result<UploadSummary, StarkSqueezeError>
+where
+    P: FnOnce(Vec<u8>, String, PinProgress) -> PFut,
+    PFut: Future<Output = Result<String, IpfsError>>,
+    S: FnOnce(UploadManifest) -> SFut,
+    SFut: Future<Output = Result<FieldElement, Box<dyn std::error::Error + Send + Sync>>>,
+{
+    // Validate the file path with async file operations. The path is only ever
+    // converted lossily for display, so non-UTF-8 names still open the right file.
+    if !tokio::fs::metadata(file_path).await.map(|m| m.is_file()).unwrap_or(false) {
+        return Err(StarkSqueezeError::InvalidInput(format!("File does not exist or is not a file: {}", file_path.display())));
+    }
+    if let Some(max_size) = options.max_size {
+        let size = tokio::fs::metadata(file_path).await?.len();
+        if size > max_size {
+            return Err(StarkSqueezeError::FileExceedsMaxSize { size, max_size });
+        }
+    }
+    check_file_size(file_path, options.size_limit)?;
+
+    // Read file contents and generate hash asynchronously
+    let mut file = File::open(file_path).await?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer).await?;
+    let debug_run = &options.debug_run;
+    debug_run.write("debug_original.bin", &buffer);
+
+    // Raw mode compresses the file's bytes as-is; otherwise convert to printable
+    // ASCII and expand each byte into its 8-character binary string
+    let (encoded, ascii_stats) = if options.raw {
+        let stats = ConversionStats { total_bytes: buffer.len(), ..Default::default() };
+        (buffer.clone(), stats)
+    } else {
+        let (ascii_buffer, ascii_stats) = convert_file_to_ascii(&buffer)
+            .map_err(|e| StarkSqueezeError::ConversionError(e.to_string()))?;
+        let loss_percent = if ascii_stats.total_bytes == 0 {
+            0.0
+        } else {
+            ascii_stats.converted_bytes as f64 / ascii_stats.total_bytes as f64 * 100.0
+        };
+        check_ascii_loss(loss_percent, options.max_ascii_loss)?;
+        debug_run.write("debug_ascii.bin", &ascii_buffer);
+
+        // Convert ASCII buffer to binary string
+        let binary_string: String = ascii_buffer.iter()
+            .map(|&byte| format!("{:08b}", byte))
+            .collect();
+        if let Some(dir) = &options.binary_string_dir {
+            let file_name = file_path.file_name().map(|n| n.to_string_lossy()).unwrap_or("upload".into());
+            match crate::debug_files::write_binary_string(dir, &file_name, &binary_string) {
+                Ok(artifact) => print_info("Binary string:", artifact.display()),
+                Err(e) => eprintln!("Warning: failed to write binary string artifact: {}", e),
+            }
+        }
+        (binary_string.into_bytes(), ascii_stats)
+    };
 
     let config = get_config();
     let spinner = ProgressBar::new_spinner();
+    output::hide_if_quiet(&spinner);
     let tick_strings: Vec<&str> = config.cli.progress.spinner_style.tick_strings.iter().map(|s| s.as_str()).collect();
-    spinner.set_style(
-        ProgressStyle::default_spinner()
-            .tick_strings(&tick_strings)
-            .template(&config.cli.progress.spinner_style.template)
-            .unwrap(),
-    );
+    let spinner_style = ProgressStyle::default_spinner()
+        .tick_strings(&tick_strings)
+        .template(&config.cli.progress.spinner_style.template)
+        .unwrap();
+    spinner.set_style(spinner_style.clone());
     spinner.enable_steady_tick(Duration::from_millis(config.cli.progress.spinner_style.steady_tick_ms));
 
     // Compress the data
-    let bytes = binary_string.as_bytes();
-    let packed_bytes = match crate::compression::compress_file(&bytes) {
-        Ok(packed) => packed,
-        Err(e) => {
-            print_error("Failed in compression step", &e);
-            return;
-        }
+    let chunk_size = match options.chunk_size {
+        Some(choice) => resolve_chunk_size(choice, &encoded),
+        None => crate::compression::configured_chunk_size(),
     };
+    let outcome = crate::compression::compress_bytes(
+        &encoded,
+        crate::compression::CompressOptions { chunk_size, ..Default::default() },
+    )?;
+    detail!(output::VERBOSE, "Codec: {}", outcome.codec);
     // Save packed_bytes to file, use for hashing, IPFS, etc.
-    std::fs::write("debug_packed.bin", &packed_bytes).expect("Failed to write debug_packed.bin");
+    debug_run.write("debug_packed.bin", &outcome.compressed);
 
     // Calculate sizes and ratios
-    let original_size = binary_string.len() as u64;
-    let compressed_size = packed_bytes.len() as u64;
-    let compression_ratio = ((compressed_size as f64 / original_size as f64) * 100.0) as u64;
+    let original_size = outcome.original_size;
+    let compressed_size = outcome.compressed_size;
+    let compression_ratio = (outcome.ratio * 100.0) as u64;
+    let packed_bytes = outcome.compressed;
 
-    // Generate hash from the compressed data
-    let mut hasher = Sha256::new();
-    // Convert encoded_data (Vec<u16>) to Vec<u8> for hashing and other uses
-    let encoded_data_bytes: Vec<u8> = packed_bytes.iter().flat_map(|x| x.to_be_bytes()).collect();
-    hasher.update(&encoded_data_bytes);
-    let hash = hasher.finalize();
+    // Generate hash from the compressed data (parallel BLAKE3 for large files when enabled)
+    let hash_output = hash_data(&packed_bytes, &config.upload.hash);
+    let hash = hash_output.digest;
 
     // Use a short hash (first 8 bytes, hex-encoded) as the URI
-    let short_hash = hex::encode(&hash[..8]); // 16 hex chars, fits in felt
-    let uri = &short_hash;
+    let uri = hex::encode(&hash[..8]); // 16 hex chars, fits in felt
 
     // Convert first 16 bytes of hash to FieldElement (for upload_id, if needed)
-    let upload_id = match FieldElement::from_byte_slice_be(&hash[..16]) {
-        Ok(id) => id,
-        Err(e) => {
-            print_error("Failed to generate upload ID", &e);
-            return;
-        }
-    };
+    let upload_id = FieldElement::from_byte_slice_be(&hash[..16])
+        .map_err(|e| StarkSqueezeError::InvalidInput(format!("Failed to generate upload ID: {}", e)))?;
 
-    // Automatically determine file size and type
-    let file_type = match Path::new(&file_path).extension() {
-        Some(ext) => {
-            let ext_str = ext.to_string_lossy().to_string();
-            if ext_str.is_empty() {
-                print_error("Invalid file type", &"File extension is empty");
-                return;
-            }
-            ext_str
-        },
-        None => {
-            print_error("Failed to determine file type", &"No file extension found");
-            return;
-        }
+    // Automatically determine file size and type, sniffing the content when the
+    // name has no extension
+    let file_type = match file_path.extension().filter(|ext| !ext.is_empty()) {
+        Some(ext) => ext.to_string_lossy().to_string(),
+        None => crate::utils::detect_file_type(&buffer),
     };
 
-    spinner.set_message("Uploading data...".yellow().to_string());
-    
     // Prepare data for upload - using minimal data to avoid calldata limits
     let compressed_by = if compression_ratio <= 100 { 
         (100 - compression_ratio) as u8 
     } else { 
         0 
     };
-    
-    // Remove the call to create_minimal_mapping and any code that tries to use or save a minimal mapping in upload_data_cli.
-    let chunk_mappings = vec![FieldElement::from(0u32)]; // Placeholder
-    let chunk_values = vec![0u8]; // Placeholder
-    let byte_mappings = vec![0u8]; // Placeholder
-    let byte_values = vec![FieldElement::from(0u32)]; // Placeholder
-    let reconstruction_steps = vec![FieldElement::from(0u32)]; // Placeholder
-    let metadata = vec![FieldElement::from(0u32)]; // Placeholder
-    
-    if let Err(e) = upload_data(
-        &uri,
-        &file_type,
-        compressed_by,
-        original_size as usize,
-        compressed_size as usize,
-        8, // chunk_size
-        chunk_mappings,
-        chunk_values,
-        byte_mappings,
-        byte_values,
-        reconstruction_steps,
-        metadata,
-    ).await {
-        print_error("Failed to upload data", &e);
-        return;
-    }
 
-    spinner.finish_with_message(config.ui.messages.upload_complete.green().to_string());
-
-    // IPFS Pinning after upload completion
-    println!("\n{}", "🔗 Starting IPFS pinning...".blue().bold());
-    
-    match pin_file_to_ipfs(&packed_bytes, &format!("{}.compressed", file_path)).await {
-        Ok(ipfs_cid) => {
-            println!("✅ Pinned to IPFS: {}", ipfs_cid.green().bold());
-            println!("🌐 IPFS Gateway: https://gateway.pinata.cloud/ipfs/{}", ipfs_cid);
+    // IPFS Pinning happens before the on-chain submission so the CID can be recorded
+    spinner.set_message("Pinning to IPFS...".yellow().to_string());
+    let pinned = if options.skip_ipfs {
+        spinner.println("⏭️  IPFS pinning skipped");
+        Ok(None)
+    } else {
+        // Show bytes sent while the upload runs, then go back to the spinner
+        spinner.set_style(
+            ProgressStyle::default_bar()
+                .template(&config.cli.progress.bar_style.template)
+                .unwrap()
+                .progress_chars(&config.cli.progress.bar_style.progress_chars),
+        );
+        spinner.set_length(packed_bytes.len() as u64);
+        let bar = spinner.clone();
+        let progress: PinProgress = Arc::new(move |sent, total| {
+            bar.set_length(total);
+            bar.set_position(sent);
+        });
+        let pinned = pin(packed_bytes.clone(), format!("{}.compressed", file_path.display()), progress).await;
+        spinner.set_style(spinner_style);
+        pinned.map(Some)
+    };
+    let ipfs_cid = match pinned {
+        Ok(None) => None,
+        Ok(Some(ipfs_cid)) => {
+            spinner.println(format!("✅ Pinned to IPFS: {}", ipfs_cid.green().bold()));
+            spinner.println(format!("🌐 IPFS Gateway: {}", gateway_url(&config.storage.ipfs.gateway, &ipfs_cid)));
+            Some(ipfs_cid)
         }
         Err(e) => {
-            println!("❌ IPFS Pin Failed: {}", e.to_string().red().bold());
-            println!("💡 Check your PINATA_JWT token in .env file");
+            // Without a CID there is nothing to retrieve, so stop before writing a manifest or submitting
+            spinner.println(format!("❌ IPFS Pin Failed: {}", e.to_string().red().bold()));
+            spinner.println("💡 Check your PINATA_JWT token in .env file");
+            spinner.finish_and_clear();
+            return Err(StarkSqueezeError::UploadError(format!("IPFS pin failed: {}", e)));
+        }
+    };
+
+    // Record everything the Starknet step needs, so an interrupted run can be resumed
+    let manifest_path = manifest_path_for(file_path);
+    let mut manifest = UploadManifest {
+        file_path: file_path.to_string_lossy().into_owned(),
+        uri: uri.clone(),
+        file_type: file_type.clone(),
+        hash: hex::encode(hash),
+        ipfs_cid: ipfs_cid.clone(),
+        compressed_by,
+        original_size: original_size as usize,
+        compressed_size: compressed_size as usize,
+        chunk_size,
+        raw: options.raw,
+        stage: UploadStage::Pinned,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    if !options.dry_run {
+        if let Err(e) = save_manifest(&manifest, &manifest_path) {
+            spinner.println(format!("⚠️  Failed to write upload manifest: {}", e));
         }
     }
 
-    // Display results
-    print_info("Upload ID:", upload_id);
-    let original_mb = buffer.len() as f64 / 1_000_000.0;
-    let compressed_mb = compressed_size as f64 / 1_000_000.0;
-    let reduction = 100.0 - compression_ratio as f64;
+    let tx_hash = if options.dry_run {
+        None
+    } else if options.skip_starknet {
+        spinner.println(format!(
+            "⏭️  Starknet submission skipped (finish with: stark_squeeze resume-upload {})",
+            manifest_path.display()
+        ));
+        None
+    } else {
+        spinner.set_message("Uploading data...".yellow().to_string());
+        let tx_hash = match submit(manifest.clone()).await {
+            Ok(tx_hash) => tx_hash,
+            Err(e) => {
+                spinner.finish_and_clear();
+                return Err(StarkSqueezeError::UploadError(format!(
+                    "{} (resume with: stark_squeeze resume-upload {})", e, manifest_path.display()
+                )));
+            }
+        };
+        complete_manifest(&mut manifest, &manifest_path)?;
+        Some(tx_hash)
+    };
+
+    spinner.finish_with_message(config.ui.messages.upload_complete.green().to_string());
+
+    Ok(UploadSummary {
+        upload_id,
+        uri,
+        cid: ipfs_cid,
+        file_type,
+        original_size: buffer.len(),
+        encoded_size: original_size as usize,
+        compressed_size: compressed_size as usize,
+        compression_ratio,
+        ascii_converted_bytes: ascii_stats.converted_bytes,
+        hash_mode: hash_output.mode,
+        tx_hash,
+        manifest_path,
+        manifest,
+    })
+}
+
+/// Prints the results of a completed upload
+pub fn print_upload_summary(summary: &UploadSummary) {
+    // In quiet mode only the URI is printed, for scripts to pick up
+    output::result(format!("{} {}", "URI:".blue().bold(), summary.uri), &summary.uri);
+    print_info("Upload ID:", summary.upload_id);
+    if let Some(tx_hash) = summary.tx_hash {
+        print_info("Transaction hash:", format!("{:#x}", tx_hash));
+    }
+    if let Some(cid) = &summary.cid {
+        print_info("IPFS CID:", cid);
+    }
+    let original_mb = summary.original_size as f64 / 1_000_000.0;
+    let compressed_mb = summary.compressed_size as f64 / 1_000_000.0;
+    let reduction = 100.0 - summary.compression_ratio as f64;
     print_info("File Size:", format!("Reduced {:.1}% (from {:.2}MB to {:.2}MB)", 
         reduction, original_mb, compressed_mb));
-    let ratio_colored = if compression_ratio > 100 {
-        format!("{:.1}%", compression_ratio).red().bold()
+    let ratio_colored = if summary.compression_ratio > 100 {
+        format!("{:.1}%", summary.compression_ratio).red().bold()
     } else {
-        format!("{:.1}%", compression_ratio).green().bold()
+        format!("{:.1}%", summary.compression_ratio).green().bold()
     };
     print_info("Compression Ratio:", ratio_colored);
+    print_info("Hash Mode:", summary.hash_mode);
     
-    if ascii_stats.converted_bytes > 0 {
+    if summary.ascii_converted_bytes > 0 {
         print_info("ASCII Conversion:", format!("{} bytes converted ({:.1}%)", 
-            ascii_stats.converted_bytes, 
-            (ascii_stats.converted_bytes as f64 / ascii_stats.total_bytes as f64) * 100.0));
+            summary.ascii_converted_bytes, 
+            (summary.ascii_converted_bytes as f64 / summary.original_size as f64) * 100.0));
+    }
+}
+
+/// Finishes an interrupted upload from its manifest, submitting only to Starknet
+pub async fn resume_upload_cli(manifest_path_arg: Option<std::path::PathBuf>) {
+    let manifest_path = match manifest_path_arg {
+        Some(path) => path,
+        None => std::path::PathBuf::from(prompt_string("Enter the upload manifest path (e.g., file.png.upload.json)").await),
+    };
+
+    status!("{}", "🔁 Resuming upload (skipping compression and IPFS pinning)...".blue().bold());
+    match resume_upload(&manifest_path, |manifest| async move { StarknetUploader.submit(&manifest).await.map(|_| ()) }).await {
+        Ok(manifest) => {
+            output::result("✅ Upload resumed and submitted to Starknet".green().bold(), &manifest.uri);
+            print_info("URI:", &manifest.uri);
+            if let Some(cid) = &manifest.ipfs_cid {
+                print_info("IPFS CID:", cid);
+            }
+        }
+        Err(e) => print_error("Failed to resume upload", &e),
     }
 }
 
-/// Reconstructs a file from the minimal mapping file
+/// Reconstructs a file from a minimal or complete mapping file
 pub async fn reconstruct_from_mapping_cli() {
     let mapping_file_path = prompt_string("Enter the mapping file path (e.g., file.png.map)").await;
     let output_file_path = prompt_string("Enter the output file path (e.g., file.png)").await;
 
-    match reconstruct_from_minimal_mapping(&mapping_file_path, &output_file_path) {
-        Ok(_) => println!("✅ File reconstructed successfully: {}", output_file_path),
+    match reconstruct_from_mapping(&mapping_file_path, &output_file_path) {
+        Ok(report) => {
+            output::result(format!("✅ File reconstructed successfully: {}", output_file_path), &output_file_path);
+            print_info("Mapping format:", report.format);
+            if report.original_hash_verified == Some(false) {
+                status!("{}", "⚠️  Output does not match the recorded original hash (lossy ASCII conversion)".yellow());
+            }
+        }
         Err(e) => print_error("Failed to reconstruct file", &e),
     }
 }
@@ -244,15 +677,15 @@ pub async fn analyze_mapping_only_cli() {
     let mapping_file_path = prompt_string("Enter the mapping file path (e.g., file.png.map)").await;
 
     match analyze_minimal_mapping(&mapping_file_path) {
-        Ok(_) => println!("\n✅ Analysis complete!"),
+        Ok(_) => status!("\n✅ Analysis complete!"),
         Err(e) => print_error("Failed to analyze mapping file", &e),
     }
 }
 
 /// Generates ASCII character combinations and saves them to a file
 pub async fn generate_ascii_combinations_cli() {
-    println!("{}", "🔤 ASCII Combination Generator".blue().bold());
-    println!();
+    status!("{}", "🔤 ASCII Combination Generator".blue().bold());
+    status!();
     
     // Get parameters from user
     let length: usize = match Input::<String>::new()
@@ -263,7 +696,7 @@ pub async fn generate_ascii_combinations_cli() {
             Err(_) => 10,
     };
     
-    let start_index: u64 = match Input::<String>::new()
+    let start_index: u128 = match Input::<String>::new()
         .with_prompt("Enter starting index (default: 0)")
         .default("0".to_string())
         .interact_text() {
@@ -272,7 +705,13 @@ pub async fn generate_ascii_combinations_cli() {
     };
     
     // Calculate total possible combinations
-    let total_combinations = 128u64.pow(length as u32);
+    let total_combinations = match combination_space(length) {
+        Ok(total) => total,
+        Err(e) => {
+            print_error("Invalid combination length", &e);
+            return;
+        }
+    };
     
     // Ask if user wants to generate all combinations
     let generate_all = match Input::<String>::new()
@@ -284,7 +723,7 @@ pub async fn generate_ascii_combinations_cli() {
     };
     
     let count = if generate_all {
-        total_combinations.saturating_sub(start_index) as usize
+        total_combinations.saturating_sub(start_index).min(usize::MAX as u128) as usize
     } else {
         match Input::<String>::new()
             .with_prompt("Enter number of combinations to generate (default: 1000)")
@@ -302,73 +741,53 @@ pub async fn generate_ascii_combinations_cli() {
             Ok(s) => s,
             Err(_) => "ascii_combinations.txt".to_string(),
     };
+
+    // An earlier run into the same file that did not finish can be continued
+    let resume = match load_progress(&output_file) {
+        Some(progress) if progress.length == length && progress.remaining() > 0 => {
+            let prompt = format!(
+                "Found an interrupted run at index {} ({} left). Resume it?",
+                progress.next_index,
+                progress.remaining()
+            );
+            Confirm::new().with_prompt(prompt).default(true).interact().unwrap_or(false).then_some(progress)
+        }
+        _ => None,
+    };
+    let (start_index, count) = match resume {
+        Some(progress) => (progress.next_index, progress.remaining() as usize),
+        None => (start_index, count),
+    };
     
-    println!();
-    println!("{}", "📊 Generation Parameters:".yellow().bold());
+    status!();
+    status!("{}", "📊 Generation Parameters:".yellow().bold());
     print_info("Length", length);
     print_info("Starting index", start_index);
     print_info("Count", count);
     print_info("Output file", &output_file);
     print_info("Total possible combinations", total_combinations);
     
-    if generate_all {
-        let estimated_size_gb = (count as f64 * (length as f64 + 20.0)) / (1024.0 * 1024.0 * 1024.0);
-        println!("{}", "⚠️  WARNING: This will generate a very large file!".red().bold());
-        print_info("Estimated file size", format!("{:.2} GB", estimated_size_gb));
-        print_info("Estimated time", "Several hours to days depending on your system");
-        
-        // Calculate more detailed estimates
-        let combinations_per_second = 1_000_000; // Conservative estimate
-        let estimated_seconds = count as f64 / combinations_per_second as f64;
-        let estimated_hours = estimated_seconds / 3600.0;
-        let estimated_days = estimated_hours / 24.0;
-        
-        println!();
-        println!("{}", "📊 Detailed Estimates:".yellow().bold());
-        print_info("Total combinations to generate", count);
-        print_info("Combinations per second (estimate)", combinations_per_second);
-        print_info("Estimated time (seconds)", format!("{:.0}", estimated_seconds));
-        print_info("Estimated time (hours)", format!("{:.1}", estimated_hours));
-        print_info("Estimated time (days)", format!("{:.1}", estimated_days));
-        
-        // File size breakdown
-        let bytes_per_combination = length as f64 + 20.0; // combination + index + formatting
-        let total_bytes = count as f64 * bytes_per_combination;
-        let size_mb = total_bytes / (1024.0 * 1024.0);
-        let size_gb = total_bytes / (1024.0 * 1024.0 * 1024.0);
-        let size_tb = total_bytes / (1024.0 * 1024.0 * 1024.0 * 1024.0);
-        
-        print_info("Bytes per combination", format!("{:.1}", bytes_per_combination));
-        print_info("Total bytes", total_bytes as u64);
-        print_info("File size (MB)", format!("{:.1}", size_mb));
-        print_info("File size (GB)", format!("{:.2}", size_gb));
-        if size_tb > 1.0 {
-            print_info("File size (TB)", format!("{:.2}", size_tb));
+    let estimate = estimate_generation(length, count as u64);
+    status!();
+    status!("{}", "📊 Estimates:".yellow().bold());
+    print_info("Estimated file size", format!("{} bytes ({:.2} GB)", estimate.bytes, estimate.bytes as f64 / GB));
+    print_info(
+        "Estimated time",
+        format!("{:.0}–{:.0} seconds ({:.1}–{:.1} hours)", estimate.seconds_low, estimate.seconds_high, estimate.seconds_low / 3600.0, estimate.seconds_high / 3600.0),
+    );
+    print_info("Free space needed", format!("{:.2} GB", estimate.disk_needed as f64 / GB));
+    match available_space(Path::new(&output_file)) {
+        Some(available) => {
+            print_info("Free space available", format!("{:.2} GB", available as f64 / GB));
+            if estimate.disk_needed > available {
+                eprintln!("{}", "⚠️  The output will not fit in the free disk space!".red().bold());
+            }
         }
-        
-        // Storage requirements
-        println!();
-        println!("{}", "💾 Storage Requirements:".yellow().bold());
-        if size_gb > 100.0 {
-            println!("{}", "⚠️  You will need significant free disk space!".red().bold());
-        }
-        print_info("Minimum free space needed", format!("{:.1} GB", size_gb * 1.1)); // 10% buffer
-        print_info("Recommended free space", format!("{:.1} GB", size_gb * 2.0)); // 2x buffer
-        
-        // Time estimates for different systems
-        println!();
-        println!("{}", "⏱️  Time Estimates by System:".yellow().bold());
-        let fast_system = 5_000_000; // 5M combinations/sec
-        let medium_system = 1_000_000; // 1M combinations/sec
-        let slow_system = 100_000; // 100K combinations/sec
-        
-        let fast_time = count as f64 / fast_system as f64 / 3600.0;
-        let medium_time = count as f64 / medium_system as f64 / 3600.0;
-        let slow_time = count as f64 / slow_system as f64 / 3600.0;
-        
-        print_info("Fast system (5M/sec)", format!("{:.1} hours", fast_time));
-        print_info("Medium system (1M/sec)", format!("{:.1} hours", medium_time));
-        print_info("Slow system (100K/sec)", format!("{:.1} hours", slow_time));
+        None => print_info("Free space available", "unknown"),
+    }
+
+    if generate_all {
+        status!("{}", "⚠️  WARNING: This will generate a very large file!".red().bold());
         
         let confirm = match Input::<String>::new()
             .with_prompt("Are you sure you want to continue? (y/N)")
@@ -379,96 +798,286 @@ pub async fn generate_ascii_combinations_cli() {
         };
         
         if !confirm {
-            println!("{}", "Generation cancelled.".yellow().bold());
+            status!("{}", "Generation cancelled.".yellow().bold());
             return;
         }
     }
     
-    if start_index >= total_combinations {
-        print_error("Invalid start index", &format!("Start index {} exceeds maximum possible combinations ({})", start_index, total_combinations));
+    if let Err(e) = check_combination_range(length, start_index, count as u128) {
+        print_error("Invalid start index", &e);
         return;
     }
     
-    // Create progress bar
-    let progress_bar = ProgressBar::new(count as u64);
-    progress_bar.set_style(
-        ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")
-            .unwrap()
-            .progress_chars("#>-"),
-    );
-    
-    // Write to file
-    let mut file = match fs::File::create(&output_file) {
-        Ok(f) => f,
+    let mut reporter = IndicatifReporter::new();
+    reporter.start(count as u64);
+
+    let plan = resume.unwrap_or(GenerationProgress {
+        length,
+        next_index: start_index,
+        end_index: start_index + count as u128,
+        bytes_written: 0,
+    });
+    let total_generated = match write_ascii_combinations(Path::new(&output_file), plan, resume.is_some(), total_combinations, &mut reporter) {
+        Ok(generated) => generated,
         Err(e) => {
-            print_error("Failed to create output file", &e);
+            reporter.finish(&"Generation failed".red().to_string());
+            print_error("Failed to write combinations", &e);
             return;
         }
     };
+
+    reporter.finish(&"Generation complete!".green().to_string());
     
-    // Write header
-    writeln!(file, "# ASCII Combinations Generated by Stark Squeeze").unwrap();
-    writeln!(file, "# Length: {}", length).unwrap();
-    writeln!(file, "# Starting index: {}", start_index).unwrap();
-    writeln!(file, "# Count: {}", count).unwrap();
-    writeln!(file, "# Total possible combinations: {}", total_combinations).unwrap();
-    writeln!(file, "# Format: [index] combination").unwrap();
-    writeln!(file, "").unwrap();
+    status!();
+    status!("{}", "✅ Success!".green().bold());
+    output::result(format!("{} {}", "Combinations saved to".blue().bold(), output_file), &output_file);
+    print_info("Total generated", total_generated);
+    
+    if let Ok(metadata) = fs::metadata(&output_file) {
+        let size_mb = metadata.len() as f64 / (1024.0 * 1024.0);
+        print_info("File size", format!("{:.2} MB", size_mb));
+    }
     
-    // Generate combinations in chunks for memory efficiency
-    let chunk_size = 100_000; // Process 100k combinations at a time
+    if generate_all {
+        status!();
+        status!("{}", "🎉 All possible combinations have been generated!".green().bold());
+        status!("This file contains every possible {} character ASCII combination.", length);
+    }
+}
+
+/// Writes the combinations from `plan.next_index` up to `plan.end_index` to `output`,
+/// recording progress in its `.progress` sidecar at every flush. When `resume` is set
+/// the output is cut back to `plan.bytes_written` and appended to instead of recreated.
+/// The sidecar is removed once every combination is written.
+fn write_ascii_combinations(
+    output: &Path,
+    plan: GenerationProgress,
+    resume: bool,
+    total_combinations: u128,
+    progress: &mut dyn ProgressReporter,
+) -> std::io::Result<usize> {
+    use std::io::{Seek, SeekFrom};
+
+    let file = if resume {
+        let mut file = fs::OpenOptions::new().write(true).open(output)?;
+        if file.metadata()?.len() < plan.bytes_written {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{} is shorter than its progress record", output.display()),
+            ));
+        }
+        // Drop whatever was written after the last flush
+        file.set_len(plan.bytes_written)?;
+        file.seek(SeekFrom::End(0))?;
+        file
+    } else {
+        fs::File::create(output)?
+    };
+    let mut file = std::io::BufWriter::new(file);
+
+    if !resume {
+        // Write header
+        writeln!(file, "# ASCII Combinations Generated by Stark Squeeze")?;
+        writeln!(file, "# Length: {}", plan.length)?;
+        writeln!(file, "# Starting index: {}", plan.next_index)?;
+        writeln!(file, "# Count: {}", plan.remaining())?;
+        writeln!(file, "# Total possible combinations: {}", total_combinations)?;
+        writeln!(file, "# Format: [index] combination")?;
+        writeln!(file)?;
+    }
+    let flush = |file: &mut std::io::BufWriter<fs::File>, next_index: u128| -> std::io::Result<()> {
+        file.flush()?;
+        let bytes_written = file.get_ref().metadata()?.len();
+        save_progress(output, &GenerationProgress { next_index, bytes_written, ..plan })
+    };
+    flush(&mut file, plan.next_index)?;
+
+    // Stream combinations straight into the writer, updating the progress bar every
+    // 100k and flushing every 1M
+    let progress_interval = 100_000;
+    let flush_interval = progress_interval * 10;
+    let end_index = plan.end_index.min(combination_space(plan.length).unwrap_or(u128::MAX));
+    let mut combinations = AsciiCombinationIterator::new(plan.length, plan.next_index, end_index);
+    let mut written: u128 = 0;
+
+    for (index, combination) in combinations.by_ref() {
+        writeln!(file, "[{}] {:?}", index, combination)?;
+        written += 1;
+        if written.is_multiple_of(progress_interval) {
+            progress.inc(progress_interval as u64);
+            progress.set_message(&format!("Current index: {}", index + 1));
+        }
+        if written.is_multiple_of(flush_interval) {
+            flush(&mut file, index + 1)?;
+        }
+    }
+    let current_index = combinations.next_index();
+    progress.inc((written % progress_interval) as u64);
+    progress.set_message(&format!("Current index: {}", current_index));
+
+    file.flush()?;
+    clear_progress(output)?;
+    Ok((current_index - plan.next_index) as usize)
+}
+
+/// Bytes in a gigabyte, for size messages
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Predicted cost of writing a plain ASCII combination file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationEstimate {
+    /// Size of the combination lines
+    pub bytes: u64,
+    /// Time at `fast_system_rate`
+    pub seconds_low: f64,
+    /// Time at `slow_system_rate`
+    pub seconds_high: f64,
+    /// `bytes` plus a 10% buffer
+    pub disk_needed: u64,
+}
+
+/// Estimates the output of `count` combinations of `length` characters, each written
+/// as one `[index] "combination"` line
+pub fn estimate_generation(length: usize, count: u64) -> GenerationEstimate {
+    let rates = &get_config().dictionary.ascii_combinations.generation;
+    // Combination, index and formatting
+    let bytes = count.saturating_mul(length as u64 + 20);
+    GenerationEstimate {
+        bytes,
+        seconds_low: count as f64 / rates.fast_system_rate.max(1) as f64,
+        seconds_high: count as f64 / rates.slow_system_rate.max(1) as f64,
+        disk_needed: bytes.saturating_add(bytes / 10),
+    }
+}
+
+/// Free bytes on the disk `path` would be written to, when it can be determined
+fn available_space(path: &Path) -> Option<u64> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let dir = dir.canonicalize().ok()?;
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    // The most specific mount point holding the directory
+    disks
+        .list()
+        .iter()
+        .filter(|disk| dir.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+/// Number of combinations of `length` characters from the 128 ASCII characters.
+/// Fails for lengths whose space does not fit in a `u128` (above 18).
+fn combination_space(length: usize) -> Result<u128, String> {
+    u32::try_from(length)
+        .ok()
+        .and_then(|length| 128u128.checked_pow(length))
+        .ok_or_else(|| format!("{}-character combinations are too many to index (at most 18 characters)", length))
+}
+
+/// Checks that `count` combinations starting at `start_index` lie within the space of
+/// `length`-character combinations, returning the size of the space
+fn check_combination_range(length: usize, start_index: u128, count: u128) -> Result<u128, String> {
+    let total = combination_space(length)?;
+    match start_index.checked_add(count) {
+        Some(end) if start_index < total && end <= total => Ok(total),
+        _ => Err(format!(
+            "{} combinations from index {} run past the last of the {} {}-character combinations",
+            count, start_index, total, length
+        )),
+    }
+}
+
+/// Index as a JSON number, or a string when it is beyond what JSON numbers hold exactly
+fn json_index(index: u128) -> Value {
+    match u64::try_from(index) {
+        Ok(index) => json!(index),
+        Err(_) => json!(index.to_string()),
+    }
+}
+
+/// Builds `{"index", "value"}` entries for `count` combinations from `start_index`,
+/// in chunks, handing the entries so far to `on_flush` whenever the flush strategy
+/// calls for a write
+fn compressed_combinations(
+    length: usize,
+    start_index: u128,
+    count: usize,
+    progress: &mut dyn ProgressReporter,
+    mut on_flush: impl FnMut(&[Value]),
+) -> Vec<Value> {
+    let chunk_size = 10_000; // Smaller chunks for JSON processing
+    let mut flush = flush_tracker(chunk_size);
+    let mut current_index = start_index;
+    let mut combinations_array = Vec::with_capacity(count);
+
+    while combinations_array.len() < count {
+        let current_chunk_size = std::cmp::min(chunk_size, count - combinations_array.len());
+        let combinations = generate_ascii_combinations(length, current_index, current_chunk_size);
+
+        // Convert to compressed format
+        for (i, combination) in combinations.iter().enumerate() {
+            let binary_values: Vec<u8> = combination.chars().map(|c| c as u8).collect();
+            combinations_array.push(json!({
+                "index": json_index(current_index + i as u128),
+                "value": binary_values
+            }));
+        }
+
+        detail!(output::DEBUG, "Chunk of {} combinations ending before index {}", combinations.len(), current_index + combinations.len() as u128);
+        current_index += combinations.len() as u128;
+        progress.inc(combinations.len() as u64);
+        progress.set_message(&format!("Current index: {}", current_index));
+
+        let batch_bytes = combinations.iter().map(|c| c.len() as u64).sum();
+        if flush.record(combinations.len(), batch_bytes) {
+            on_flush(&combinations_array);
+        }
+    }
+    combinations_array
+}
+
+/// Builds the `combination -> single character` dictionary for `count` combinations
+/// from `start_index`, in chunks, handing the entries so far to `on_flush` whenever
+/// the flush strategy calls for a write
+fn ultra_compressed_combinations(
+    length: usize,
+    start_index: u128,
+    count: usize,
+    progress: &mut dyn ProgressReporter,
+    mut on_flush: impl FnMut(&serde_json::Map<String, Value>),
+) -> serde_json::Map<String, Value> {
+    let chunk_size = 100_000; // Larger chunks for faster generation
+    let mut flush = flush_tracker(chunk_size);
     let mut current_index = start_index;
     let mut total_generated = 0;
-    
+    let mut combinations_dict = serde_json::Map::new();
+
     while total_generated < count {
-        let remaining = count - total_generated;
-        let current_chunk_size = std::cmp::min(chunk_size, remaining);
-        
-        // Generate current chunk
+        let current_chunk_size = std::cmp::min(chunk_size, count - total_generated);
         let combinations = generate_ascii_combinations(length, current_index, current_chunk_size);
-        
-        // Write chunk to file
+
+        // Convert to key-value dictionary format
         for (i, combination) in combinations.iter().enumerate() {
-            let actual_index = current_index + i as u64;
-            writeln!(file, "[{}] {:?}", actual_index, combination).unwrap();
+            let actual_index = current_index + i as u128;
+            let value = char::from_u32((actual_index % 128) as u32).unwrap_or('.'); // Use ASCII character as value
+            combinations_dict.insert(combination.clone(), Value::String(value.to_string()));
         }
-        
-        // Update progress
+
+        detail!(output::DEBUG, "Chunk of {} combinations ending before index {}", combinations.len(), current_index + combinations.len() as u128);
         total_generated += combinations.len();
-        current_index += combinations.len() as u64;
-        progress_bar.set_position(total_generated as u64);
-        
-        // Update progress message with current index
-        progress_bar.set_message(format!("Current index: {}", current_index));
-        
-        // Flush file periodically
-        if total_generated % (chunk_size * 10) == 0 {
-            file.flush().unwrap();
+        current_index += combinations.len() as u128;
+        progress.inc(combinations.len() as u64);
+        progress.set_message(&format!("Current index: {} ({:.1}%)", current_index, (total_generated as f64 / count as f64) * 100.0));
+
+        let batch_bytes = combinations.iter().map(|c| c.len() as u64).sum();
+        if flush.record(combinations.len(), batch_bytes) {
+            on_flush(&combinations_dict);
         }
     }
-    
-    progress_bar.finish_with_message("Generation complete!".green().to_string());
-    
-    println!();
-    println!("{}", "✅ Success!".green().bold());
-    print_info("Combinations saved to", &output_file);
-    print_info("Total generated", total_generated);
-    
-    if let Ok(metadata) = fs::metadata(&output_file) {
-        let size_mb = metadata.len() as f64 / (1024.0 * 1024.0);
-        print_info("File size", format!("{:.2} MB", size_mb));
-    }
-    
-    if generate_all {
-        println!();
-        println!("{}", "🎉 All possible combinations have been generated!".green().bold());
-        println!("This file contains every possible {} character ASCII combination.", length);
-    }
+    combinations_dict
 }
 
 /// Generates ASCII character combinations of specified length
-fn generate_ascii_combinations(length: usize, start_index: u64, count: usize) -> Vec<String> {
+fn generate_ascii_combinations(length: usize, start_index: u128, count: usize) -> Vec<String> {
     const ASCII_CHARS: usize = 128;
     let mut result = Vec::with_capacity(count);
     
@@ -489,13 +1098,13 @@ fn generate_ascii_combinations(length: usize, start_index: u64, count: usize) ->
 }
 
 /// Converts an index to its corresponding combination
-fn index_to_combination(mut index: u64, length: usize, base: usize) -> String {
+fn index_to_combination(mut index: u128, length: usize, base: usize) -> String {
     let mut combination = String::with_capacity(length);
     
     for _ in 0..length {
-        let remainder = (index % base as u64) as u8;
+        let remainder = (index % base as u128) as u8;
         combination.push(remainder as char);
-        index /= base as u64;
+        index /= base as u128;
     }
     
     // Reverse to get correct order (least significant digit first)
@@ -526,8 +1135,8 @@ fn increment_combination(combination: &mut String, base: usize) -> bool {
 
 /// Generates ASCII character combinations in compressed JSON format
 pub async fn generate_compressed_ascii_combinations_cli() {
-    println!("{}", "🔤 Compressed ASCII Combination Generator".blue().bold());
-    println!();
+    status!("{}", "🔤 Compressed ASCII Combination Generator".blue().bold());
+    status!();
     
     // Get parameters from user
     let length: usize = match Input::<String>::new()
@@ -538,7 +1147,7 @@ pub async fn generate_compressed_ascii_combinations_cli() {
             Err(_) => 5,
     };
     
-    let start_index: u64 = match Input::<String>::new()
+    let start_index: u128 = match Input::<String>::new()
         .with_prompt("Enter starting index (default: 0)")
         .default("0".to_string())
         .interact_text() {
@@ -547,7 +1156,13 @@ pub async fn generate_compressed_ascii_combinations_cli() {
     };
     
     // Calculate total possible combinations
-    let total_combinations = 128u64.pow(length as u32);
+    let total_combinations = match combination_space(length) {
+        Ok(total) => total,
+        Err(e) => {
+            print_error("Invalid combination length", &e);
+            return;
+        }
+    };
     
     // Ask if user wants to generate all combinations
     let generate_all = match Input::<String>::new()
@@ -559,7 +1174,7 @@ pub async fn generate_compressed_ascii_combinations_cli() {
     };
     
     let count = if generate_all {
-        total_combinations.saturating_sub(start_index) as usize
+        total_combinations.saturating_sub(start_index).min(usize::MAX as u128) as usize
     } else {
         match Input::<String>::new()
             .with_prompt("Enter number of combinations to generate (default: 1000)")
@@ -578,8 +1193,8 @@ pub async fn generate_compressed_ascii_combinations_cli() {
             Err(_) => "ascii_combinations.json".to_string(),
     };
     
-    println!();
-    println!("{}", "📊 Generation Parameters:".yellow().bold());
+    status!();
+    status!("{}", "📊 Generation Parameters:".yellow().bold());
     print_info("Length", length);
     print_info("Starting index", start_index);
     print_info("Count", count);
@@ -598,8 +1213,8 @@ pub async fn generate_compressed_ascii_combinations_cli() {
         let estimated_seconds = count as f64 / combinations_per_second as f64;
         let estimated_hours = estimated_seconds / 3600.0;
         
-        println!();
-        println!("{}", "📊 Compressed Format Estimates:".yellow().bold());
+        status!();
+        status!("{}", "📊 Compressed Format Estimates:".yellow().bold());
         print_info("Original size (5-char strings)", format!("{:.1} GB", count as f64 * 25.0 / (1024.0 * 1024.0 * 1024.0)));
         print_info("Compressed size (4-byte binary)", format!("{:.1} GB", size_gb));
         print_info("Compression ratio", format!("{:.1}%", (1.0 - size_gb / (count as f64 * 25.0 / (1024.0 * 1024.0 * 1024.0))) * 100.0));
@@ -614,31 +1229,25 @@ pub async fn generate_compressed_ascii_combinations_cli() {
         };
         
         if !confirm {
-            println!("{}", "Generation cancelled.".yellow().bold());
+            status!("{}", "Generation cancelled.".yellow().bold());
             return;
         }
     }
     
-    if start_index >= total_combinations {
-        print_error("Invalid start index", &format!("Start index {} exceeds maximum possible combinations ({})", start_index, total_combinations));
+    if let Err(e) = check_combination_range(length, start_index, count as u128) {
+        print_error("Invalid start index", &e);
         return;
     }
     
-    // Create progress bar
-    let progress_bar = ProgressBar::new(count as u64);
-    progress_bar.set_style(
-        ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")
-            .unwrap()
-            .progress_chars("#>-"),
-    );
+    let mut reporter = IndicatifReporter::new();
+    reporter.start(count as u64);
     
     // Create JSON structure
     let mut json_data = json!({
         "metadata": {
             "length": length,
-            "total_combinations": total_combinations,
-            "start_index": start_index,
+            "total_combinations": json_index(total_combinations),
+            "start_index": json_index(start_index),
             "count": count,
             "encoding": "4-byte-binary",
             "generated_at": chrono::Utc::now().to_rfc3339(),
@@ -647,44 +1256,14 @@ pub async fn generate_compressed_ascii_combinations_cli() {
         "combinations": []
     });
     
-    // Generate combinations in chunks for memory efficiency
-    let chunk_size = 10_000; // Smaller chunks for JSON processing
-    let mut current_index = start_index;
-    let mut total_generated = 0;
-    let mut combinations_array = Vec::new();
-    
-    while total_generated < count {
-        let remaining = count - total_generated;
-        let current_chunk_size = std::cmp::min(chunk_size, remaining);
-        
-        // Generate current chunk
-        let combinations = generate_ascii_combinations(length, current_index, current_chunk_size);
-        
-        // Convert to compressed format
-        for (i, combination) in combinations.iter().enumerate() {
-            let actual_index = current_index + i as u64;
-            let binary_values: Vec<u8> = combination.chars().map(|c| c as u8).collect();
-            
-            combinations_array.push(json!({
-                "index": actual_index,
-                "value": binary_values
-            }));
-        }
-        
-        // Update progress
-        total_generated += combinations.len();
-        current_index += combinations.len() as u64;
-        progress_bar.set_position(total_generated as u64);
-        progress_bar.set_message(format!("Current index: {}", current_index));
-        
-        // Write to file periodically to avoid memory issues
-        if total_generated % (chunk_size * 5) == 0 {
-            json_data["combinations"] = Value::Array(combinations_array.clone());
-            if let Ok(json_string) = serde_json::to_string_pretty(&json_data) {
-                fs::write(&output_file, json_string).unwrap();
-            }
-        }
-    }
+    // Write to file periodically, as configured by the flush strategy
+    let combinations_array = compressed_combinations(length, start_index, count, &mut reporter, |entries| {
+        json_data["combinations"] = Value::Array(entries.to_vec());
+        if let Ok(json_string) = serde_json::to_string_pretty(&json_data) {
+            fs::write(&output_file, json_string).unwrap();
+        }
+    });
+    let total_generated = combinations_array.len();
     
     // Final write
     json_data["combinations"] = Value::Array(combinations_array);
@@ -692,11 +1271,11 @@ pub async fn generate_compressed_ascii_combinations_cli() {
         fs::write(&output_file, json_string).unwrap();
     }
     
-    progress_bar.finish_with_message("Generation complete!".green().to_string());
+    reporter.finish(&"Generation complete!".green().to_string());
     
-    println!();
-    println!("{}", "✅ Success!".green().bold());
-    print_info("Compressed combinations saved to", &output_file);
+    status!();
+    status!("{}", "✅ Success!".green().bold());
+    output::result(format!("{} {}", "Compressed combinations saved to".blue().bold(), output_file), &output_file);
     print_info("Total generated", total_generated);
     
     if let Ok(metadata) = fs::metadata(&output_file) {
@@ -711,40 +1290,56 @@ pub async fn generate_compressed_ascii_combinations_cli() {
     }
     
     if generate_all {
-        println!();
-        println!("{}", "🎉 All possible combinations have been generated!".green().bold());
-        println!("This file contains every possible {} character ASCII combination in compressed format.", length);
+        status!();
+        status!("{}", "🎉 All possible combinations have been generated!".green().bold());
+        status!("This file contains every possible {} character ASCII combination in compressed format.", length);
     }
     
     // Show sample of the JSON structure
-    println!();
-    println!("{}", "📋 JSON Structure Sample:".yellow().bold());
-    println!("The file contains combinations in this format:");
-    println!("{{\"index\": 0, \"value\": [0, 0, 0, 0, 0]}}");
-    println!("{{\"index\": 1, \"value\": [0, 0, 0, 0, 1]}}");
-    println!("...");
+    status!();
+    status!("{}", "📋 JSON Structure Sample:".yellow().bold());
+    status!("The file contains combinations in this format:");
+    status!("{{\"index\": 0, \"value\": [0, 0, 0, 0, 0]}}");
+    status!("{{\"index\": 1, \"value\": [0, 0, 0, 0, 1]}}");
+    status!("...");
 }
 
 /// Generates ASCII character combinations in ultra-compressed JSON format (3:1 compression for fast testing)
 pub async fn generate_ultra_compressed_ascii_combinations_cli() {
     let config = get_config();
-    println!("{}", "🔤 Ultra-Compressed ASCII Combination Generator (3:1 compression for fast testing)".blue().bold());
-    println!();
+    status!("{}", "🔤 Ultra-Compressed ASCII Combination Generator (3:1 compression for fast testing)".blue().bold());
+    status!();
     
     // Use configuration for optimal settings
     let length = config.dictionary.ultra_compressed.length;
-    let start_index = config.dictionary.ultra_compressed.start_index;
+    let start_index = config.dictionary.ultra_compressed.start_index as u128;
     
     // Calculate total possible combinations
-    let total_combinations = 128u64.pow(length as u32);
+    let total_combinations = match combination_space(length) {
+        Ok(total) => total,
+        Err(e) => {
+            print_error("Invalid combination length", &e);
+            return;
+        }
+    };
     
-    // Always generate all combinations
-    let count = total_combinations as usize;
+    // Always generate all combinations from the start index on
+    let count = match usize::try_from(total_combinations.saturating_sub(start_index)) {
+        Ok(count) => count,
+        Err(_) => {
+            print_error("Too many combinations", &format!("{} combinations do not fit in memory", total_combinations - start_index));
+            return;
+        }
+    };
+    if let Err(e) = check_combination_range(length, start_index, count as u128) {
+        print_error("Invalid start index", &e);
+        return;
+    }
     
     let output_file = "ascii_combinations.json".to_string();
     
-    println!();
-    println!("{}", "📊 Automatic Generation Parameters:".yellow().bold());
+    status!();
+    status!("{}", "📊 Automatic Generation Parameters:".yellow().bold());
     print_info("Length", length);
     print_info("Starting index", start_index);
     print_info("Count", count);
@@ -765,8 +1360,8 @@ pub async fn generate_ultra_compressed_ascii_combinations_cli() {
     let estimated_hours = estimated_seconds / 3600.0;
     let estimated_days = estimated_hours / 24.0;
     
-    println!();
-    println!("{}", "📊 Generation Estimates:".yellow().bold());
+    status!();
+    status!("{}", "📊 Generation Estimates:".yellow().bold());
     print_info("Original size", format!("{:.1} GB", original_size_gb));
     print_info("Compressed size", format!("{:.1} GB", final_size_gb));
     print_info("Compression ratio", format!("{:.1}%", (1.0 - final_size_gb / original_size_gb) * 100.0));
@@ -776,14 +1371,14 @@ pub async fn generate_ultra_compressed_ascii_combinations_cli() {
     print_info("Estimated time (days)", format!("{:.1}", estimated_days));
     
     // Storage requirements
-    println!();
-    println!("{}", "💾 Storage Requirements:".yellow().bold());
+    status!();
+    status!("{}", "💾 Storage Requirements:".yellow().bold());
     print_info("Minimum free space needed", format!("{:.1} GB", final_size_gb * 1.1));
     print_info("Recommended free space", format!("{:.1} GB", final_size_gb * 2.0));
     
     // Time estimates for different systems
-    println!();
-    println!("{}", "⏱️  Time Estimates by System:".yellow().bold());
+    status!();
+    status!("{}", "⏱️  Time Estimates by System:".yellow().bold());
     let fast_system = 5_000_000; // 5M combinations/sec
     let medium_system = 1_000_000; // 1M combinations/sec
     let slow_system = 100_000; // 100K combinations/sec
@@ -805,25 +1400,19 @@ pub async fn generate_ultra_compressed_ascii_combinations_cli() {
     };
     
     if !confirm {
-        println!("{}", "Generation cancelled.".yellow().bold());
+        status!("{}", "Generation cancelled.".yellow().bold());
         return;
     }
     
-    // Create progress bar
-    let progress_bar = ProgressBar::new(count as u64);
-    progress_bar.set_style(
-        ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")
-            .unwrap()
-            .progress_chars("#>-"),
-    );
+    let mut reporter = IndicatifReporter::new();
+    reporter.start(count as u64);
     
     // Create JSON structure with key-value dictionary
     let mut json_data = json!({
         "metadata": {
             "length": length,
-            "total_combinations": total_combinations,
-            "start_index": start_index,
+            "total_combinations": json_index(total_combinations),
+            "start_index": json_index(start_index),
             "count": count,
                     "encoding": &config.dictionary.ultra_compressed.encoding,
         "generated_at": chrono::Utc::now().to_rfc3339(),
@@ -834,44 +1423,14 @@ pub async fn generate_ultra_compressed_ascii_combinations_cli() {
         "combinations": {}
     });
     
-    // Generate combinations in chunks for memory efficiency
-    let chunk_size = 100_000; // Larger chunks for faster generation
-    let mut current_index = start_index;
-    let mut total_generated = 0;
-    let mut combinations_dict = serde_json::Map::new();
-    
-    while total_generated < count {
-        let remaining = count - total_generated;
-        let current_chunk_size = std::cmp::min(chunk_size, remaining);
-        
-        // Generate current chunk
-        let combinations = generate_ascii_combinations(length, current_index, current_chunk_size);
-        
-        // Convert to key-value dictionary format
-        for (i, combination) in combinations.iter().enumerate() {
-            let actual_index = current_index + i as u64;
-            
-            // Create key-value pair: combination -> single character
-            let key = combination.clone();
-            let value = char::from_u32((actual_index % 128) as u32).unwrap_or('.'); // Use ASCII character as value
-            
-            combinations_dict.insert(key, Value::String(value.to_string()));
+    // Write to file periodically, as configured by the flush strategy
+    let combinations_dict = ultra_compressed_combinations(length, start_index, count, &mut reporter, |entries| {
+        json_data["combinations"] = Value::Object(entries.clone());
+        if let Ok(json_string) = serde_json::to_string(&json_data) {
+            fs::write(&output_file, json_string).unwrap();
         }
-        
-        // Update progress
-        total_generated += combinations.len();
-        current_index += combinations.len() as u64;
-        progress_bar.set_position(total_generated as u64);
-        progress_bar.set_message(format!("Current index: {} ({:.1}%)", current_index, (total_generated as f64 / count as f64) * 100.0));
-        
-        // Write to file periodically to avoid memory issues
-        if total_generated % (chunk_size * 5) == 0 {
-            json_data["combinations"] = Value::Object(combinations_dict.clone());
-            if let Ok(json_string) = serde_json::to_string(&json_data) {
-                fs::write(&output_file, json_string).unwrap();
-            }
-        }
-    }
+    });
+    let total_generated = combinations_dict.len();
     
     // Final write
     json_data["combinations"] = Value::Object(combinations_dict);
@@ -879,11 +1438,11 @@ pub async fn generate_ultra_compressed_ascii_combinations_cli() {
         fs::write(&output_file, json_string).unwrap();
     }
     
-    progress_bar.finish_with_message("Generation complete!".green().to_string());
+    reporter.finish(&"Generation complete!".green().to_string());
     
-    println!();
-    println!("{}", "✅ Success!".green().bold());
-    print_info("Key-value dictionary saved to", &output_file);
+    status!();
+    status!("{}", "✅ Success!".green().bold());
+    output::result(format!("{} {}", "Key-value dictionary saved to".blue().bold(), output_file), &output_file);
     print_info("Total generated", total_generated);
     
     if let Ok(metadata) = fs::metadata(&output_file) {
@@ -897,23 +1456,23 @@ pub async fn generate_ultra_compressed_ascii_combinations_cli() {
         print_info("Compression achieved", format!("{:.1}%", compression_ratio));
         
         if compression_ratio >= config.dictionary.ultra_compressed.compression_ratio {
-            println!("{}", format!("🎉 {:.1}%+ compression achieved!", config.dictionary.ultra_compressed.compression_ratio).green().bold());
+            status!("{}", format!("🎉 {:.1}%+ compression achieved!", config.dictionary.ultra_compressed.compression_ratio).green().bold());
         }
     }
     
-    println!();
-    println!("{}", "🎉 All possible combinations have been generated!".green().bold());
-    println!("This file contains every possible {} character ASCII combination in key-value dictionary format.", length);
+    status!();
+    status!("{}", "🎉 All possible combinations have been generated!".green().bold());
+    status!("This file contains every possible {} character ASCII combination in key-value dictionary format.", length);
     
     // Show JSON format info
-    println!();
-    println!("{}", "📋 JSON Format Info:".yellow().bold());
-    println!("File contains:");
-    println!("- Metadata with generation info");
-    println!("- Key-value dictionary: {{\"combination\": \"single_char\"}}");
-    println!("- {:.1}% compression achieved through efficient encoding", config.dictionary.ultra_compressed.compression_ratio);
-    println!("- Each {} character combination mapped to single character", length);
-    println!("- Ready for file compression using option 8");
+    status!();
+    status!("{}", "📋 JSON Format Info:".yellow().bold());
+    status!("File contains:");
+    status!("- Metadata with generation info");
+    status!("- Key-value dictionary: {{\"combination\": \"single_char\"}}");
+    status!("- {:.1}% compression achieved through efficient encoding", config.dictionary.ultra_compressed.compression_ratio);
+    status!("- Each {} character combination mapped to single character", length);
+    status!("- Ready for file compression using option 8");
 }
 
 /// Generates ASCII character combinations in ultra-compressed JSON format (3:1 compression for fast testing)
@@ -921,7 +1480,7 @@ pub async fn generate_10bit_dictionary_cli() {
     use std::collections::HashMap;
     use std::fs;
 
-    println!("\u{1F522} Generating 10-bit Dictionary (0..1023)");
+    status!("\u{1F522} Generating 10-bit Dictionary (0..1023)");
     let mut dict = HashMap::new();
     for i in 0..1024u16 {
         dict.insert(i, format!("{:010b}", i));
@@ -929,95 +1488,941 @@ pub async fn generate_10bit_dictionary_cli() {
     let json = serde_json::to_string_pretty(&dict).unwrap();
     let filename = "10bit_dictionary.json";
     if let Err(e) = fs::write(filename, json) {
-        println!("Failed to write dictionary: {}", e);
+        print_error("Failed to write dictionary", &e);
         return;
     }
-    println!("Dictionary saved to {} ({} entries)", filename, dict.len());
+    output::result(format!("Dictionary saved to {} ({} entries)", filename, dict.len()), filename);
 }
 
-/// Decompresses a file using a minimal mapping
+/// Asks for a compressed file and decompresses it next to itself
 pub async fn decompress_file_cli() {
-    use std::fs;
-    use std::path::Path;
-    println!("\u{1F513} Decompress file");
+    status!("\u{1F513} Decompress file");
     let compressed_file = prompt_string("Enter compressed file path (.txt)").await;
-    let path = Path::new(&compressed_file);
-    let file_stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-    // Remove trailing .txt from file_stem if present
-    let output_file = if file_stem.ends_with(".txt") {
-        &file_stem[..file_stem.len()-4]
+    decompress_path_cli(Path::new(&compressed_file), None);
+}
+
+/// Where a compressed file is restored by default: next to it, without the `.txt`
+/// that `compress` appends
+fn default_decompressed_path(input: &Path) -> std::path::PathBuf {
+    let restored = input.with_file_name(input.file_stem().unwrap_or_default());
+    if restored == input {
+        input.with_extension("out")
+    } else {
+        restored
+    }
+}
+
+/// Restores the compressed file `input` into `out`, or its default path
+pub fn decompress_path_cli(input: &Path, out: Option<&Path>) -> i32 {
+    let out = out.map_or_else(|| default_decompressed_path(input), Path::to_path_buf);
+    status!("Output file will be: {}", out.display());
+    let bytes = match restore_compressed(input) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            print_error("Decompression failed", &e);
+            return 1;
+        }
+    };
+    if let Err(e) = fs::write(&out, &bytes) {
+        print_error("Failed to write output file", &e);
+        return 1;
+    }
+    output::result(format!("\u{2705} Decompression complete! Output: {}", out.display()), out.display());
+    0
+}
+
+
+
+/// Options for the compress command
+#[derive(Debug, Clone, Default)]
+pub struct CompressCliOptions {
+    /// Mapping layout written next to the compressed file
+    pub mapping_format: MappingFormat,
+    /// Read files above `large_file_warning_mb` without asking
+    pub allow_large: bool,
+    /// Overrides the configured `max_loss_percent` ASCII conversion limit
+    pub max_ascii_loss: Option<f64>,
+    /// Chunk the file's raw bytes instead of its ASCII conversion
+    pub raw: bool,
+    /// Dictionary to compress with instead of the configured `dictionary.path`
+    pub dictionary: Option<std::path::PathBuf>,
+    /// File to compress; prompted for when omitted
+    pub file: Option<std::path::PathBuf>,
+    /// Gzip the compressed output and add `.gz` to its name
+    pub gzip: bool,
+    /// Directory for the compressed and mapping files; overrides `storage.local.output_dir`
+    pub output_dir: Option<std::path::PathBuf>,
+    /// Chunk size to use instead of the sidecar's or the detected one: clamped to
+    /// `compression.chunk_size_range`, or searched for with `auto`
+    pub chunk_size: Option<ChunkSizeChoice>,
+    /// Compressed file to write instead of the one named after the input. The command
+    /// line turns `--output -` into stdout mode, so this is always a real path
+    pub output: Option<std::path::PathBuf>,
+    /// Extension input read from stdin is treated as having, e.g. `txt`
+    pub file_type: Option<String>,
+}
+
+/// Outcome of a compress run, printed as JSON by `compress --json`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompressSummary {
+    pub original_size: u64,
+    pub compressed_size: u64,
+    /// Compressed size over original size; 1.0 for empty input
+    pub ratio: f64,
+    pub output_path: String,
+    /// Always `None` for now, since compress does not pin its output
+    pub ipfs_cid: Option<String>,
+}
+
+impl CompressSummary {
+    fn new(original_len: usize, compressed_len: usize, output_path: String) -> Self {
+        Self {
+            original_size: original_len as u64,
+            compressed_size: compressed_len as u64,
+            ratio: crate::compression::size_ratio(original_len as u64, compressed_len as u64),
+            output_path,
+            ipfs_cid: None,
+        }
+    }
+}
+
+/// Builds a mapping chunked by the combination length of the dictionary at `dictionary_path`
+pub(crate) fn dictionary_mapping(
+    data: &[u8],
+    file_name: &str,
+    dictionary_path: &Path,
+    format: MappingFormat,
+    raw: bool,
+) -> Result<MappingFile, DictionaryError> {
+    let dictionary = load_dictionary(dictionary_path)?;
+    let mapping = create_mapping(data, file_name, dictionary.length, format, raw)?;
+    dictionary.check_chunk_size(mapping.chunk_size())?;
+    Ok(mapping)
+}
+
+/// Writes `data` behind a stored-codec `SQZ1` header alongside a stored mapping,
+/// skipping compression. With `gzip` the written copy is still gzipped, as `--gzip`
+/// output always is. Returns the number of bytes written
+pub(crate) fn store_uncompressed(
+    data: &[u8],
+    file_name: &str,
+    format: MappingFormat,
+    mapping_file: &Path,
+    compressed_file: &Path,
+    gzip: bool,
+) -> Result<usize, MappingError> {
+    save_mapping(&create_stored_mapping(data, file_name, format), mapping_file)?;
+    let stored = crate::compression::compress_file_with_codec(data, crate::compression::Codec::None)
+        .map_err(|e| MappingError::ConversionError(e.to_string()))?;
+    let written = if gzip {
+        crate::compression::gzip_bytes(&stored).map_err(|e| MappingError::ConversionError(e.to_string()))?
     } else {
-        file_stem
+        stored
+    };
+    write_compressed(&written, compressed_file)?;
+    Ok(written.len())
+}
+
+/// Writes compressed bytes to stdout when it is reserved for data, otherwise to `compressed_file`
+pub(crate) fn write_compressed(data: &[u8], compressed_file: &Path) -> std::io::Result<()> {
+    if output::data_on_stdout() {
+        output::write_data(data)
+    } else {
+        fs::write(compressed_file, data)
+    }
+}
+
+/// Where compressed output goes, for messages
+fn compressed_destination(compressed_file: &Path) -> String {
+    if output::data_on_stdout() {
+        "<stdout>".to_string()
+    } else {
+        compressed_file.display().to_string()
+    }
+}
+
+/// Writes the compressed bytes and reports the size reduction
+pub(crate) fn finish_compression(
+    compressed_data: &[u8],
+    original_len: usize,
+    compressed_file: &Path,
+) -> std::io::Result<CompressSummary> {
+    write_compressed(compressed_data, compressed_file)?;
+    let original_size = original_len as f64;
+    let compressed_size = compressed_data.len() as f64;
+    let reduction = if original_size > 0.0 {
+        100.0 - (compressed_size / original_size * 100.0)
+    } else {
+        0.0
+    };
+    let destination = compressed_destination(compressed_file);
+    output::result(format!("\u{2705} Compression complete! Compressed: {}", destination), &destination);
+    status!("Original size: {:.2} KB, Compressed size: {:.2} KB", original_size / 1024.0, compressed_size / 1024.0);
+    status!("Compression: {:.1}% smaller", reduction);
+    Ok(CompressSummary::new(original_len, compressed_data.len(), destination))
+}
+
+/// Checks that `dir` does not climb out through `..` and creates it if needed
+pub(crate) fn prepare_output_dir(dir: &Path) -> Result<(), String> {
+    if dir.components().any(|c| c == std::path::Component::ParentDir) {
+        return Err(format!("{} must not contain '..'", dir.display()));
+    }
+    fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))
+}
+
+/// Output path `<file name><suffix>` in the working directory, built without
+/// going through UTF-8 so unusual file names are preserved
+fn output_path_for(input: &Path, suffix: &str) -> std::path::PathBuf {
+    let mut name = input.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    std::path::PathBuf::from(name)
+}
+
+/// Compresses a file using the bit-packed pipeline. Returns `None` once an error has been reported
+pub async fn compress_file_cli(options: CompressCliOptions) -> Option<CompressSummary> {
+    status!("\u{1F4E6} Compress file");
+    let input_file = match options.file.clone() {
+        Some(file) => file,
+        None => std::path::PathBuf::from(prompt_string("Enter input file path").await),
     };
-    println!("Output file will be: {}", output_file);
-    // Read compressed data
-    let compressed_data = match fs::read(&compressed_file) {
+    compress_path(&input_file, &options, true)
+}
+
+/// Reads and compresses `input_file`. Without `interactive`, files above the large
+/// file threshold fail instead of prompting
+fn compress_path(input_file: &Path, options: &CompressCliOptions, interactive: bool) -> Option<CompressSummary> {
+    // Check the size before attempting to allocate the whole file
+    let size_limit = size_limit_for(input_file, options.allow_large, interactive);
+    if let Err(e) = check_file_size(input_file, size_limit) {
+        print_error("Refusing to read input file", &e);
+        return None;
+    }
+    // Read input data
+    let input_data = match fs::read(input_file) {
         Ok(data) => data,
         Err(e) => {
-            print_error("Failed to read compressed file", &e);
-            return;
+            print_error("Failed to read input file", &e);
+            return None;
+        }
+    };
+    compress_input(&input_data, input_file, true, options)
+}
+
+/// Compresses everything `reader` yields, as `compress --stdin` does. Output is named
+/// as if the input were `stdin.<file_type>`. Returns `None` once an error has been reported
+pub async fn compress_reader_cli(reader: impl std::io::Read, options: CompressCliOptions) -> Option<CompressSummary> {
+    status!("\u{1F4E6} Compress stdin");
+    let Some(file_type) = options.file_type.as_deref().map(|t| t.trim_start_matches('.')).filter(|t| !t.is_empty()) else {
+        print_error("Refusing to read stdin", &"--file-type is required, since there is no file extension");
+        return None;
+    };
+    let name = std::path::PathBuf::from(format!("stdin.{}", file_type));
+    // Stop reading one byte past the limit rather than buffering an unbounded stream
+    let size_limit = if options.allow_large { None } else { Some(large_file_threshold()) };
+    let mut input_data = Vec::new();
+    let mut limited = std::io::Read::take(reader, size_limit.map_or(u64::MAX, |limit| limit + 1));
+    if let Err(e) = std::io::Read::read_to_end(&mut limited, &mut input_data) {
+        print_error("Failed to read stdin", &e);
+        return None;
+    }
+    if let Some(threshold) = size_limit.filter(|&limit| input_data.len() as u64 > limit) {
+        print_error("Refusing to read stdin", &StarkSqueezeError::FileTooLarge { size: input_data.len() as u64, threshold });
+        return None;
+    }
+    compress_input(&input_data, &name, false, &options)
+}
+
+/// The compress pipeline over bytes already in memory. `path` names the outputs;
+/// its sidecar is only consulted when `from_file` is set, so stdin input always
+/// uses the global compression settings
+fn compress_input(input_data: &[u8], path: &Path, from_file: bool, options: &CompressCliOptions) -> Option<CompressSummary> {
+    let output_dir = options
+        .output_dir
+        .clone()
+        .or_else(|| get_config().storage.local.output_dir.as_ref().map(std::path::PathBuf::from));
+    if let Some(dir) = &output_dir {
+        if let Err(e) = prepare_output_dir(dir) {
+            print_error("Invalid output directory", &e);
+            return None;
+        }
+    }
+    let output_path_for = |input: &Path, suffix: &str| match &output_dir {
+        Some(dir) => dir.join(output_path_for(input, suffix)),
+        None => output_path_for(input, suffix),
+    };
+    let compressed_file = match &options.output {
+        Some(output) => output.clone(),
+        None => output_path_for(path, if options.gzip { ".txt.gz" } else { ".txt" }),
+    };
+    status!("Compressed file will be: {}", compressed_destination(&compressed_file));
+    let mapping_file = output_path_for(path, ".map");
+    let file_name = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy();
+    let file_name = file_name.as_ref();
+    // Tiny files would only grow once a mapping is attached, so keep them as they are
+    let min_size = get_config().file_processing.min_compress_size_bytes;
+    if (input_data.len() as u64) < min_size {
+        let stored_len = match store_uncompressed(input_data, file_name, options.mapping_format, &mapping_file, &compressed_file, options.gzip) {
+            Ok(len) => len,
+            Err(e) => {
+                print_error("Failed to store file", &e);
+                return None;
+            }
+        };
+        print_info(&format!("Mapping ({}):", options.mapping_format), mapping_file.display());
+        output::result(
+            format!(
+                "\u{23ED}\u{FE0F} Compression skipped: {} is {} bytes, below min_compress_size_bytes ({}), so it would not get smaller. Stored as-is: {}",
+                file_name, input_data.len(), min_size, compressed_destination(&compressed_file)
+            ),
+            compressed_destination(&compressed_file),
+        );
+        return Some(CompressSummary::new(input_data.len(), stored_len, compressed_destination(&compressed_file)));
+    }
+    // Refuse inputs the lossy ASCII conversion would mangle
+    let max_ascii_loss = options
+        .max_ascii_loss
+        .or(get_config().file_processing.ascii_conversion.max_loss_percent);
+    let raw = options.raw || get_config().file_processing.raw_mode;
+    if !raw {
+        let loss = ascii_loss_percent(input_data);
+        detail!(output::VERBOSE, "ASCII conversion alters {:.2}% of bytes", loss);
+        if let Err(e) = check_ascii_loss(loss, max_ascii_loss) {
+            print_error("Refusing to compress", &e);
+            return None;
+        }
+    }
+    // A sidecar next to the input overrides the global compression settings for this file;
+    // stdin has no file for a sidecar to sit next to
+    let params = if from_file {
+        match compression_params_for(path, &get_config().compression) {
+            Ok(params) => params,
+            Err(e) => {
+                print_error("Failed to read sidecar", &e);
+                return None;
+            }
         }
+    } else {
+        CompressionParams::from_config(&get_config().compression)
+    };
+    if let Some(sidecar) = &params.sidecar {
+        print_info("Sidecar:", sidecar.display());
+    }
+    print_info("Codec:", params.codec);
+    // Chunk by the dictionary's combination length when one is given, otherwise use
+    // --chunk-size, the sidecar's chunk size or pick one from the content
+    let chunk_size = options.chunk_size.map(|choice| resolve_chunk_size(choice, input_data)).or(params.chunk_size);
+    let mapping = match (resolve_dictionary_path(options.dictionary.as_deref()), chunk_size) {
+        (Some(dictionary_path), _) => {
+            print_info("Dictionary:", dictionary_path.display());
+            dictionary_mapping(input_data, file_name, &dictionary_path, options.mapping_format, raw)
+                .map_err(|e| e.to_string())
+        }
+        (None, Some(chunk_size)) => {
+            create_mapping(input_data, file_name, chunk_size, options.mapping_format, raw).map_err(|e| e.to_string())
+        }
+        (None, None) => {
+            let suggestion = suggest_chunk_size(input_data, &get_config().compression.chunk_size_range);
+            print_info("Reason:", &suggestion.reason);
+            create_mapping(input_data, file_name, suggestion.chunk_size, options.mapping_format, raw)
+                .map_err(|e| e.to_string())
+        }
+    };
+    let mapping = match mapping {
+        Ok(mapping) => mapping,
+        Err(e) => {
+            print_error("Failed to build chunk mapping", &e);
+            return None;
+        }
+    };
+    print_info("Chunk size:", mapping.chunk_size());
+    let unique_chunks = match &mapping {
+        MappingFile::Minimal(m) => m.code_to_chunk.len(),
+        MappingFile::Complete(m) => m.code_to_chunk.len(),
+    };
+    print_info("Unique chunks:", unique_chunks);
+    if output::verbosity() >= output::DEBUG {
+        let code_to_chunk = match &mapping {
+            MappingFile::Minimal(m) => &m.code_to_chunk,
+            MappingFile::Complete(m) => &m.code_to_chunk,
+        };
+        let mut codes: Vec<_> = code_to_chunk.iter().collect();
+        codes.sort();
+        for (code, chunk) in codes {
+            detail!(output::DEBUG, "Chunk {}: {:?}", code, String::from_utf8_lossy(chunk));
+        }
+    }
+    if let Err(e) = save_mapping(&mapping, &mapping_file) {
+        print_error("Failed to write mapping file", &e);
+        return None;
+    }
+    print_info(&format!("Mapping ({}):", options.mapping_format), mapping_file.display());
+    // Compress into the SQZ1 format: the sidecar's or configured codec, at the chunk size
+    // the mapping was built with
+    let codec_options = crate::compression::CompressOptions {
+        codec: params.codec,
+        chunk_size: clamped_chunk_size(mapping.chunk_size()),
+        gzip: options.gzip,
     };
-    // Decompress
-    match crate::compression::decompress_file(&compressed_data) {
-        Ok(bytes) => {
-            if let Err(e) = fs::write(&output_file, &bytes) {
-                print_error("Failed to write output file", &e);
-                return;
+    let compressed_data = match crate::compression::compress_bytes(input_data, codec_options) {
+        Ok(outcome) => {
+            // The codec falls back to storing the bytes when it can't shrink them
+            if outcome.codec != params.codec {
+                status!("{}", "Incompressible \u{2014} stored raw".yellow());
             }
-            println!("\u{2705} Decompression complete! Output: {}", output_file);
+            outcome.compressed
         }
         Err(e) => {
-            print_error("Decompression failed", &e);
+            print_error("Compression failed", &e);
+            return None;
+        }
+    };
+    // Save compressed data and report the ratio
+    match finish_compression(&compressed_data, input_data.len(), &compressed_file) {
+        Ok(summary) => Some(summary),
+        Err(e) => {
+            print_error("Failed to write compressed data", &e);
+            None
         }
     }
 }
 
+/// Outcome of a `compress-dir` run
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CompressDirSummary {
+    /// Files compressed successfully
+    pub files_processed: usize,
+    pub original_bytes: u64,
+    pub compressed_bytes: u64,
+    /// Files that could not be compressed; each error is printed as it happens
+    pub failures: Vec<std::path::PathBuf>,
+}
 
+/// Whether `path` passes `validation.file.allowed_extensions`, where `*` allows every file
+pub(crate) fn extension_allowed(path: &Path, allowed: &[String]) -> bool {
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    allowed
+        .iter()
+        .any(|a| a == "*" || a.trim_start_matches('.').eq_ignore_ascii_case(&extension))
+}
 
-/// Compresses a file using the bit-packed pipeline
-pub async fn compress_file_cli() {
-    use std::fs;
-    use std::path::Path;
-    println!("\u{1F4E6} Compress file");
-    let input_file = prompt_string("Enter input file path").await;
-    let path = Path::new(&input_file);
-    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-    let compressed_file = format!("{}.{}.txt", stem, ext);
-    println!("Compressed file will be: {}", compressed_file);
-    // Read input data
-    let input_data = match fs::read(&input_file) {
+/// Lists the files in `dir` in sorted order, descending into subdirectories when `recursive`
+fn files_in_dir(dir: &Path, recursive: bool) -> std::io::Result<Vec<std::path::PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if recursive {
+                files.extend(files_in_dir(&path, true)?);
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Compresses every allowed file in `dir` with the given options, carrying on past
+/// failures, and prints a summary. Outputs mirror each file's path relative to `dir`,
+/// so same-named files in different subdirectories don't collide. Never prompts.
+/// Returns `None` only when the directory can't be read
+pub async fn compress_dir_cli(dir: &Path, recursive: bool, options: CompressCliOptions) -> Option<CompressDirSummary> {
+    let files = match files_in_dir(dir, recursive) {
+        Ok(files) => files,
+        Err(e) => {
+            print_error("Failed to read directory", &e);
+            return None;
+        }
+    };
+    let allowed = &get_config().validation.file.allowed_extensions;
+    let base_dir = options
+        .output_dir
+        .clone()
+        .or_else(|| get_config().storage.local.output_dir.as_ref().map(std::path::PathBuf::from));
+    let mut summary = CompressDirSummary::default();
+    for file in files.into_iter().filter(|f| extension_allowed(f, allowed)) {
+        let relative_dir = file.strip_prefix(dir).ok().and_then(Path::parent).filter(|p| !p.as_os_str().is_empty());
+        let output_dir = match (&base_dir, relative_dir) {
+            (Some(base), Some(relative)) => Some(base.join(relative)),
+            (None, Some(relative)) => Some(relative.to_path_buf()),
+            (base, None) => base.clone(),
+        };
+        let file_options = CompressCliOptions { file: Some(file.clone()), output_dir, ..options.clone() };
+        match compress_path(&file, &file_options, false) {
+            Some(result) => {
+                summary.files_processed += 1;
+                summary.original_bytes += result.original_size;
+                summary.compressed_bytes += result.compressed_size;
+            }
+            None => {
+                status!("{} {}", "Skipped".yellow(), file.display());
+                summary.failures.push(file);
+            }
+        }
+    }
+
+    status!("{}", "\u{1F4C2} Directory summary".blue().bold());
+    print_info("Files processed:", summary.files_processed);
+    print_info("Original size:", format!("{} bytes", summary.original_bytes));
+    print_info("Compressed size:", format!("{} bytes", summary.compressed_bytes));
+    print_info("Failures:", summary.failures.len());
+    for failure in &summary.failures {
+        status!("  {} {}", "\u{2718}".red(), failure.display());
+    }
+    Some(summary)
+}
+
+/// Downloads a file from the IPFS gateway by CID and writes it to a chosen path
+pub async fn download_from_ipfs_cli() {
+    status!("\u{1F4E5} Download from IPFS");
+    let cid = prompt_string("Enter the IPFS CID").await;
+    let cid = cid.trim();
+    if let Err(e) = validate_cid(cid) {
+        print_error("Refusing to download", &e);
+        return;
+    }
+    let output_file = std::path::PathBuf::from(prompt_string("Enter the output file path").await);
+    let data = match fetch_file_from_ipfs(cid).await {
         Ok(data) => data,
         Err(e) => {
-            print_error("Failed to read input file", &e);
+            print_error("Download failed", &e);
             return;
         }
     };
-    // Compress
-    let compressed_data = match crate::compression::compress_file(&input_data) {
-        Ok(c) => c,
+    if let Err(e) = fs::write(&output_file, &data) {
+        print_error("Failed to write output file", &e);
+        return;
+    }
+    output::result(
+        format!("\u{2705} Downloaded {} bytes to {}", data.len(), output_file.display()),
+        output_file.display(),
+    );
+}
+
+/// Result of restoring an upload from its on-chain record and IPFS
+#[derive(Debug, Clone)]
+pub struct ReconstructSummary {
+    pub uri: String,
+    pub cid: String,
+    pub file_format: String,
+    pub bytes_written: usize,
+    /// The upload went through ASCII conversion, so non-printable bytes were not restored
+    pub lossy: bool,
+}
+
+/// Fails unless `uri` looks like an upload URI: 16 hex characters
+fn validate_uri(uri: &str) -> Result<(), StarkSqueezeError> {
+    if uri.len() == 16 && uri.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(())
+    } else {
+        Err(StarkSqueezeError::InvalidInput(format!("'{}' is not an upload URI; expected 16 hex characters", uri)))
+    }
+}
+
+/// CID recorded for `uri` in the local upload index, if the index is enabled and has one
+fn indexed_cid(uri: &str) -> Option<String> {
+    let local = &get_config().storage.local;
+    if !local.upload_index {
+        return None;
+    }
+    let records = UploadIndex::open(&local.upload_index_path).and_then(|index| index.search(Some(uri))).ok()?;
+    records.into_iter().filter(|record| record.hash == uri).find_map(|record| record.cid)
+}
+
+/// Turns the `{:08b}` binary string an ASCII-mode upload compressed back into bytes
+fn decode_binary_string(encoded: &[u8]) -> Result<Vec<u8>, StarkSqueezeError> {
+    if !encoded.len().is_multiple_of(8) {
+        return Err(StarkSqueezeError::ConversionError(format!(
+            "binary string length {} is not a multiple of 8; was the upload made in raw mode?",
+            encoded.len()
+        )));
+    }
+    encoded
+        .chunks(8)
+        .map(|bits| {
+            std::str::from_utf8(bits)
+                .ok()
+                .and_then(|bits| u8::from_str_radix(bits, 2).ok())
+                .ok_or_else(|| StarkSqueezeError::ConversionError("data is not a binary string; was the upload made in raw mode?".to_string()))
+        })
+        .collect()
+}
+
+/// Restores the upload `uri` into `out`: looks up its metadata with `retrieve`, downloads
+/// its CID with `fetch`, checks the bytes against the URI and decompresses them.
+/// `cid` overrides the CID recorded on-chain, which falls back to the local upload index
+/// for uploads made before it was recorded; `raw` forces raw mode over the recorded flag.
+pub(crate) async fn reconstruct_uri_with<R, RFut, F, FFut>(
+    uri: &str,
+    cid: Option<String>,
+    out: &Path,
+    raw: bool,
+    retrieve: R,
+    fetch: F,
+) -> Result<ReconstructSummary, StarkSqueezeError>
+where
+    R: FnOnce(String) -> RFut,
+    RFut: Future<Output = Result<OnChainUpload, Box<dyn std::error::Error + Send + Sync>>>,
+    F: FnOnce(String) -> FFut,
+    FFut: Future<Output = Result<Vec<u8>, IpfsError>>,
+{
+    validate_uri(uri)?;
+    if let Some(cid) = &cid {
+        validate_cid(cid).map_err(|e| StarkSqueezeError::InvalidInput(e.to_string()))?;
+    }
+
+    status!("\u{1F50E} Looking up {} on Starknet...", uri);
+    let record = retrieve(uri.to_string())
+        .await
+        .map_err(|e| StarkSqueezeError::RetrievalError(format!("on-chain lookup failed: {}", e)))?;
+    detail!(1, "Recorded in transaction {:#x}", record.transaction_hash);
+    print_info("File format:", &record.file_format);
+
+    let cid = cid.or_else(|| record.cid.clone()).or_else(|| indexed_cid(uri)).ok_or_else(|| {
+        StarkSqueezeError::RetrievalError(format!("no CID recorded for {}; pass --cid", uri))
+    })?;
+    validate_cid(&cid).map_err(|e| StarkSqueezeError::RetrievalError(e.to_string()))?;
+    let raw = raw || record.raw.unwrap_or(get_config().file_processing.raw_mode);
+
+    status!("\u{1F4E5} Fetching {} from IPFS...", cid);
+    let packed = fetch(cid.clone())
+        .await
+        .map_err(|e| StarkSqueezeError::RetrievalError(format!("IPFS download failed: {}", e)))?;
+    let fetched_uri = hex::encode(&hash_data(&packed, &get_config().upload.hash).digest[..8]);
+    if fetched_uri != uri || packed.len() as u64 != record.final_size {
+        return Err(StarkSqueezeError::RetrievalError(format!(
+            "CID {} holds {} bytes hashing to {}, not the {} bytes recorded for {}; is it the right CID?",
+            cid,
+            packed.len(),
+            fetched_uri,
+            record.final_size,
+            uri
+        )));
+    }
+
+    status!("\u{1F513} Decompressing {} bytes...", packed.len());
+    let encoded = crate::compression::decompress_file(&packed)?;
+    if encoded.len() as u64 != record.original_size {
+        return Err(StarkSqueezeError::RetrievalError(format!(
+            "decompressed to {} bytes, but {} were recorded on-chain",
+            encoded.len(),
+            record.original_size
+        )));
+    }
+    let bytes = if raw { encoded } else { decode_binary_string(&encoded)? };
+
+    fs::write(out, &bytes)?;
+    Ok(ReconstructSummary {
+        uri: uri.to_string(),
+        cid,
+        file_format: record.file_format,
+        bytes_written: bytes.len(),
+        lossy: !raw,
+    })
+}
+
+/// Restores an upload from Starknet and IPFS into `out`, using the CID and raw flag
+/// recorded with it unless `cid` or `raw` are given
+pub async fn reconstruct_uri_cli(uri: &str, cid: Option<String>, out: &Path, raw: bool) -> i32 {
+    let retrieve = |uri: String| async move { crate::starknet_client::retrieve_data(&uri).await };
+    let fetch = |cid: String| async move { fetch_file_from_ipfs(&cid).await };
+    match reconstruct_uri_with(uri, cid, out, raw, retrieve, fetch).await {
+        Ok(summary) => {
+            if summary.lossy {
+                detail!(1, "Restored from the ASCII conversion; bytes outside printable ASCII were altered on upload");
+            }
+            output::result(
+                format!("\u{2705} Reconstructed {} bytes to {}", summary.bytes_written, out.display()),
+                out.display(),
+            );
+            0
+        }
         Err(e) => {
-            print_error("Compression failed", &e);
+            print_error("Reconstruct failed", &e);
+            1
+        }
+    }
+}
+
+/// Prints the pinned bytes and estimated monthly IPFS storage cost for a batch of files
+pub fn report_ipfs_cost_cli(paths: &[std::path::PathBuf], raw: bool) {
+    let cost_per_gb = get_config().storage.ipfs.cost_per_gb_month;
+    let report = match crate::ipfs_cost::estimate_ipfs_cost(paths, raw, cost_per_gb) {
+        Ok(report) => report,
+        Err(e) => {
+            print_error("Failed to estimate IPFS cost", &e);
             return;
         }
     };
-    // Save compressed data
-    if let Err(e) = fs::write(&compressed_file, &compressed_data) {
-        print_error("Failed to write compressed file", &e);
-        return;
+
+    status!("{}", "📌 IPFS pinning estimate".blue().bold());
+    for file in &report.files {
+        status!("  {} — {} bytes → {} bytes pinned", file.path.display(), file.original_size, file.pinned_size);
     }
-    // Calculate and print compression ratio
-    let original_size = input_data.len() as f64;
-    let compressed_size = compressed_data.len() as f64;
-    let reduction = if original_size > 0.0 {
-        100.0 - (compressed_size / original_size * 100.0)
+    print_info("Files:", report.files.len());
+    print_info("Total pinned:", format!("{} bytes ({:.3} GB)", report.total_pinned_bytes, report.total_pinned_bytes as f64 / 1e9));
+    output::result(
+        format!("{} ${:.4}/month at ${:.2}/GB", "Estimated storage:".blue().bold(), report.monthly_cost, report.cost_per_gb_month),
+        format!("{} {:.4}", report.total_pinned_bytes, report.monthly_cost),
+    );
+}
+
+/// Lists the configured debug files of the latest run with their size and modification time
+pub fn debug_ls_cli() {
+    let entries = crate::debug_files::list_debug_files(Path::new("."), &get_config().debug);
+    for entry in &entries {
+        match (entry.size, entry.modified) {
+            (Some(size), modified) => {
+                let modified = modified
+                    .map(|time| chrono::DateTime::<chrono::Local>::from(time).format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_else(|| "-".to_string());
+                output::result(
+                    format!("{} {} {:>10} bytes  {}", "✔".green(), entry.path.display(), size, modified),
+                    entry.path.display(),
+                );
+            }
+            (None, _) => status!("{} {} (absent)", "✘".red(), entry.path.display()),
+        }
+    }
+    let present = entries.iter().filter(|e| e.exists()).count();
+    status!("{} of {} debug files present", present, entries.len());
+}
+
+/// Removes all debug files and rotated debug runs
+pub fn debug_clean_cli() {
+    match crate::debug_files::clean_debug_files(Path::new("."), &get_config().debug) {
+        Ok(removed) => output::result(format!("🧹 Removed {} debug artifacts", removed), removed),
+        Err(e) => print_error("Failed to clean debug files", &e),
+    }
+}
+
+/// Runs the built-in self-test battery and returns the process exit code
+pub fn selftest_cli() -> i32 {
+    let checks = crate::selftest::run_selftest();
+    for check in &checks {
+        if check.passed {
+            status!("{} {}: {}", "PASS".green().bold(), check.name, check.detail);
+        } else {
+            eprintln!("{} {}: {}", "FAIL".red().bold(), check.name, check.detail);
+        }
+    }
+    let failed = checks.iter().filter(|c| !c.passed).count();
+    let code = crate::selftest::exit_code(&checks);
+    output::result(
+        format!("{}/{} checks passed", checks.len() - failed, checks.len()),
+        if code == 0 { "ok" } else { "failed" },
+    );
+    code
+}
+
+/// Measures the compression ratio on the built-in corpus, printing it next to
+/// `compression.current_compression_ratio` and warning when they drift apart
+pub fn ratio_self_check_cli() {
+    let compression = &get_config().compression;
+    match crate::selftest::check_compression_ratio(compression.current_compression_ratio, compression.ratio_tolerance_percent) {
+        Ok(check) => {
+            status!("Compression ratio: measured {:.1}%, configured {:.1}%", check.measured, check.configured);
+            if let Some(warning) = check.warning() {
+                eprintln!("Warning: {}", warning);
+            }
+        }
+        Err(e) => print_error("Compression ratio self-check failed", &e),
+    }
+}
+
+/// What `verify` restores the original from
+#[derive(Debug, Clone)]
+pub enum VerifySource {
+    /// Output of `compress`, gzipped or not
+    Compressed(std::path::PathBuf),
+    /// A `.map` mapping file
+    Mapping(std::path::PathBuf),
+}
+
+/// Restores the bytes `source` describes, in memory
+fn restore_for_verify(source: &VerifySource) -> Result<Vec<u8>, String> {
+    match source {
+        VerifySource::Compressed(path) => restore_compressed(path),
+        VerifySource::Mapping(path) => {
+            let mapping = crate::mapping::load_mapping(&path.to_string_lossy()).map_err(|e| e.to_string())?;
+            crate::mapping::decode_mapping_file(mapping).map(|(bytes, _)| bytes).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Reads and decompresses a compressed file written by `compress`, inflating `--gzip`
+/// output first
+fn restore_compressed(path: &Path) -> Result<Vec<u8>, String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let data = crate::compression::gunzip_if_needed(&data).map_err(|e| e.to_string())?;
+    // Bit-packed files start with their magic; anything else is the configured backend's output
+    if data.starts_with(crate::compression::MAGIC) {
+        crate::compression::decompress_file(&data).map_err(|e| e.to_string())
     } else {
-        0.0
+        get_config().compression.backend.decompress(&data).map_err(|e| e.to_string())
+    }
+}
+
+/// Offset of the first byte where `restored` and `original` differ, if any
+fn first_mismatch(restored: &[u8], original: &[u8]) -> Option<usize> {
+    restored
+        .iter()
+        .zip(original)
+        .position(|(a, b)| a != b)
+        .or_else(|| (restored.len() != original.len()).then(|| restored.len().min(original.len())))
+}
+
+/// Checks that `source` restores `original` byte for byte, printing PASS or FAIL.
+/// Returns the process exit code
+pub fn verify_cli(source: &VerifySource, original: &Path) -> i32 {
+    let outcome = fs::read(original)
+        .map_err(|e| format!("Failed to read {}: {}", original.display(), e))
+        .and_then(|original| {
+            let restored = restore_for_verify(source)?;
+            match first_mismatch(&restored, &original) {
+                None => Ok(restored.len()),
+                Some(offset) => Err(format!(
+                    "restored data ({} bytes) differs from the original ({} bytes) at byte {}",
+                    restored.len(),
+                    original.len(),
+                    offset
+                )),
+            }
+        });
+    match outcome {
+        Ok(len) => {
+            output::result(format!("{} {} bytes match {}", "PASS".green().bold(), len, original.display()), "PASS");
+            0
+        }
+        Err(e) => {
+            output::result(format!("{} {}", "FAIL".red().bold(), e), "FAIL");
+            1
+        }
+    }
+}
+
+/// Byte values shown in the `analyze` histogram unless -v asks for all of them
+const ANALYZE_TOP_BYTES: usize = 16;
+
+/// How a byte value is shown in the `analyze` histogram
+fn byte_label(byte: u8) -> String {
+    if byte.is_ascii_graphic() {
+        format!("0x{:02x} '{}'", byte, byte as char)
+    } else {
+        format!("0x{:02x}", byte)
+    }
+}
+
+/// Reports a file's entropy, byte histogram and predicted compression ratio. Returns the
+/// process exit code
+pub fn analyze_cli(path: &Path) -> i32 {
+    let analysis = match crate::analysis::analyze_file(path) {
+        Ok(analysis) => analysis,
+        Err(e) => {
+            print_error(&format!("Failed to read {}", path.display()), &e);
+            return 1;
+        }
+    };
+    let entropy = analysis.entropy();
+    status!("{}", "\u{1F50D} Analyze file".blue().bold());
+    print_info("File:", path.display());
+    print_info("Size:", format!("{} bytes", analysis.total_bytes));
+    print_info("Entropy:", format!("{:.3} bits/byte", entropy));
+    print_info("Distinct byte values:", analysis.distinct_bytes());
+    print_info("Predicted ratio:", format!("{:.1}% of original", analysis.predicted_ratio() * 100.0));
+
+    let common = analysis.most_common();
+    let shown = if output::verbosity() >= output::VERBOSE { common.len() } else { common.len().min(ANALYZE_TOP_BYTES) };
+    status!("  {:<10} {:>12} {:>8}", "Byte", "Count", "Share");
+    for &(byte, count) in &common[..shown] {
+        status!("  {:<10} {:>12} {:>7.2}%", byte_label(byte), count, count as f64 / analysis.total_bytes as f64 * 100.0);
+    }
+    if shown < common.len() {
+        status!("  ... {} more byte values (-v to show all)", common.len() - shown);
+    }
+
+    let verdict = if analysis.likely_incompressible() { "likely incompressible" } else { "compressible" };
+    let line = if analysis.likely_incompressible() {
+        format!("\u{26A0}\u{FE0F} Likely incompressible: {:.3} bits/byte is close to the 8-bit maximum", entropy).yellow().to_string()
+    } else {
+        format!("\u{2705} Compressible: predicted {:.1}% of original size", analysis.predicted_ratio() * 100.0).green().to_string()
     };
-    println!("\u{2705} Compression complete! Compressed: {}", compressed_file);
-    println!("Original size: {:.2} KB, Compressed size: {:.2} KB", original_size / 1024.0, compressed_size / 1024.0);
-    println!("Compression: {:.1}% smaller", reduction);
+    output::result(line, format!("{:.3} {:.3} {}", entropy, analysis.predicted_ratio(), verdict));
+    0
+}
+
+/// Bundles `files` into the archive `out`. Returns the process exit code
+pub fn archive_create_cli(out: &Path, files: &[std::path::PathBuf]) -> i32 {
+    match crate::archive::create_archive(out, files) {
+        Ok(manifest) => {
+            for entry in &manifest.files {
+                detail!(output::VERBOSE, "{}: {} -> {} bytes", entry.file_name, entry.original_size, entry.compressed_size);
+            }
+            let original: u64 = manifest.files.iter().map(|entry| entry.original_size).sum();
+            let archived = fs::metadata(out).map(|m| m.len()).unwrap_or(0);
+            output::result(
+                format!("\u{2705} Archived {} files ({} bytes) into {} ({} bytes)", manifest.files.len(), original, out.display(), archived),
+                out.display(),
+            );
+            0
+        }
+        Err(e) => {
+            print_error("Failed to create archive", &e);
+            1
+        }
+    }
+}
+
+/// Restores every file of `archive` into `dir`. Returns the process exit code
+pub fn archive_extract_cli(archive: &Path, dir: &Path) -> i32 {
+    match crate::archive::extract_archive(archive, dir) {
+        Ok(manifest) => {
+            for entry in &manifest.files {
+                detail!(output::VERBOSE, "{}: {} bytes", entry.file_name, entry.original_size);
+            }
+            output::result(
+                format!("\u{2705} Extracted {} files into {}", manifest.files.len(), dir.display()),
+                dir.display(),
+            );
+            0
+        }
+        Err(e) => {
+            print_error("Failed to extract archive", &e);
+            1
+        }
+    }
+}
+
+/// Writes the default configuration, keeping an existing file unless `force` is set.
+/// Returns the process exit code
+pub fn config_init_cli(force: bool) -> i32 {
+    match crate::config::init_config(force) {
+        Ok(path) => {
+            output::result(format!("\u{2705} Wrote default configuration to {}", path.display()), path.display());
+            0
+        }
+        Err(e) => {
+            print_error("Failed to write configuration", &e);
+            1
+        }
+    }
+}
+
+/// Upgrades a mapping file to the current format version. Returns the process exit code
+pub fn migrate_mapping_cli(path: &Path) -> i32 {
+    match crate::mapping::migrate_mapping(&path.to_string_lossy()) {
+        Ok(()) => {
+            output::result(
+                format!("\u{2705} {} is at mapping version {}", path.display(), crate::mapping::MAPPING_VERSION),
+                crate::mapping::MAPPING_VERSION,
+            );
+            0
+        }
+        Err(e) => {
+            print_error("Failed to migrate mapping", &e);
+            1
+        }
+    }
 }
 
 /// Displays the CLI menu and handles command routing
@@ -1028,25 +2433,962 @@ pub async fn main_menu() {
     println!("4. Generate 10-bit Dictionary (0..1023)");
     println!("5. Decompress file");
     println!("6. Compress file");
-    println!("7. Exit");
+    println!("7. Resume interrupted upload");
+    println!("8. Download from IPFS");
+    println!("9. Exit");
     let mut input = String::new();
-    print!("Enter your choice (1-7): ");
+    print!("Enter your choice (1-9): ");
     std::io::stdout().flush().unwrap();
     std::io::stdin().read_line(&mut input).unwrap();
     match input.trim() {
-        "1" => upload_data_cli(None).await,
+        "1" => match upload_data_cli(None, false).await {
+            Ok(summary) => print_upload_summary(&summary),
+            Err(e) => print_error("Upload failed", &e),
+        },
         "2" => reconstruct_from_mapping_cli().await,
         "3" => analyze_mapping_only_cli().await,
         "4" => generate_10bit_dictionary_cli().await,
         "5" => decompress_file_cli().await,
-        "6" => compress_file_cli().await,
-        "7" => {
+        "6" => {
+            compress_file_cli(CompressCliOptions::default()).await;
+        }
+        "7" => resume_upload_cli(None).await,
+        "8" => download_from_ipfs_cli().await,
+        "9" => {
             println!("{}", "\u{1F44B} Goodbye!".bold().green());
-            return;
         }
         _ => {
-            println!("Invalid choice. Please enter a number between 1 and 7.");
+            println!("Invalid choice. Please enter a number between 1 and 9.");
         }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_streamed_combinations_match_chunked_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("combos.txt");
+        let plan = GenerationProgress { length: 2, next_index: 100, end_index: 400, bytes_written: 0 };
+        let generated = write_ascii_combinations(&output, plan, false, 128 * 128, &mut crate::progress::NoopReporter).unwrap();
+        assert_eq!(generated, 300);
+
+        let header = "# ASCII Combinations Generated by Stark Squeeze\n# Length: 2\n# Starting index: 100\n# Count: 300\n\
+                      # Total possible combinations: 16384\n# Format: [index] combination\n\n";
+        let chunked: String = generate_ascii_combinations(2, 100, 300)
+            .iter()
+            .enumerate()
+            .map(|(i, combination)| format!("[{}] {:?}\n", 100 + i, combination))
+            .collect();
+        assert_eq!(fs::read(&output).unwrap(), format!("{}{}", header, chunked).into_bytes());
+    }
+
+    #[test]
+    fn test_long_combinations_at_high_indices() {
+        let total = combination_space(10).unwrap();
+        assert_eq!(total, 1u128 << 70);
+        let last = "\u{7f}".repeat(10);
+        assert_eq!(index_to_combination(total - 1, 10, 128), last);
+
+        let tail = generate_ascii_combinations(10, total - 2, 2);
+        assert_eq!(tail, vec![format!("{}\u{7e}", "\u{7f}".repeat(9)), last]);
+        assert_eq!(check_combination_range(10, total - 2, 2), Ok(total));
+        assert!(check_combination_range(10, total - 2, 3).is_err());
+        assert!(check_combination_range(10, total, 0).is_err());
+        assert!(check_combination_range(10, u128::MAX, 2).is_err());
+
+        assert_eq!(combination_space(18), Ok(1u128 << 126));
+        assert!(combination_space(19).is_err());
+        assert_eq!(json_index(total), json!(total.to_string()));
+        assert_eq!(json_index(5), json!(5));
+    }
+
+    #[test]
+    fn test_generation_estimate_scales_linearly_with_count() {
+        let one = estimate_generation(10, 1_000);
+        let ten = estimate_generation(10, 10_000);
+        assert_eq!(ten.bytes, one.bytes * 10);
+        assert_eq!(ten.disk_needed, one.disk_needed * 10);
+        assert!((ten.seconds_low - one.seconds_low * 10.0).abs() < 1e-9);
+        assert!((ten.seconds_high - one.seconds_high * 10.0).abs() < 1e-9);
+        assert!(one.seconds_low <= one.seconds_high);
+        assert_eq!(estimate_generation(10, 0).bytes, 0);
+    }
+
+    #[test]
+    fn test_generation_resumes_from_progress_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("combos.txt");
+        let flushed = "# header\n[0] \"\\0\"\n[1] \"\\u{1}\"\n[2] \"\\u{2}\"\n";
+        // Lines written after the last flush are not trusted
+        fs::write(&output, format!("{}[3] \"\\u{{3", flushed)).unwrap();
+        let seeded = GenerationProgress { length: 1, next_index: 3, end_index: 6, bytes_written: flushed.len() as u64 };
+        crate::generation_progress::save_progress(&output, &seeded).unwrap();
+
+        let plan = load_progress(&output).unwrap();
+        let generated = write_ascii_combinations(&output, plan, true, 128, &mut crate::progress::NoopReporter).unwrap();
+        assert_eq!(generated, 3);
+        let expected: String = (3..6u8).map(|i| format!("[{}] {:?}\n", i, (i as char).to_string())).collect();
+        assert_eq!(fs::read_to_string(&output).unwrap(), format!("{}{}", flushed, expected));
+        assert_eq!(load_progress(&output), None);
+    }
+
+    #[test]
+    fn test_compress_with_dictionary_at_custom_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let dictionary_path = dir.path().join("dicts").join("custom_dictionary.json");
+        std::fs::create_dir_all(dictionary_path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &dictionary_path,
+            r#"{"metadata": {"length": 4}, "combinations": {"abcd": "a", "efgh": "b"}}"#,
+        )
+        .unwrap();
+        let data = b"abcdefghabcdefgh";
+
+        let mapping = dictionary_mapping(data, "data.txt", &dictionary_path, MappingFormat::Minimal, false).unwrap();
+        assert_eq!(mapping.chunk_size(), 4);
+        let mapping_path = dir.path().join("data.txt.map");
+        save_mapping(&mapping, mapping_path.to_str().unwrap()).unwrap();
+        let restored = dir.path().join("restored.txt");
+        reconstruct_from_mapping(mapping_path.to_str().unwrap(), restored.to_str().unwrap()).unwrap();
+        assert_eq!(std::fs::read(&restored).unwrap(), data);
+
+        // More than 256 distinct 4-byte chunks forces 1-byte chunks, which the dictionary cannot serve
+        let varied: Vec<u8> = (0..4000u32).map(|i| (i.wrapping_mul(2654435761) >> 7) as u8 % 95 + 32).collect();
+        let result = dictionary_mapping(&varied, "varied.txt", &dictionary_path, MappingFormat::Minimal, true);
+        assert!(matches!(result, Err(DictionaryError::ChunkSizeMismatch { dictionary: 4, data: 1 })));
+    }
+
+    #[test]
+    fn test_stdout_mode_keeps_metrics_off_stdout() {
+        let dir = tempfile::tempdir().unwrap();
+        let compressed_file = dir.path().join("data.bin.txt");
+        let payload: Vec<u8> = (0..=255u8).chain([b'\n', 0, 0xff]).collect();
+
+        output::start_capture_with_data_on_stdout();
+        print_info("Chunk size:", 4);
+        finish_compression(&payload, 1024, &compressed_file).unwrap();
+        let captured = output::finish_capture_streams();
+
+        assert_eq!(captured.stdout, payload);
+        assert!(!compressed_file.exists());
+        assert!(captured.stderr.iter().any(|l| l.contains("Chunk size:")));
+        assert!(captured.stderr.iter().any(|l| l.contains("Compression complete! Compressed: <stdout>")));
+        assert!(captured.stderr.iter().any(|l| l.starts_with("Compression:")));
+    }
+
+    #[test]
+    fn test_json_mode_prints_only_the_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        let compressed_file = dir.path().join("data.bin.txt");
+
+        output::start_capture_with_json();
+        print_info("Chunk size:", 4);
+        let summary = finish_compression(&[1, 2, 3, 4], 16, &compressed_file).unwrap();
+        output::print_json(&summary).unwrap();
+        let captured = output::finish_capture_streams();
+
+        assert!(captured.stderr.is_empty());
+        let stdout = String::from_utf8(captured.stdout).unwrap();
+        assert_eq!(stdout.lines().count(), 1);
+        let value: Value = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "original_size": 16,
+                "compressed_size": 4,
+                "ratio": 0.25,
+                "output_path": compressed_file.display().to_string(),
+                "ipfs_cid": null,
+            })
+        );
+        assert_eq!(std::fs::read(&compressed_file).unwrap(), vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_compress_writes_into_output_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("notes.txt");
+        std::fs::write(&input, b"stark squeeze output dir test ".repeat(40)).unwrap();
+        let out = dir.path().join("out/nested");
+
+        let options = CompressCliOptions { file: Some(input.clone()), output_dir: Some(out.clone()), ..Default::default() };
+        let summary = compress_file_cli(options).await.unwrap();
+        assert_eq!(summary.output_path, out.join("notes.txt.txt").display().to_string());
+        assert!(out.join("notes.txt.txt").is_file());
+        assert!(out.join("notes.txt.map").is_file());
+
+        let escaping = dir.path().join("out/../escape");
+        let options = CompressCliOptions { file: Some(input), output_dir: Some(escaping), ..Default::default() };
+        assert!(compress_file_cli(options).await.is_none());
+        assert!(!dir.path().join("escape").exists());
+    }
+
+    #[tokio::test]
+    async fn test_compress_from_reader_matches_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = b"piped through stdin, piped through stdin ".repeat(50);
+        let input = dir.path().join("notes.txt");
+        std::fs::write(&input, &data).unwrap();
+
+        let options = CompressCliOptions { file: Some(input), output_dir: Some(dir.path().join("file")), ..Default::default() };
+        let from_file = compress_file_cli(options).await.unwrap();
+
+        let piped = dir.path().join("piped.sqz");
+        let options = CompressCliOptions {
+            file_type: Some("txt".to_string()),
+            output: Some(piped.clone()),
+            output_dir: Some(dir.path().join("stdin")),
+            ..Default::default()
+        };
+        let from_reader = compress_reader_cli(std::io::Cursor::new(data.clone()), options.clone()).await.unwrap();
+        assert_eq!(from_reader.output_path, piped.display().to_string());
+        assert_eq!(std::fs::read(&piped).unwrap(), std::fs::read(&from_file.output_path).unwrap());
+        assert!(dir.path().join("stdin/stdin.txt.map").is_file());
+
+        let untyped = CompressCliOptions { file_type: None, ..options };
+        assert!(compress_reader_cli(std::io::Cursor::new(data), untyped).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_compress_to_stdout_writes_only_compressed_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("notes.txt");
+        std::fs::write(&input, b"line one\r\nline two\n\0binary tail\xff ".repeat(40)).unwrap();
+
+        let options = CompressCliOptions { file: Some(input.clone()), output_dir: Some(dir.path().join("file")), ..Default::default() };
+        let on_disk = std::fs::read(compress_file_cli(options).await.unwrap().output_path).unwrap();
+
+        let piped = dir.path().join("piped");
+        output::start_capture_with_data_on_stdout();
+        let options = CompressCliOptions { file: Some(input), output_dir: Some(piped.clone()), ..Default::default() };
+        let summary = compress_file_cli(options).await;
+        let captured = output::finish_capture_streams();
+
+        assert_eq!(summary.unwrap().output_path, "<stdout>");
+        assert_eq!(captured.stdout, on_disk);
+        assert!(captured.stderr.iter().any(|l| l.contains("Compression complete!")));
+        assert!(!piped.join("notes.txt.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_compress_writes_sqz1_with_sidecar_codec() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("repeat.txt");
+        let data = b"aaaaaaaaaabbbbbbbbbb".repeat(100);
+        std::fs::write(&input, &data).unwrap();
+
+        let options = CompressCliOptions { file: Some(input.clone()), output_dir: Some(dir.path().join("out")), ..Default::default() };
+        let summary = compress_file_cli(options.clone()).await.unwrap();
+        assert!(summary.compressed_size < summary.original_size);
+        let packed = std::fs::read(&summary.output_path).unwrap();
+        assert!(packed.starts_with(crate::compression::MAGIC));
+        assert_eq!(crate::compression::decompress_bytes(&packed).unwrap(), data);
+
+        std::fs::write(dir.path().join("repeat.txt.ssqconf"), r#"{"codec": "dictionary", "chunk_size": 5}"#).unwrap();
+        let summary = compress_file_cli(options).await.unwrap();
+        let packed = std::fs::read(&summary.output_path).unwrap();
+        let header = crate::compression::HEADER_LEN;
+        assert_eq!(&packed[header..header + 2], &[crate::compression::CODEC_DICTIONARY_SIZED, 5]);
+        assert_eq!(crate::compression::decompress_bytes(&packed).unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_incompressible_input_is_stored_raw() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("noise.bin");
+        // xorshift bytes: the chunk mapping only adds overhead
+        let mut x = 0x9e37_79b9_u32;
+        let data: Vec<u8> = (0..4096)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                x as u8
+            })
+            .collect();
+        std::fs::write(&input, &data).unwrap();
+        std::fs::write(dir.path().join("noise.bin.ssqconf"), r#"{"codec": "dictionary", "chunk_size": 4}"#).unwrap();
+
+        let options = CompressCliOptions { file: Some(input), raw: true, output_dir: Some(dir.path().join("out")), ..Default::default() };
+        let summary = compress_file_cli(options).await.unwrap();
+        assert!(summary.compressed_size as usize <= data.len() + crate::compression::HEADER_LEN + 1);
+        let packed = std::fs::read(&summary.output_path).unwrap();
+        assert_eq!(packed[crate::compression::HEADER_LEN], crate::compression::CODEC_STORED);
+        assert_eq!(crate::compression::decompress_file(&packed).unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_compress_dir_compresses_every_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("in");
+        std::fs::create_dir_all(input.join("nested")).unwrap();
+        let files = [("a.txt", 300), ("b.txt", 500), ("nested/a.txt", 700), ("nested/README", 400)];
+        for (name, len) in files {
+            std::fs::write(input.join(name), b"batch compression ".repeat(len / 18 + 1)).unwrap();
+        }
+        let out = dir.path().join("out");
+
+        let options = CompressCliOptions { output_dir: Some(out.clone()), ..Default::default() };
+        let summary = compress_dir_cli(&input, true, options).await.unwrap();
+        assert_eq!(summary.files_processed, 4);
+        assert!(summary.failures.is_empty());
+        let original: u64 = files.iter().map(|(name, _)| std::fs::metadata(input.join(name)).unwrap().len()).sum();
+        assert_eq!(summary.original_bytes, original);
+        // Same-named files keep their relative directory, and names without an extension get no stray dot
+        let compressed: u64 = ["a.txt.txt", "b.txt.txt", "nested/a.txt.txt", "nested/README.txt"]
+            .iter()
+            .map(|name| std::fs::metadata(out.join(name)).unwrap().len())
+            .sum();
+        assert_eq!(summary.compressed_bytes, compressed);
+
+        // Without --recursive the nested file is left alone
+        let options = CompressCliOptions { output_dir: Some(dir.path().join("flat")), ..Default::default() };
+        assert_eq!(compress_dir_cli(&input, false, options).await.unwrap().files_processed, 2);
+        assert!(out.join("nested/README.map").is_file());
+        assert!(extension_allowed(Path::new("x.TXT"), &[".txt".to_string()]));
+        assert!(!extension_allowed(Path::new("x.bin"), &["txt".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_verify_passes_for_matching_original_and_fails_otherwise() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("report.txt");
+        let original = b"verify me: the quick brown fox ".repeat(30);
+        std::fs::write(&input, &original).unwrap();
+        let options = CompressCliOptions { file: Some(input.clone()), output_dir: Some(dir.path().join("out")), ..Default::default() };
+        compress_file_cli(options).await.unwrap();
+
+        let compressed = VerifySource::Compressed(dir.path().join("out/report.txt.txt"));
+        let mapping = VerifySource::Mapping(dir.path().join("out/report.txt.map"));
+        assert_eq!(verify_cli(&compressed, &input), 0);
+        assert_eq!(verify_cli(&mapping, &input), 0);
+
+        let mut altered = original.clone();
+        altered[100] = b'#';
+        let altered_path = dir.path().join("altered.txt");
+        std::fs::write(&altered_path, &altered).unwrap();
+        assert_eq!(verify_cli(&compressed, &altered_path), 1);
+        assert_eq!(verify_cli(&mapping, &altered_path), 1);
+        assert_eq!(first_mismatch(&original, &altered), Some(100));
+        assert_eq!(first_mismatch(b"abc", b"ab"), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_mapping_and_codec_share_the_detected_chunk_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("period.txt");
+        std::fs::write(&input, b"abcde".repeat(100)).unwrap();
+        std::fs::write(dir.path().join("period.txt.ssqconf"), r#"{"codec": "dictionary"}"#).unwrap();
+        let options = CompressCliOptions { file: Some(input), raw: true, output_dir: Some(dir.path().join("out")), ..Default::default() };
+        let summary = compress_file_cli(options).await.unwrap();
+
+        // The content repeats every 5 bytes, away from the configured default
+        let mapping = crate::mapping::load_mapping(&dir.path().join("out/period.txt.map").to_string_lossy()).unwrap();
+        assert_eq!(mapping.chunk_size(), 5);
+        assert_ne!(crate::compression::configured_chunk_size(), 5);
+        let packed = std::fs::read(&summary.output_path).unwrap();
+        assert_eq!(packed[crate::compression::HEADER_LEN], crate::compression::CODEC_DICTIONARY_SIZED);
+        assert_eq!(packed[crate::compression::HEADER_LEN + 1], 5);
+    }
+
+    #[test]
+    fn test_empty_input_ratio_matches_compress_bytes() {
+        let outcome = crate::compression::compress_bytes(b"", Default::default()).unwrap();
+        let summary = CompressSummary::new(0, outcome.compressed.len(), String::new());
+        assert_eq!(summary.ratio, outcome.ratio);
+        assert_eq!(summary.ratio, 1.0);
+        assert_eq!(CompressSummary::new(200, 50, String::new()).ratio, 0.25);
+    }
+
+    #[tokio::test]
+    async fn test_decompress_restores_compressed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("report.bin");
+        let original: Vec<u8> = (0..=255u8).cycle().take(3000).collect();
+        std::fs::write(&input, &original).unwrap();
+        let options = CompressCliOptions { file: Some(input.clone()), raw: true, output_dir: Some(dir.path().join("out")), ..Default::default() };
+        compress_file_cli(options).await.unwrap();
+
+        let compressed = dir.path().join("out/report.bin.txt");
+        assert_eq!(default_decompressed_path(&compressed), dir.path().join("out/report.bin"));
+        assert_eq!(decompress_path_cli(&compressed, None), 0);
+        assert_eq!(std::fs::read(dir.path().join("out/report.bin")).unwrap(), original);
+
+        let out = dir.path().join("restored.bin");
+        assert_eq!(decompress_path_cli(&compressed, Some(&out)), 0);
+        assert_eq!(std::fs::read(&out).unwrap(), original);
+        assert_eq!(decompress_path_cli(&dir.path().join("missing.txt"), Some(&out)), 1);
+        assert_eq!(default_decompressed_path(Path::new("notes")), Path::new("notes.out"));
+    }
+
+    /// Reporter that adds up the steps it is told about
+    #[derive(Default)]
+    struct CountingReporter {
+        total: u64,
+        done: u64,
+        finished: bool,
+    }
+
+    impl ProgressReporter for CountingReporter {
+        fn start(&mut self, total: u64) {
+            self.total = total;
+        }
+        fn inc(&mut self, n: u64) {
+            self.done += n;
+        }
+        fn set_message(&mut self, _msg: &str) {}
+        fn finish(&mut self, _msg: &str) {
+            self.finished = true;
+        }
+    }
+
+    #[test]
+    fn test_generators_run_with_any_progress_reporter() {
+        let entries = compressed_combinations(2, 5, 12_000, &mut crate::progress::NoopReporter, |_| {});
+        assert_eq!(entries.len(), 12_000);
+        assert_eq!(entries[0], json!({"index": 5, "value": [0, 5]}));
+
+        let mut reporter = CountingReporter::default();
+        reporter.start(300);
+        let dict = ultra_compressed_combinations(2, 0, 300, &mut reporter, |_| {});
+        assert_eq!(dict.len(), 300);
+        assert_eq!(reporter.done, reporter.total);
+
+        let dir = tempfile::tempdir().unwrap();
+        let plan = GenerationProgress { length: 2, next_index: 0, end_index: 128 * 128, bytes_written: 0 };
+        let mut reporter = CountingReporter::default();
+        write_ascii_combinations(&dir.path().join("combos.txt"), plan, false, 128 * 128, &mut reporter).unwrap();
+        assert_eq!(reporter.done, 128 * 128);
+        assert!(!reporter.finished);
+    }
+
+    #[tokio::test]
+    async fn test_quiet_compress_prints_only_the_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("quiet.txt");
+        std::fs::write(&input, b"quiet mode keeps logs short ".repeat(40)).unwrap();
+        let options = || CompressCliOptions { file: Some(input.clone()), output_dir: Some(dir.path().join("out")), ..Default::default() };
+
+        output::start_capture(true);
+        compress_file_cli(options()).await.unwrap();
+        let lines = output::finish_capture();
+        assert_eq!(lines.len(), 1, "{:?}", lines);
+        assert!(lines[0].ends_with("quiet.txt.txt"), "{:?}", lines);
+
+        output::start_capture_with_verbosity(output::DEBUG);
+        compress_file_cli(options()).await.unwrap();
+        let lines = output::finish_capture();
+        assert!(lines.iter().any(|line| line.contains("Unique chunks:")));
+        assert!(lines.iter().any(|line| line.starts_with("Chunk 0: ")));
+        assert!(lines.iter().any(|line| line.starts_with("ASCII conversion alters")));
+    }
+
+    #[test]
+    fn test_tiny_file_is_stored_uncompressed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mapping_file = dir.path().join("abc.txt.map");
+        let stored_file = dir.path().join("abc.txt.txt");
+        let restored_file = dir.path().join("restored.txt");
+        let data = b"abc";
+        assert!((data.len() as u64) < get_config().file_processing.min_compress_size_bytes);
+
+        store_uncompressed(
+            data,
+            "abc.txt",
+            MappingFormat::Complete,
+            &mapping_file,
+            &stored_file,
+            false,
+        )
+        .unwrap();
+        // Still a stark-squeeze file, so decompress accepts it like any other output
+        let stored = std::fs::read(&stored_file).unwrap();
+        assert!(stored.starts_with(b"SQZ1"));
+        assert_eq!(crate::compression::decompress_bytes(&stored).unwrap(), data);
+        match crate::mapping::load_mapping(mapping_file.to_str().unwrap()).unwrap() {
+            MappingFile::Complete(mapping) => {
+                assert!(mapping.stored);
+                assert_eq!(mapping.compressed_data, data);
+            }
+            MappingFile::Minimal(_) => panic!("expected a complete mapping"),
+        }
+
+        let report = reconstruct_from_mapping(mapping_file.to_str().unwrap(), restored_file.to_str().unwrap()).unwrap();
+        assert_eq!(report.original_hash_verified, Some(true));
+        assert_eq!(std::fs::read(&restored_file).unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_upload_summary_for_mocked_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("notes.txt");
+        std::fs::write(&file_path, b"hello stark squeeze").unwrap();
+
+        let pins = Arc::new(AtomicUsize::new(0));
+        let submits = Arc::new(AtomicUsize::new(0));
+        let (pin_count, submit_count) = (pins.clone(), submits.clone());
+
+        let summary = upload_file_with(
+            &file_path,
+            PipelineOptions::default(),
+            |data, filename, _| async move {
+                pin_count.fetch_add(1, Ordering::SeqCst);
+                assert!(filename.ends_with("notes.txt.compressed"));
+                assert!(!data.is_empty());
+                Ok("QmMockCid".to_string())
+            },
+            |manifest| async move {
+                submit_count.fetch_add(1, Ordering::SeqCst);
+                assert_eq!(manifest.ipfs_cid.as_deref(), Some("QmMockCid"));
+                Ok(FieldElement::from(0xabcu32))
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(pins.load(Ordering::SeqCst), 1);
+        assert_eq!(submits.load(Ordering::SeqCst), 1);
+        assert_eq!(summary.cid.as_deref(), Some("QmMockCid"));
+        assert_eq!(summary.tx_hash, Some(FieldElement::from(0xabcu32)));
+        assert_eq!(summary.file_type, "txt");
+        assert_eq!(summary.original_size, 19);
+        // Each byte is expanded to an 8-character binary string before compression
+        assert_eq!(summary.encoded_size, 19 * 8);
+        assert_eq!(summary.uri.len(), 16);
+        assert_eq!(summary.hash_mode, HashMode::Sha256);
+
+        // A submitted upload leaves no resumable manifest behind
+        assert!(!summary.manifest_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_upload_data_cli_pins_then_submits_once() {
+        use crate::storage::mock::{CallLog, MockChainUploader, MockStorage};
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("report.txt");
+        std::fs::write(&file_path, b"orchestrated upload").unwrap();
+
+        let calls = CallLog::default();
+        let storage = MockStorage::new("QmMockCid", calls.clone());
+        let uploader = MockChainUploader::new(FieldElement::from(0x51u32), calls.clone());
+
+        let options = UploadCliOptions { allow_large: true, ..Default::default() };
+        let summary = upload_data_cli_with(Some(file_path.clone()), options, &storage, &uploader).await.unwrap();
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(
+            *calls,
+            vec![format!("pin {}.compressed", file_path.display()), format!("submit {}", summary.uri)]
+        );
+        assert_eq!(storage.pinned.lock().unwrap().len(), 1);
+        let submitted = uploader.submitted.lock().unwrap();
+        assert_eq!(submitted.len(), 1);
+        assert_eq!(submitted[0].ipfs_cid.as_deref(), Some("QmMockCid"));
+        assert_eq!(summary.cid.as_deref(), Some("QmMockCid"));
+        assert_eq!(summary.tx_hash, Some(FieldElement::from(0x51u32)));
+    }
+
+    #[tokio::test]
+    async fn test_upload_skips_ipfs_and_starknet() {
+        use crate::storage::mock::{CallLog, MockChainUploader, MockStorage};
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("offline.txt");
+        std::fs::write(&file_path, b"no network needed").unwrap();
+
+        let calls = CallLog::default();
+        let storage = MockStorage::new("QmUnused", calls.clone());
+        let uploader = MockChainUploader::new(FieldElement::from(1u32), calls.clone());
+        let options = UploadCliOptions {
+            allow_large: false,
+            skip_ipfs: true,
+            skip_starknet: true,
+            chunk_size: Some(ChunkSizeChoice::Fixed(5)),
+            ..Default::default()
+        };
+        let summary = upload_data_cli_with(Some(file_path), options, &storage, &uploader).await.unwrap();
+
+        assert!(calls.lock().unwrap().is_empty());
+        assert_eq!(summary.cid, None);
+        assert_eq!(summary.tx_hash, None);
+        let manifest = crate::upload_manifest::load_manifest(&summary.manifest_path).unwrap();
+        assert_eq!(manifest.stage, UploadStage::Pinned);
+        assert_eq!(manifest.chunk_size, 5);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_upload_sends_nothing() {
+        use crate::storage::mock::{CallLog, MockChainUploader, MockStorage};
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("preflight.txt");
+        std::fs::write(&file_path, b"how much would this cost").unwrap();
+
+        let calls = CallLog::default();
+        let storage = MockStorage::new("QmUnused", calls.clone());
+        let uploader = MockChainUploader::new(FieldElement::from(1u32), calls.clone());
+        let before = files_in_dir(dir.path(), true).unwrap();
+        let estimate = estimate_upload_cli_with(&file_path, UploadCliOptions::default(), &storage, &uploader).await.unwrap();
+
+        // No manifest or artifacts are left behind for resume-upload to pick up
+        assert_eq!(files_in_dir(dir.path(), true).unwrap(), before);
+        assert!(!manifest_path_for(&file_path).exists());
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert!(calls[0].starts_with("estimate "), "{:?}", calls);
+        assert!(uploader.submitted.lock().unwrap().is_empty());
+        assert!(!estimate.exceeds_cap());
+        assert_eq!(estimate.overall_fee, FieldElement::from(estimate.calldata_felts));
+    }
+
+    #[tokio::test]
+    async fn test_reconstruct_round_trips_upload() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("notes.txt");
+        let original = b"reconstruct me from the chain and IPFS, please".repeat(8);
+        std::fs::write(&file_path, &original).unwrap();
+
+        let pinned = std::sync::Mutex::new(Vec::new());
+        let submitted = std::sync::Mutex::new(None);
+        let summary = upload_file_with(
+            &file_path,
+            PipelineOptions::default(),
+            |bytes, _, _| {
+                *pinned.lock().unwrap() = bytes;
+                async { Ok("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_string()) }
+            },
+            |manifest| {
+                *submitted.lock().unwrap() = Some(manifest);
+                async { Ok(FieldElement::from(1u32)) }
+            },
+        )
+        .await
+        .unwrap();
+        let pinned = pinned.into_inner().unwrap();
+
+        // Read the record back from the event the submitted calldata would emit
+        let params = crate::storage::manifest_calldata_params(&submitted.into_inner().unwrap().unwrap()).unwrap();
+        let mut data = vec![
+            params.file_format,
+            FieldElement::from(params.compressed_by),
+            FieldElement::from(params.original_size),
+            FieldElement::from(params.final_size),
+            FieldElement::from(params.metadata.len()),
+        ];
+        data.extend(params.metadata);
+        let record = crate::starknet_client::parse_mapping_event(&summary.uri, &data, FieldElement::from(1u32)).unwrap();
+        assert_eq!(record.cid, summary.cid);
+        assert_eq!(record.raw, Some(false));
+
+        let out = dir.path().join("restored.txt");
+        let cid = summary.cid.clone().unwrap();
+        let restored = reconstruct_uri_with(
+            &summary.uri,
+            None,
+            &out,
+            false,
+            |_| async { Ok(record.clone()) },
+            |_| async { Ok(pinned.clone()) },
+        )
+        .await
+        .unwrap();
+        assert_eq!(restored.bytes_written, original.len());
+        assert_eq!(std::fs::read(&out).unwrap(), original);
+
+        // Bytes from the wrong CID are caught before decompression
+        let result = reconstruct_uri_with(
+            &summary.uri,
+            Some(cid.clone()),
+            &out,
+            false,
+            |_| async { Ok(record.clone()) },
+            |_| async { Ok(b"something else".to_vec()) },
+        )
+        .await;
+        assert!(matches!(result, Err(StarkSqueezeError::RetrievalError(_))));
+
+        let result = reconstruct_uri_with("not-a-uri", Some(cid), &out, false, |_| async { panic!() }, |_| async { panic!() }).await;
+        assert!(matches!(result, Err(StarkSqueezeError::InvalidInput(_))));
+    }
+
+    /// Needs a deployed contract with `uri` on it and the pinned CID; skipped otherwise
+    #[tokio::test]
+    async fn test_reconstruct_from_live_network() {
+        let (Ok(uri), Ok(cid)) = (std::env::var("RECONSTRUCT_TEST_URI"), std::env::var("RECONSTRUCT_TEST_CID")) else {
+            return;
+        };
+        if std::env::var("RPC_URL").is_err() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("restored");
+        assert_eq!(reconstruct_uri_cli(&uri, Some(cid), &out, false).await, 0);
+        assert!(out.exists());
+    }
+
+    #[tokio::test]
+    async fn test_upload_stops_when_the_pin_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("notes.txt");
+        std::fs::write(&file_path, b"pin me if you can").unwrap();
+
+        let result = upload_file_with(
+            &file_path,
+            PipelineOptions::default(),
+            |_, _, _| async { Err(IpfsError::ApiError("401 Unauthorized".to_string())) },
+            |_| async { panic!("submit must not be called") },
+        )
+        .await;
+
+        assert!(matches!(result, Err(StarkSqueezeError::UploadError(ref msg)) if msg.contains("401 Unauthorized")));
+        assert!(!manifest_path_for(&file_path).exists());
+    }
+
+    #[tokio::test]
+    async fn test_upload_rejects_missing_file() {
+        let result = upload_file_with(
+            Path::new("/nonexistent/file.bin"),
+            PipelineOptions::default(),
+            |_, _, _| async { Ok(String::new()) },
+            |_| async { Ok(FieldElement::from(0u32)) },
+        )
+        .await;
+        assert!(matches!(result, Err(StarkSqueezeError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn test_upload_refuses_file_above_size_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("huge.bin");
+        std::fs::write(&file_path, vec![0u8; 4096]).unwrap();
+
+        let result = upload_file_with(
+            &file_path,
+            PipelineOptions { size_limit: Some(1024), ..Default::default() },
+            |_, _, _| async { panic!("pin must not be called") },
+            |_| async { panic!("submit must not be called") },
+        )
+        .await;
+
+        match result {
+            Err(e @ StarkSqueezeError::FileTooLarge { size: 4096, threshold: 1024 }) => {
+                assert!(e.to_string().contains("--allow-large"));
+            }
+            other => panic!("expected FileTooLarge, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upload_enforces_max_size_unless_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("big.txt");
+        std::fs::write(&file_path, vec![b'a'; 4096]).unwrap();
+        let upload = |max_size| {
+            upload_file_with(
+                &file_path,
+                PipelineOptions { max_size, skip_starknet: true, ..Default::default() },
+                |_, _, _| async { Ok("QmBigCid".to_string()) },
+                |_| async { Ok(FieldElement::from(1u32)) },
+            )
+        };
+
+        match upload(Some(1024)).await {
+            Err(e @ StarkSqueezeError::FileExceedsMaxSize { size: 4096, max_size: 1024 }) => {
+                assert!(e.to_string().contains("--disable-file-size-limit"));
+            }
+            other => panic!("expected FileExceedsMaxSize, got {:?}", other),
+        }
+        assert_eq!(upload(None).await.unwrap().original_size, 4096);
+
+        let mut validation = crate::config::create_default_config().validation.file;
+        validation.max_size_mb = 2;
+        assert_eq!(max_upload_size(&validation, false), Some(2 * 1024 * 1024));
+        assert_eq!(max_upload_size(&validation, true), None);
+    }
+
+    #[test]
+    fn test_check_file_size_without_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("small.bin");
+        std::fs::write(&file_path, [1u8, 2, 3]).unwrap();
+        assert_eq!(check_file_size(&file_path, None).unwrap(), 3);
+        assert_eq!(check_file_size(&file_path, Some(3)).unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_quiet_upload_prints_only_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("quiet.txt");
+        std::fs::write(&file_path, b"quiet please").unwrap();
+
+        output::start_capture(true);
+        let summary = upload_file_with(
+            &file_path,
+            PipelineOptions::default(),
+            |_, _, _| async { Ok("QmQuietCid".to_string()) },
+            |_| async { Ok(FieldElement::from(1u32)) },
+        )
+        .await
+        .unwrap();
+        print_upload_summary(&summary);
+        let lines = output::finish_capture();
+
+        assert_eq!(lines, vec![summary.uri.clone()]);
+    }
+
+    #[test]
+    fn test_selftest_cli_exits_zero() {
+        output::start_capture(true);
+        let code = selftest_cli();
+        assert_eq!(output::finish_capture(), vec!["ok".to_string()]);
+        assert_eq!(code, 0);
+    }
+
+    #[tokio::test]
+    async fn test_ascii_loss_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = PipelineOptions { max_ascii_loss: Some(10.0), ..Default::default() };
+
+        // Mostly binary: 90% of bytes are outside the printable range
+        let binary_path = dir.path().join("blob.bin");
+        let binary: Vec<u8> = (0..1000u32).map(|i| if i % 10 == 0 { b'x' } else { (i % 32) as u8 }).collect();
+        std::fs::write(&binary_path, binary).unwrap();
+        let result = upload_file_with(
+            &binary_path,
+            options.clone(),
+            |_, _, _| async { panic!("pin must not be called") },
+            |_| async { panic!("submit must not be called") },
+        )
+        .await;
+        assert!(matches!(result, Err(StarkSqueezeError::AsciiLossExceeded { max_percent, .. }) if max_percent == 10.0));
+
+        // Mostly text: a single tab in a line of prose stays under the limit
+        let text_path = dir.path().join("notes.txt");
+        std::fs::write(&text_path, b"mostly text\twith one tab in a reasonably long line").unwrap();
+        let result = upload_file_with(
+            &text_path,
+            options,
+            |_, _, _| async { Ok("QmTextCid".to_string()) },
+            |_| async { Ok(FieldElement::from(2u32)) },
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_raw_upload_skips_binary_string_expansion() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("blob.bin");
+        std::fs::write(&file_path, [0u8, 1, 2, 200, 255, 10]).unwrap();
+
+        let options = PipelineOptions { raw: true, max_ascii_loss: Some(0.0), ..Default::default() };
+        let summary = upload_file_with(
+            &file_path,
+            options,
+            |data, _, _| async move {
+                assert_eq!(crate::compression::decompress_file(&data).unwrap(), vec![0u8, 1, 2, 200, 255, 10]);
+                Ok("QmRawCid".to_string())
+            },
+            |_| async { Ok(FieldElement::from(3u32)) },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(summary.encoded_size, 6);
+        assert_eq!(summary.ascii_converted_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn test_binary_string_artifact_is_opt_in() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("notes.txt");
+        std::fs::write(&file_path, b"AB").unwrap();
+        let artifacts = dir.path().join("artifacts");
+        let artifact = artifacts.join("notes.txt.binary-string.txt");
+        let upload = |options: PipelineOptions| {
+            upload_file_with(
+                &file_path,
+                options,
+                |_, _, _| async { Ok("QmCid".to_string()) },
+                |_| async { Ok(FieldElement::from(1u32)) },
+            )
+        };
+
+        assert_eq!(crate::debug_files::binary_string_dir(&get_config().debug), None);
+        upload(PipelineOptions::default()).await.unwrap();
+        assert!(!artifact.exists());
+
+        upload(PipelineOptions { binary_string_dir: Some(artifacts.clone()), ..Default::default() })
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&artifact).unwrap(), "0100000101000010");
+    }
+
+    #[tokio::test]
+    async fn test_upload_writes_debug_files_only_when_enabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("data.txt");
+        std::fs::write(&file_path, b"debug me").unwrap();
+        let debug_dir = dir.path().join("debug");
+        std::fs::create_dir(&debug_dir).unwrap();
+        let upload = |debug_run| {
+            upload_file_with(
+                &file_path,
+                PipelineOptions { debug_run, skip_starknet: true, ..Default::default() },
+                |_, _, _| async { Ok("QmCid".to_string()) },
+                |_| async { Ok(FieldElement::from(1u32)) },
+            )
+        };
+
+        // save_debug_files alone is not enough
+        let mut config = crate::config::create_default_config();
+        config.debug.save_debug_files = true;
+        config.debug.rotate = false;
+        config.storage.local.debug_files = false;
+        upload(DebugRun::start_with(&debug_dir, &config)).await.unwrap();
+        assert_eq!(std::fs::read_dir(&debug_dir).unwrap().count(), 0);
+
+        config.storage.local.debug_files = true;
+        upload(DebugRun::start_with(&debug_dir, &config)).await.unwrap();
+        for name in ["debug_original.bin", "debug_ascii.bin", "debug_packed.bin"] {
+            assert!(debug_dir.join(name).exists(), "{} missing", name);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_upload_opens_non_utf8_file_name() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join(OsStr::from_bytes(b"data\xff.bin"));
+        std::fs::write(&file_path, b"the real file").unwrap();
+        // A lossy conversion of the name would land on this decoy instead
+        std::fs::write(dir.path().join("data\u{FFFD}.bin"), b"decoy").unwrap();
+
+        // Stop before submitting, so the resumable manifest stays behind to be checked
+        let options = PipelineOptions { raw: true, skip_starknet: true, ..Default::default() };
+        let summary = upload_file_with(
+            &file_path,
+            options,
+            |data, _, _| async move {
+                assert_eq!(crate::compression::decompress_file(&data).unwrap(), b"the real file".to_vec());
+                Ok("QmCid".to_string())
+            },
+            |_| async { Ok(FieldElement::from(1u32)) },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(summary.original_size, b"the real file".len());
+        assert_eq!(summary.manifest_path, dir.path().join(OsStr::from_bytes(b"data\xff.bin.upload.json")));
+        assert!(summary.manifest_path.exists());
+    }
+}
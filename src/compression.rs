@@ -1,6 +1,9 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
+use std::io::{Read, Write};
+use std::str::FromStr;
 use serde::{Serialize, Deserialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,6 +25,7 @@ pub struct CompressionResult {
 pub enum CompressionError {
     CompressionFailed,
     Custom(String),
+    Io(std::io::Error),
 }
 
 impl fmt::Display for CompressionError {
@@ -29,39 +33,807 @@ impl fmt::Display for CompressionError {
         match self {
             CompressionError::CompressionFailed => write!(f, "Compression failed"),
             CompressionError::Custom(msg) => write!(f, "{}", msg),
+            CompressionError::Io(e) => write!(f, "IO error: {}", e),
         }
     }
 }
 
 impl Error for CompressionError {}
 
-/// Mock compression - just returns the original data
+impl From<std::io::Error> for CompressionError {
+    fn from(err: std::io::Error) -> Self {
+        CompressionError::Io(err)
+    }
+}
+
+/// Splits `data` into `chunk_size`-byte chunks and assigns each unique chunk a code.
+/// The final chunk may be shorter; `padding` records how many bytes it is short by.
+pub fn create_chunk_mapping(data: &[u8], chunk_size: usize) -> Result<CompressionMapping, CompressionError> {
+    if chunk_size == 0 {
+        return Err(CompressionError::Custom("Chunk size must be greater than zero".to_string()));
+    }
+
+    let mut chunk_to_code = HashMap::new();
+    let mut code_to_chunk = HashMap::new();
+    for chunk in data.chunks(chunk_size) {
+        if !chunk_to_code.contains_key(chunk) {
+            let code = u16::try_from(chunk_to_code.len()).map_err(|_| {
+                CompressionError::Custom(format!("More than {} unique chunks", u16::MAX as usize + 1))
+            })?;
+            chunk_to_code.insert(chunk.to_vec(), code);
+            code_to_chunk.insert(code, chunk.to_vec());
+        }
+    }
+
+    let remainder = data.len() % chunk_size;
+    let padding = if remainder == 0 { 0 } else { chunk_size - remainder };
+    let padding = u8::try_from(padding)
+        .map_err(|_| CompressionError::Custom(format!("Chunk size {} is too large", chunk_size)))?;
+
+    Ok(CompressionMapping {
+        chunk_size,
+        chunk_to_code,
+        padding,
+        original_size: data.len(),
+        code_to_chunk,
+    })
+}
+
+/// Replaces each chunk of `data` with its code from `mapping`
+pub fn compress_data(data: &[u8], mapping: &CompressionMapping) -> Result<Vec<u16>, CompressionError> {
+    data.chunks(mapping.chunk_size)
+        .map(|chunk| {
+            mapping.chunk_to_code.get(chunk).copied().ok_or_else(|| {
+                CompressionError::Custom(format!("Chunk {:?} not found in mapping", chunk))
+            })
+        })
+        .collect()
+}
+
+/// Magic bytes every `compress_file` output starts with
+pub const MAGIC: &[u8; 4] = b"SQZ1";
+/// Magic followed by the original length as a little-endian u64, before the codec byte
+pub const HEADER_LEN: usize = MAGIC.len() + 8;
+
+/// Header byte of data stored as-is, used when RLE would not make it smaller
+pub const CODEC_STORED: u8 = 0x00;
+/// Header byte of run-length encoded data
+pub const CODEC_RLE: u8 = 0x01;
+/// Header byte of dictionary-coded data with 4-byte chunks, written before the chunk
+/// size was configurable
+pub const CODEC_DICTIONARY: u8 = 0x02;
+/// Header byte of gzip-deflated data
+pub const CODEC_GZIP: u8 = 0x03;
+/// Header byte of dictionary-coded data whose chunk size is the byte after it
+pub const CODEC_DICTIONARY_SIZED: u8 = 0x04;
+
+/// Encoding `compress_file` applies, chosen by `compression.compression_method`.
+/// The codec's header byte is written after the length, so `decompress_file`
+/// picks the matching decoder on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Codec {
+    /// Stores the data unchanged
+    None,
+    /// Run-length encoding
+    Rle,
+    /// A table of unique fixed-size chunks plus a 10-bit code per chunk
+    Dictionary,
+    /// Deflate, in a gzip stream
+    Gzip,
+}
+
+impl Codec {
+    pub const ALL: [Codec; 4] = [Codec::None, Codec::Rle, Codec::Dictionary, Codec::Gzip];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Codec::None => "none",
+            Codec::Rle => "rle",
+            Codec::Dictionary => "dictionary",
+            Codec::Gzip => "gzip",
+        }
+    }
+
+    /// Codec of a header byte; `None` for bytes no codec writes
+    pub fn from_id(id: u8) -> Option<Codec> {
+        match id {
+            CODEC_STORED => Some(Codec::None),
+            CODEC_RLE => Some(Codec::Rle),
+            CODEC_DICTIONARY | CODEC_DICTIONARY_SIZED => Some(Codec::Dictionary),
+            CODEC_GZIP => Some(Codec::Gzip),
+            _ => None,
+        }
+    }
+
+    /// Header byte recording the codec
+    pub fn id(&self) -> u8 {
+        match self {
+            Codec::None => CODEC_STORED,
+            Codec::Rle => CODEC_RLE,
+            Codec::Dictionary => CODEC_DICTIONARY_SIZED,
+            Codec::Gzip => CODEC_GZIP,
+        }
+    }
+
+    /// Codec named by the configured `compression_method`, which is checked when the
+    /// configuration loads
+    pub fn configured() -> Codec {
+        crate::config::get_config().compression.compression_method.parse().unwrap_or(Codec::Rle)
+    }
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Codec {
+    type Err = CompressionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // `dictionary_based` is the name older configuration files use
+        if s == "dictionary_based" {
+            return Ok(Codec::Dictionary);
+        }
+        Codec::ALL.into_iter().find(|codec| codec.name() == s).ok_or_else(|| {
+            CompressionError::Custom(format!("Unknown compression method '{}' (expected one of: none, rle, dictionary, gzip)", s))
+        })
+    }
+}
+
+/// First two bytes of every gzip stream
+pub const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// `compress_file`, optionally followed by a gzip stage over the whole output
+pub fn compress_file_with_options(data: &[u8], gzip: bool) -> Result<Vec<u8>, CompressionError> {
+    let packed = compress_file(data)?;
+    if gzip {
+        gzip_bytes(&packed)
+    } else {
+        Ok(packed)
+    }
+}
+
+/// Deflates `data` into a gzip stream
+pub fn gzip_bytes(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Inflates `data` when it starts with `GZIP_MAGIC`, otherwise returns it unchanged.
+/// Output is capped at `file_processing.max_decompressed_mb`.
+pub fn gunzip_if_needed(data: &[u8]) -> Result<Cow<'_, [u8]>, CompressionError> {
+    if !data.starts_with(&GZIP_MAGIC) {
+        return Ok(Cow::Borrowed(data));
+    }
+    let limit = crate::config::get_config().file_processing.max_decompressed_mb.saturating_mul(1024 * 1024);
+    Ok(Cow::Owned(gunzip_limited(data, limit)?))
+}
+
+/// Inflates a gzip stream, failing once the output would exceed `limit` bytes
+fn gunzip_limited(data: &[u8], limit: u64) -> Result<Vec<u8>, CompressionError> {
+    let mut inflated = Vec::new();
+    flate2::read::GzDecoder::new(data)
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut inflated)
+        .map_err(|e| CompressionError::Custom(format!("Invalid gzip data: {}", e)))?;
+    if inflated.len() as u64 > limit {
+        return Err(CompressionError::Custom(format!("gzip data inflates beyond {} bytes", limit)));
+    }
+    Ok(inflated)
+}
+
+/// Shortest run worth encoding as a repeat packet
+const MIN_RUN: usize = 3;
+/// Longest run a single repeat packet holds
+const MAX_RUN: usize = 0x7F + MIN_RUN;
+/// Longest literal a single literal packet holds
+const MAX_LITERAL: usize = 0x80;
+
+/// How `compress_bytes` compresses
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompressOptions {
+    pub codec: Codec,
+    /// Chunk size of the dictionary codec
+    pub chunk_size: usize,
+    /// Gzip the whole output as a final stage
+    pub gzip: bool,
+}
+
+impl Default for CompressOptions {
+    /// The configured codec and chunk size, without gzip
+    fn default() -> Self {
+        Self { codec: Codec::configured(), chunk_size: configured_chunk_size(), gzip: false }
+    }
+}
+
+/// Result of `compress_bytes`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressionOutcome {
+    pub compressed: Vec<u8>,
+    pub original_size: u64,
+    pub compressed_size: u64,
+    /// Compressed size over original size; 1.0 for empty input
+    pub ratio: f64,
+    /// Codec the data ended up in: `Codec::None` when the requested one could not shrink it
+    pub codec: Codec,
+}
+
+/// Compresses `data` without printing or touching the filesystem.
+///
+/// ```
+/// use stark_squeeze::compression::{compress_bytes, decompress_file, Codec, CompressOptions};
+///
+/// let data = b"aaaaaaaaaabbbbbbbbbb".repeat(10);
+/// let outcome = compress_bytes(&data, CompressOptions { codec: Codec::Rle, ..Default::default() }).unwrap();
+/// assert!(outcome.compressed_size < outcome.original_size);
+/// assert_eq!(decompress_file(&outcome.compressed).unwrap(), data);
+/// ```
+pub fn compress_bytes(data: &[u8], opts: CompressOptions) -> Result<CompressionOutcome, CompressionError> {
+    let packed = compress_file_with_chunk_size(data, opts.codec, opts.chunk_size)?;
+    let codec = Codec::from_id(packed[HEADER_LEN]).unwrap_or(Codec::None);
+    let compressed = if opts.gzip { gzip_bytes(&packed)? } else { packed };
+    let original_size = data.len() as u64;
+    let compressed_size = compressed.len() as u64;
+    let ratio = size_ratio(original_size, compressed_size);
+    Ok(CompressionOutcome { compressed, original_size, compressed_size, ratio, codec })
+}
+
+/// Compressed size over original size; 1.0 for empty input, which nothing can shrink
+pub fn size_ratio(original_size: u64, compressed_size: u64) -> f64 {
+    if original_size == 0 {
+        1.0
+    } else {
+        compressed_size as f64 / original_size as f64
+    }
+}
+
+/// Reverses `compress_bytes`, including its optional gzip stage, without printing or
+/// touching the filesystem. Input that doesn't start with `MAGIC` is rejected.
+///
+/// ```
+/// use stark_squeeze::compression::{compress_bytes, decompress_bytes, CompressOptions};
+///
+/// let outcome = compress_bytes(b"hello hello hello", CompressOptions { gzip: true, ..Default::default() }).unwrap();
+/// assert_eq!(decompress_bytes(&outcome.compressed).unwrap(), b"hello hello hello");
+/// assert!(decompress_bytes(b"plain text").is_err());
+/// ```
+pub fn decompress_bytes(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    let data = gunzip_if_needed(data)?;
+    if !data.starts_with(MAGIC) {
+        let found = &data[..data.len().min(MAGIC.len())];
+        return Err(CompressionError::Custom(format!(
+            "Unrecognized header {:02x?}; stark-squeeze data starts with {:?}",
+            found,
+            std::str::from_utf8(MAGIC).unwrap_or_default()
+        )));
+    }
+    decompress_file(&data)
+}
+
+/// Compresses `data` with the configured codec; see `compress_file_with_codec`
 pub fn compress_file(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
-    // Mock compression - return original data
-    Ok(data.to_vec())
+    compress_file_with_codec(data, Codec::configured())
 }
 
-/// Mock decompression - just returns the original data
+/// Chunk size the dictionary codec uses: `compression.chunk_size_range.default`,
+/// clamped to the range
+pub fn configured_chunk_size() -> usize {
+    let range = &crate::config::get_config().compression.chunk_size_range;
+    range.clamp(range.default)
+}
+
+/// Compresses `data` with `codec` and the configured chunk size; see
+/// `compress_file_with_chunk_size`
+pub fn compress_file_with_codec(data: &[u8], codec: Codec) -> Result<Vec<u8>, CompressionError> {
+    compress_file_with_chunk_size(data, codec, configured_chunk_size())
+}
+
+/// Compresses `data` with `codec`, prefixed by `MAGIC`, the original length and the
+/// codec's header byte. The dictionary codec splits the data into `chunk_size`-byte
+/// chunks and records that size after the header byte; other codecs ignore it.
+///
+/// RLE packets start with a control byte: with the high bit set, the next byte is
+/// repeated `(control & 0x7F) + 3` times; otherwise `control + 1` literal bytes
+/// follow. Input the codec cannot shrink is stored after a `CODEC_STORED` header
+/// instead, so output is never more than `HEADER_LEN + 1` bytes larger than the input.
+pub fn compress_file_with_chunk_size(data: &[u8], codec: Codec, chunk_size: usize) -> Result<Vec<u8>, CompressionError> {
+    let encoded = match codec {
+        Codec::None => None,
+        Codec::Rle => Some(rle_encode(data)),
+        Codec::Dictionary => {
+            let size_byte = u8::try_from(chunk_size)
+                .ok()
+                .filter(|&size| size > 0)
+                .ok_or_else(|| CompressionError::Custom(format!("Chunk size {} must be between 1 and 255", chunk_size)))?;
+            dictionary_encode(data, chunk_size)?.map(|encoded| [&[size_byte][..], &encoded].concat())
+        }
+        Codec::Gzip => Some(gzip_bytes(data)?),
+    };
+    let (codec, payload) = match &encoded {
+        Some(encoded) if encoded.len() < data.len() => (codec.id(), encoded.as_slice()),
+        _ => (CODEC_STORED, data),
+    };
+    let mut packed = Vec::with_capacity(HEADER_LEN + 1 + payload.len());
+    packed.extend_from_slice(MAGIC);
+    packed.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    packed.push(codec);
+    packed.extend_from_slice(payload);
+    Ok(packed)
+}
+
+/// Exact inverse of `compress_file`. Rejects input without the stark-squeeze magic
+/// and output whose length differs from the recorded one.
 pub fn decompress_file(packed: &[u8]) -> Result<Vec<u8>, CompressionError> {
-    // Mock decompression - return original data
-    Ok(packed.to_vec())
+    if packed.len() < HEADER_LEN || &packed[..MAGIC.len()] != MAGIC {
+        return Err(CompressionError::Custom("not a stark-squeeze file".to_string()));
+    }
+    let mut length_bytes = [0u8; 8];
+    length_bytes.copy_from_slice(&packed[MAGIC.len()..HEADER_LEN]);
+    let expected_len = u64::from_le_bytes(length_bytes);
+
+    let data = match packed[HEADER_LEN..].split_first() {
+        Some((&CODEC_STORED, data)) => data.to_vec(),
+        Some((&CODEC_RLE, encoded)) => rle_decode(encoded)?,
+        Some((&CODEC_DICTIONARY, encoded)) => dictionary_decode(encoded, expected_len, 4)?,
+        Some((&CODEC_DICTIONARY_SIZED, encoded)) => match encoded.split_first() {
+            Some((&chunk_size, encoded)) if chunk_size > 0 => dictionary_decode(encoded, expected_len, chunk_size as usize)?,
+            _ => return Err(CompressionError::Custom("Dictionary data is missing its chunk size".to_string())),
+        },
+        Some((&CODEC_GZIP, encoded)) => gunzip_limited(encoded, expected_len)?,
+        Some((header, _)) => return Err(CompressionError::Custom(format!("Unknown codec header 0x{:02x}", header))),
+        None => return Err(CompressionError::Custom("Compressed data is missing its codec header".to_string())),
+    };
+    if data.len() as u64 != expected_len {
+        return Err(CompressionError::Custom(format!(
+            "Decompressed {} bytes but the header records {}",
+            data.len(),
+            expected_len
+        )));
+    }
+    Ok(data)
+}
+
+/// File extension of archives of concatenated frames
+pub const ARCHIVE_EXTENSION: &str = "sqza";
+
+/// Writes `data` prefixed by its length as a little-endian u32, so several blobs can
+/// share one stream
+pub fn write_frame<W: Write>(writer: &mut W, data: &[u8]) -> std::io::Result<()> {
+    let len = u32::try_from(data.len()).map_err(|_| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{} bytes do not fit in one frame", data.len()))
+    })?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(data)
+}
+
+/// Reads the next frame written by `write_frame`; `None` at a clean end of the stream.
+/// A stream ending inside a frame is an `UnexpectedEof` error.
+pub fn read_frame<R: Read>(reader: &mut R) -> std::io::Result<Option<Vec<u8>>> {
+    let mut len_bytes = [0u8; 4];
+    let mut filled = 0;
+    while filled < len_bytes.len() {
+        match reader.read(&mut len_bytes[filled..])? {
+            0 if filled == 0 => return Ok(None),
+            0 => return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "stream ends inside a frame length")),
+            read => filled += read,
+        }
+    }
+    let len = u32::from_le_bytes(len_bytes) as u64;
+    let mut data = Vec::new();
+    reader.take(len).read_to_end(&mut data)?;
+    if data.len() as u64 != len {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            format!("frame holds {} of its {} bytes", data.len(), len),
+        ));
+    }
+    Ok(Some(data))
+}
+
+/// Reads frames of `compress_file` output until the end of `reader` and decompresses
+/// each, in order
+pub fn decompress_archive<R: Read>(reader: &mut R) -> Result<Vec<Vec<u8>>, CompressionError> {
+    let mut files = Vec::new();
+    while let Some(frame) = read_frame(reader)? {
+        files.push(decompress_file(&frame)?);
+    }
+    Ok(files)
+}
+
+/// Length of the run of identical bytes at the start of `data`, capped at `MAX_RUN`
+fn run_length(data: &[u8]) -> usize {
+    data.iter().take(MAX_RUN).take_while(|&&b| b == data[0]).count()
+}
+
+fn rle_encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut literal_start = 0;
+    let mut i = 0;
+    while i < data.len() {
+        let run = run_length(&data[i..]);
+        if run >= MIN_RUN {
+            flush_literals(&mut out, &data[literal_start..i]);
+            out.push(0x80 | (run - MIN_RUN) as u8);
+            out.push(data[i]);
+            i += run;
+            literal_start = i;
+        } else {
+            i += run;
+        }
+    }
+    flush_literals(&mut out, &data[literal_start..]);
+    out
 }
 
-/// Mock function for packing 10-bit values
-pub fn pack_10bit_values(values: &[u16]) -> Vec<u8> {
-    // Mock implementation - just convert to bytes
-    values.iter().flat_map(|&val| val.to_le_bytes()).collect()
+fn flush_literals(out: &mut Vec<u8>, literals: &[u8]) {
+    for chunk in literals.chunks(MAX_LITERAL) {
+        out.push((chunk.len() - 1) as u8);
+        out.extend_from_slice(chunk);
+    }
 }
 
-/// Mock function for unpacking 10-bit values
-pub fn unpack_10bit_values(packed: &[u8]) -> Vec<u16> {
-    // Mock implementation - just convert from bytes
-    let mut values = Vec::new();
-    for chunk in packed.chunks(2) {
-        if chunk.len() == 2 {
-            let val = u16::from_le_bytes([chunk[0], chunk[1]]);
-            values.push(val);
+fn rle_decode(encoded: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    let mut out = Vec::with_capacity(encoded.len() * 2);
+    let mut i = 0;
+    while i < encoded.len() {
+        let control = encoded[i] as usize;
+        i += 1;
+        if control & 0x80 != 0 {
+            let byte = *encoded.get(i).ok_or_else(|| {
+                CompressionError::Custom(format!("Truncated run at offset {}", i - 1))
+            })?;
+            out.extend(std::iter::repeat_n(byte, (control & 0x7F) + MIN_RUN));
+            i += 1;
+        } else {
+            let literals = encoded.get(i..i + control + 1).ok_or_else(|| {
+                CompressionError::Custom(format!("Truncated literal run at offset {}", i - 1))
+            })?;
+            out.extend_from_slice(literals);
+            i += control + 1;
         }
     }
-    values
-} 
\ No newline at end of file
+    Ok(out)
+}
+
+/// Entries the dictionary codec's 10-bit codes can address
+const MAX_DICTIONARY_ENTRIES: usize = 1 << PACKED_BITS;
+
+/// Dictionary codec payload: the entry count as a little-endian u16, the entries in
+/// code order, the 10-bit packed code of each whole chunk, then the trailing bytes
+/// that don't fill a chunk. `None` when the data has too many distinct chunks.
+fn dictionary_encode(data: &[u8], chunk_size: usize) -> Result<Option<Vec<u8>>, CompressionError> {
+    let whole = &data[..data.len() - data.len() % chunk_size];
+    // Give up once the table outgrows the 10-bit codes, before the mapping's own
+    // u16 limit turns high-entropy input into an error
+    let mut unique = std::collections::HashSet::new();
+    for chunk in whole.chunks(chunk_size) {
+        if unique.insert(chunk) && unique.len() > MAX_DICTIONARY_ENTRIES {
+            return Ok(None);
+        }
+    }
+    let mapping = create_chunk_mapping(whole, chunk_size)?;
+    let entries = mapping.code_to_chunk.len();
+    let codes = compress_data(whole, &mapping)?;
+
+    let mut out = Vec::with_capacity(2 + entries * chunk_size + (codes.len() * PACKED_BITS).div_ceil(8));
+    out.extend_from_slice(&(entries as u16).to_le_bytes());
+    for code in 0..entries as u16 {
+        out.extend_from_slice(&mapping.code_to_chunk[&code]);
+    }
+    out.extend_from_slice(&pack_10bit_values(&codes)?);
+    out.extend_from_slice(&data[whole.len()..]);
+    Ok(Some(out))
+}
+
+/// Reverses `dictionary_encode` for data that was `original_len` bytes long
+fn dictionary_decode(encoded: &[u8], original_len: u64, chunk_size: usize) -> Result<Vec<u8>, CompressionError> {
+    let truncated = || CompressionError::Custom("Truncated dictionary data".to_string());
+    let entries = u16::from_le_bytes(encoded.get(..2).ok_or_else(truncated)?.try_into().unwrap()) as usize;
+    let table_end = 2 + entries * chunk_size;
+    let table = encoded.get(2..table_end).ok_or_else(truncated)?;
+
+    // Every chunk takes at least a byte of codes, so a longer recorded length can't be real
+    let count = (original_len / chunk_size as u64) as usize;
+    let tail_len = (original_len % chunk_size as u64) as usize;
+    if count > encoded.len() {
+        return Err(truncated());
+    }
+    let codes_end = table_end + (count * PACKED_BITS).div_ceil(8);
+    let codes = unpack_10bit_values(encoded.get(table_end..codes_end).ok_or_else(truncated)?, count)?;
+    let tail = encoded.get(codes_end..codes_end + tail_len).ok_or_else(truncated)?;
+
+    let mut out = Vec::with_capacity(original_len as usize);
+    for code in codes {
+        let start = code as usize * chunk_size;
+        let chunk = table.get(start..start + chunk_size).ok_or_else(|| {
+            CompressionError::Custom(format!("Dictionary code {} is outside the {} entries", code, entries))
+        })?;
+        out.extend_from_slice(chunk);
+    }
+    out.extend_from_slice(tail);
+    Ok(out)
+}
+
+/// Bits used per packed value
+const PACKED_BITS: usize = 10;
+
+/// Bit-packs values below 1024 into 10 bits each, most significant bit first.
+/// Four values fit in five bytes; a trailing partial byte is padded with zero bits.
+pub fn pack_10bit_values(values: &[u16]) -> Result<Vec<u8>, CompressionError> {
+    let mut packed = Vec::with_capacity((values.len() * PACKED_BITS).div_ceil(8));
+    let mut buffer: u32 = 0;
+    let mut buffered_bits = 0;
+    for &value in values {
+        if value >= 1 << PACKED_BITS {
+            return Err(CompressionError::Custom(format!("Value {} does not fit in 10 bits", value)));
+        }
+        buffer = (buffer << PACKED_BITS) | value as u32;
+        buffered_bits += PACKED_BITS;
+        while buffered_bits >= 8 {
+            buffered_bits -= 8;
+            packed.push((buffer >> buffered_bits) as u8);
+        }
+        buffer &= (1 << buffered_bits) - 1;
+    }
+    if buffered_bits > 0 {
+        packed.push((buffer << (8 - buffered_bits)) as u8);
+    }
+    Ok(packed)
+}
+
+/// Reads `count` 10-bit values back out of `pack_10bit_values` output
+pub fn unpack_10bit_values(packed: &[u8], count: usize) -> Result<Vec<u16>, CompressionError> {
+    let needed = (count * PACKED_BITS).div_ceil(8);
+    if packed.len() < needed {
+        return Err(CompressionError::Custom(format!(
+            "{} values need {} packed bytes, got {}",
+            count, needed, packed.len()
+        )));
+    }
+    let mut values = Vec::with_capacity(count);
+    let mut buffer: u32 = 0;
+    let mut buffered_bits = 0;
+    let mut bytes = packed.iter();
+    while values.len() < count {
+        while buffered_bits < PACKED_BITS {
+            // `needed` guarantees enough bytes remain
+            buffer = (buffer << 8) | *bytes.next().unwrap_or(&0) as u32;
+            buffered_bits += 8;
+        }
+        buffered_bits -= PACKED_BITS;
+        values.push((buffer >> buffered_bits) as u16);
+        buffer &= (1 << buffered_bits) - 1;
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_mapping_round_trip() {
+        let data = b"abcabcabcab";
+        let mapping = create_chunk_mapping(data, 3).unwrap();
+        assert_eq!(mapping.chunk_to_code.len(), 2);
+        assert_eq!(mapping.padding, 1);
+        assert_eq!(mapping.original_size, data.len());
+
+        let codes = compress_data(data, &mapping).unwrap();
+        assert_eq!(codes, vec![0, 0, 0, 1]);
+        let restored: Vec<u8> = codes.iter().flat_map(|c| mapping.code_to_chunk[c].clone()).collect();
+        assert_eq!(restored, data);
+    }
+
+    #[test]
+    fn test_zero_chunk_size_rejected() {
+        assert!(create_chunk_mapping(b"data", 0).is_err());
+    }
+
+    #[test]
+    fn test_rle_compresses_runs() {
+        let data = [vec![7u8; 200], b"abc".to_vec(), vec![0u8; 5]].concat();
+        let packed = compress_file(&data).unwrap();
+        assert_eq!(packed[HEADER_LEN], CODEC_RLE);
+        // 200 = 130 + 70: two repeat packets, one literal packet, one repeat packet
+        assert_eq!(packed.len(), HEADER_LEN + 1 + 2 + 2 + 4 + 2);
+        assert_eq!(decompress_file(&packed).unwrap(), data);
+    }
+
+    #[test]
+    fn test_incompressible_input_is_stored() {
+        let data: Vec<u8> = (0..=255u8).collect();
+        let packed = compress_file(&data).unwrap();
+        assert_eq!(packed[HEADER_LEN], CODEC_STORED);
+        assert_eq!(packed.len(), HEADER_LEN + data.len() + 1);
+        assert_eq!(decompress_file(&packed).unwrap(), data);
+        assert_eq!(decompress_file(&compress_file(&[]).unwrap()).unwrap(), Vec::<u8>::new());
+    }
+
+    /// `MAGIC` and a recorded length followed by `body`
+    fn with_header(len: u64, body: &[u8]) -> Vec<u8> {
+        [MAGIC.as_slice(), &len.to_le_bytes(), body].concat()
+    }
+
+    #[test]
+    fn test_every_codec_round_trips() {
+        let text = b"the quick brown fox jumps over the lazy dog; ".repeat(40);
+        let inputs: [&[u8]; 4] = [&text, &[9u8; 300], b"abcde", b""];
+        for codec in Codec::ALL {
+            assert_eq!(codec.name().parse::<Codec>().unwrap(), codec);
+            for data in inputs {
+                let packed = compress_file_with_codec(data, codec).unwrap();
+                assert_eq!(decompress_file(&packed).unwrap(), data, "{} on {} bytes", codec, data.len());
+            }
+            let header = compress_file_with_codec(&[9u8; 300], codec).unwrap()[HEADER_LEN];
+            assert_eq!(header, codec.id(), "{} should shrink a run", codec);
+        }
+        assert_eq!("dictionary_based".parse::<Codec>().unwrap(), Codec::Dictionary);
+        assert!("lz77".parse::<Codec>().is_err());
+
+        // Too many distinct chunks for 10-bit codes falls back to storing
+        let noisy: Vec<u8> = (0..8192u32).flat_map(|i| i.to_le_bytes()).collect();
+        assert_eq!(compress_file_with_codec(&noisy, Codec::Dictionary).unwrap()[HEADER_LEN], CODEC_STORED);
+        // A recorded length beyond the codes is rejected rather than allocated
+        let packed = compress_file_with_codec(&text, Codec::Dictionary).unwrap();
+        let mut bogus = packed.clone();
+        bogus[MAGIC.len()..HEADER_LEN].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(decompress_file(&bogus).is_err());
+    }
+
+    #[test]
+    fn test_dictionary_falls_back_to_stored_for_high_entropy_input() {
+        // Well over 65536 unique 4-byte chunks
+        let random = crate::benchmark::sample_inputs(1 << 20).swap_remove(2).data;
+        let outcome = compress_bytes(&random, CompressOptions { codec: Codec::Dictionary, chunk_size: 4, gzip: false }).unwrap();
+        assert_eq!(outcome.codec, Codec::None);
+        assert_eq!(outcome.compressed[HEADER_LEN], CODEC_STORED);
+        assert_eq!(decompress_bytes(&outcome.compressed).unwrap(), random);
+    }
+
+    #[test]
+    fn test_dictionary_chunk_size_is_recorded() {
+        let text = b"abcdefgh".repeat(100);
+        for chunk_size in [2, 3, 8] {
+            let packed = compress_file_with_chunk_size(&text, Codec::Dictionary, chunk_size).unwrap();
+            assert_eq!(&packed[HEADER_LEN..HEADER_LEN + 2], &[CODEC_DICTIONARY_SIZED, chunk_size as u8]);
+            assert_eq!(decompress_file(&packed).unwrap(), text);
+        }
+        assert!(compress_file_with_chunk_size(&text, Codec::Dictionary, 0).is_err());
+
+        // Files written before the chunk size was recorded decode with 4-byte chunks
+        let mut legacy = compress_file_with_chunk_size(&text, Codec::Dictionary, 4).unwrap();
+        legacy.remove(HEADER_LEN + 1);
+        legacy[HEADER_LEN] = CODEC_DICTIONARY;
+        assert_eq!(decompress_file(&legacy).unwrap(), text);
+    }
+
+    #[test]
+    fn test_decompress_bytes_reverses_compress_bytes() {
+        let data = b"the quick brown fox jumps over the lazy dog; ".repeat(20);
+        for codec in Codec::ALL {
+            for gzip in [false, true] {
+                let outcome = compress_bytes(&data, CompressOptions { codec, chunk_size: 4, gzip }).unwrap();
+                assert_eq!(decompress_bytes(&outcome.compressed).unwrap(), data, "{} gzip={}", codec, gzip);
+            }
+        }
+        match decompress_bytes(b"PK\x03\x04zip data") {
+            Err(CompressionError::Custom(msg)) => assert!(msg.starts_with("Unrecognized header [50, 4b, 03, 04]"), "{}", msg),
+            other => panic!("expected an unrecognized header error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_frames_read_back_in_order() {
+        let mut stream = Vec::new();
+        for data in [&b"first"[..], b"", b"third frame"] {
+            write_frame(&mut stream, data).unwrap();
+        }
+        let mut reader = stream.as_slice();
+        assert_eq!(read_frame(&mut reader).unwrap().unwrap(), b"first");
+        assert_eq!(read_frame(&mut reader).unwrap().unwrap(), b"");
+        assert_eq!(read_frame(&mut reader).unwrap().unwrap(), b"third frame");
+        assert_eq!(read_frame(&mut reader).unwrap(), None);
+        // Streams cut inside a frame's body or length are errors, not a short frame
+        assert!(read_frame(&mut &stream[..7]).is_err());
+        assert!(read_frame(&mut &stream[..2]).is_err());
+
+        let mut archive = Vec::new();
+        let files = [b"aaaaaaaaaaaaaaaa".to_vec(), Vec::new(), b"the last file".to_vec()];
+        for file in &files {
+            write_frame(&mut archive, &compress_file(file).unwrap()).unwrap();
+        }
+        assert_eq!(decompress_archive(&mut archive.as_slice()).unwrap(), files);
+    }
+
+    #[test]
+    fn test_decompress_rejects_bad_input() {
+        assert!(decompress_file(&with_header(0, &[])).is_err());
+        assert!(decompress_file(&with_header(2, &[0x7e, 1, 2])).is_err());
+        assert!(decompress_file(&with_header(3, &[CODEC_RLE, 0x85])).is_err());
+        assert!(decompress_file(&with_header(4, &[CODEC_RLE, 0x03, b'a'])).is_err());
+    }
+
+    #[test]
+    fn test_gzip_round_trip() {
+        let data = b"{\"combinations\": [\"aaaa\", \"aaab\", \"aaac\", \"aaad\"]}".repeat(50);
+        let gzipped = compress_file_with_options(&data, true).unwrap();
+        assert!(gzipped.starts_with(&GZIP_MAGIC));
+        assert!(gzipped.len() < compress_file(&data).unwrap().len());
+        assert_eq!(decompress_file(&gunzip_if_needed(&gzipped).unwrap()).unwrap(), data);
+
+        let plain = compress_file_with_options(&data, false).unwrap();
+        assert!(matches!(gunzip_if_needed(&plain).unwrap(), Cow::Borrowed(_)));
+        assert!(gunzip_if_needed(&[0x1f, 0x8b, 0x08]).is_err());
+    }
+
+    #[test]
+    fn test_gzip_inflation_is_capped() {
+        let bomb = gzip_bytes(&vec![0u8; 1 << 20]).unwrap();
+        assert_eq!(gunzip_limited(&bomb, 1 << 20).unwrap().len(), 1 << 20);
+        assert!(gunzip_limited(&bomb, 1024).is_err());
+
+        // A gzip frame whose header understates its length is rejected without inflating it all
+        let mut packed = MAGIC.to_vec();
+        packed.extend_from_slice(&16u64.to_le_bytes());
+        packed.push(CODEC_GZIP);
+        packed.extend_from_slice(&bomb);
+        let err = decompress_file(&packed).unwrap_err();
+        assert!(err.to_string().contains("inflates beyond 16 bytes"));
+    }
+
+    #[test]
+    fn test_decompress_rejects_foreign_files() {
+        let data = b"hello hello hello";
+        assert_eq!(decompress_file(&compress_file(data).unwrap()).unwrap(), data);
+
+        for bogus in [&b""[..], b"SQZ", b"%PDF-1.7 not ours at all", &[CODEC_STORED, 1, 2, 3]] {
+            match decompress_file(bogus) {
+                Err(CompressionError::Custom(msg)) => assert_eq!(msg, "not a stark-squeeze file"),
+                other => panic!("expected a magic mismatch, got {:?}", other),
+            }
+        }
+
+        // Right magic, wrong recorded length
+        let mut packed = compress_file(data).unwrap();
+        packed[MAGIC.len()] += 1;
+        assert!(matches!(decompress_file(&packed), Err(CompressionError::Custom(msg)) if msg.contains("header records")));
+    }
+
+    #[test]
+    fn test_10bit_packing() {
+        let values = [0, 1023, 512, 7];
+        let packed = pack_10bit_values(&values).unwrap();
+        assert_eq!(packed.len(), 5);
+        assert_eq!(packed, vec![0b0000_0000, 0b0011_1111, 0b1111_1000, 0b0000_0000, 0b0000_0111]);
+        assert_eq!(unpack_10bit_values(&packed, 4).unwrap(), values);
+
+        // Three values leave a partial trailing byte
+        let packed = pack_10bit_values(&values[..3]).unwrap();
+        assert_eq!(packed.len(), 4);
+        assert_eq!(unpack_10bit_values(&packed, 3).unwrap(), &values[..3]);
+
+        assert!(pack_10bit_values(&[1024]).is_err());
+        assert!(unpack_10bit_values(&packed, 4).is_err());
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_rle_round_trip(data in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..2048)) {
+            let packed = compress_file(&data).unwrap();
+            proptest::prop_assert!(packed.len() <= HEADER_LEN + data.len() + 1);
+            proptest::prop_assert_eq!(decompress_file(&packed).unwrap(), data);
+        }
+
+        #[test]
+        fn prop_rle_round_trip_runs(runs in proptest::collection::vec((proptest::prelude::any::<u8>(), 1usize..300), 0..64)) {
+            let data: Vec<u8> = runs.iter().flat_map(|&(byte, len)| std::iter::repeat_n(byte, len)).collect();
+            let packed = compress_file(&data).unwrap();
+            proptest::prop_assert_eq!(decompress_file(&packed).unwrap(), data);
+        }
+    }
+}
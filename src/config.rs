@@ -3,9 +3,15 @@ use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::backend::CompressionBackend;
+use crate::flush::FlushStrategy;
+use crate::ipfs_client::IpfsProvider;
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Config {
     pub version: String,
     pub description: String,
@@ -24,24 +30,44 @@ pub struct Config {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CompressionConfig {
     pub target_compression_ratio: f64,
     pub current_compression_ratio: f64,
+    /// How far, in percentage points, `--self-check` lets the measured ratio stray from `current_compression_ratio`
+    #[serde(default = "default_ratio_tolerance_percent")]
+    pub ratio_tolerance_percent: f64,
     pub compression_method: String,
     pub chunk_size_range: ChunkSizeRange,
     pub optimal_compression_threshold: f64,
     pub max_unique_chunks: u8,
     pub compression_ratios: HashMap<String, CompressionRatio>,
+    /// Backend that produces the compressed output file
+    #[serde(default)]
+    pub backend: CompressionBackend,
+}
+
+fn default_ratio_tolerance_percent() -> f64 {
+    10.0
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ChunkSizeRange {
     pub min: usize,
     pub max: usize,
     pub default: usize,
 }
 
+impl ChunkSizeRange {
+    /// `chunk_size` moved into `min..=max`
+    pub fn clamp(&self, chunk_size: usize) -> usize {
+        chunk_size.clamp(self.min, self.max)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CompressionRatio {
     pub input_chars: usize,
     pub output_bytes: usize,
@@ -50,12 +76,17 @@ pub struct CompressionRatio {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DictionaryConfig {
     pub ascii_combinations: AsciiCombinationsConfig,
     pub ultra_compressed: UltraCompressedConfig,
+    /// Dictionary used for compression and by the server (overridden by `--dictionary`)
+    #[serde(default)]
+    pub path: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AsciiCombinationsConfig {
     pub default_length: usize,
     pub default_start_index: u64,
@@ -67,10 +98,14 @@ pub struct AsciiCombinationsConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GenerationConfig {
     pub chunk_size: usize,
     pub json_chunk_size: usize,
     pub flush_interval: usize,
+    /// When generated combinations are written to disk (entries, bytes or a memory budget)
+    #[serde(default)]
+    pub flush_strategy: FlushStrategy,
     pub combinations_per_second_estimate: u64,
     pub fast_system_rate: u64,
     pub medium_system_rate: u64,
@@ -78,6 +113,7 @@ pub struct GenerationConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct UltraCompressedConfig {
     pub length: usize,
     pub start_index: u64,
@@ -87,65 +123,186 @@ pub struct UltraCompressedConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct FileProcessingConfig {
     pub ascii_conversion: AsciiConversionConfig,
     pub binary_string_conversion: BinaryStringConversionConfig,
+    #[serde(default = "default_large_file_warning_mb")]
+    pub large_file_warning_mb: u64,
+    /// Compress file bytes directly instead of their printable-ASCII conversion
+    #[serde(default)]
+    pub raw_mode: bool,
+    /// Files smaller than this are stored as-is instead of compressed
+    #[serde(default = "default_min_compress_size_bytes")]
+    pub min_compress_size_bytes: u64,
+    /// Largest output a gzip stream may inflate to
+    #[serde(default = "default_max_decompressed_mb")]
+    pub max_decompressed_mb: u64,
+}
+
+fn default_max_decompressed_mb() -> u64 {
+    1024
+}
+
+fn default_min_compress_size_bytes() -> u64 {
+    64
+}
+
+fn default_large_file_warning_mb() -> u64 {
+    1024
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AsciiConversionConfig {
     pub chunk_size: usize,
     pub printable_range: PrintableRange,
     pub conversion_map: ConversionMap,
+    /// Abort when more than this percentage of bytes would be converted (no limit when unset)
+    #[serde(default)]
+    pub max_loss_percent: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PrintableRange {
     pub min: u8,
     pub max: u8,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ConversionMap {
     pub control_chars: String,
     pub extended_ascii: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BinaryStringConversionConfig {
     pub bits_per_byte: usize,
     pub format: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct UploadConfig {
     pub hash: HashConfig,
     pub starknet: StarknetConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct HashConfig {
     pub algorithm: String,
     pub short_hash_length: usize,
     pub upload_id_length: usize,
+    /// Opt-in: hashes large inputs with BLAKE3 instead of `algorithm`
+    #[serde(default = "default_parallel_hashing")]
+    pub parallel_hashing: bool,
+    #[serde(default = "default_parallel_threshold_mb")]
+    pub parallel_threshold_mb: usize,
+}
+
+fn default_parallel_hashing() -> bool {
+    false
+}
+
+fn default_parallel_threshold_mb() -> usize {
+    64
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StarknetConfig {
     pub chunk_size: usize,
     pub field_element_size: usize,
     pub calldata_optimization: bool,
+    #[serde(default = "default_entrypoint")]
+    pub entrypoint: String,
+    /// How long to wait for an upload transaction to be accepted or reverted
+    #[serde(default = "default_confirmation_timeout_secs")]
+    pub confirmation_timeout_secs: u64,
+    /// Delay between transaction receipt polls
+    #[serde(default = "default_confirmation_poll_interval_secs")]
+    pub confirmation_poll_interval_secs: u64,
+    /// Most felts of calldata sent in one transaction; larger uploads are split into chunks
+    #[serde(default = "default_max_calldata_felts")]
+    pub max_calldata_felts: usize,
+}
+
+fn default_entrypoint() -> String {
+    "store_compression_mapping".to_string()
+}
+
+fn default_confirmation_timeout_secs() -> u64 {
+    120
+}
+
+fn default_confirmation_poll_interval_secs() -> u64 {
+    3
+}
+
+fn default_max_calldata_felts() -> usize {
+    3000
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ServerConfig {
     pub port: u16,
     pub host: String,
     pub endpoints: EndpointsConfig,
     pub dictionary: DictionaryServerConfig,
+    /// Pin the original file behind the stored header instead of compressed output that came out larger
+    #[serde(default = "default_abort_on_expansion")]
+    pub abort_on_expansion: bool,
+    /// Uploads each owner may make to `/compress` per UTC day (unlimited when unset)
+    #[serde(default)]
+    pub max_uploads_per_day: Option<u32>,
+    /// SQLite file recording the CID pinned for each upload's SHA-256
+    #[serde(default = "default_pin_cache_path")]
+    pub pin_cache_path: String,
+    /// Requests per second each client IP may sustain (no limit when 0)
+    #[serde(default = "default_rate_limit_rps")]
+    pub rate_limit_rps: f64,
+    /// Requests a client IP may send at once before `rate_limit_rps` applies
+    #[serde(default = "default_rate_limit_burst")]
+    pub rate_limit_burst: u32,
+    /// Seconds to let in-flight requests finish after SIGINT/SIGTERM before exiting anyway
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u64,
+    /// Largest file `/ws/compress` accepts; larger announced sizes are refused before buffering
+    #[serde(default = "default_max_socket_upload_mb")]
+    pub max_socket_upload_mb: u64,
+}
+
+fn default_abort_on_expansion() -> bool {
+    true
+}
+
+fn default_pin_cache_path() -> String {
+    "pinned_files.sqlite".to_string()
+}
+
+fn default_rate_limit_rps() -> f64 {
+    10.0
+}
+
+fn default_rate_limit_burst() -> u32 {
+    20
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    30
+}
+
+fn default_max_socket_upload_mb() -> u64 {
+    100
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct EndpointsConfig {
     pub health: String,
     pub status: String,
@@ -154,6 +311,7 @@ pub struct EndpointsConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DictionaryServerConfig {
     pub auto_generate: bool,
     pub path: String,
@@ -161,6 +319,7 @@ pub struct DictionaryServerConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct FallbackMetadata {
     pub length: usize,
     pub total_combinations: usize,
@@ -168,18 +327,21 @@ pub struct FallbackMetadata {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CliConfig {
     pub progress: ProgressConfig,
     pub prompts: PromptsConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ProgressConfig {
     pub spinner_style: SpinnerStyle,
     pub bar_style: BarStyle,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SpinnerStyle {
     pub tick_strings: Vec<String>,
     pub template: String,
@@ -187,12 +349,14 @@ pub struct SpinnerStyle {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BarStyle {
     pub template: String,
     pub progress_chars: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PromptsConfig {
     pub default_length: usize,
     pub default_start_index: u64,
@@ -202,12 +366,14 @@ pub struct PromptsConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MappingConfig {
     pub minimal_mapping: MinimalMappingConfig,
     pub complete_mapping: CompleteMappingConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MinimalMappingConfig {
     pub version: String,
     pub include_compressed_data: bool,
@@ -215,6 +381,7 @@ pub struct MinimalMappingConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CompleteMappingConfig {
     pub version: String,
     pub include_reversal_instructions: bool,
@@ -222,38 +389,93 @@ pub struct CompleteMappingConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StorageConfig {
     pub ipfs: IpfsConfig,
     pub local: LocalStorageConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct IpfsConfig {
     pub enabled: bool,
     pub gateway: String,
     pub pinata_jwt_env: String,
+    /// Service uploads are pinned to
+    #[serde(default)]
+    pub provider: IpfsProvider,
+    /// Environment variable holding the web3.storage API token
+    #[serde(default = "default_web3_storage_token_env")]
+    pub web3_storage_token_env: String,
+    /// Storage price used for pinning cost estimates, per GB per month
+    #[serde(default = "default_cost_per_gb_month")]
+    pub cost_per_gb_month: f64,
+}
+
+fn default_web3_storage_token_env() -> String {
+    "WEB3_STORAGE_TOKEN".to_string()
+}
+
+fn default_cost_per_gb_month() -> f64 {
+    0.15
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct LocalStorageConfig {
     pub mapping_files: bool,
     pub compressed_files: bool,
     pub debug_files: bool,
+    /// Record each CLI upload in a local SQLite index
+    #[serde(default)]
+    pub upload_index: bool,
+    #[serde(default = "default_upload_index_path")]
+    pub upload_index_path: String,
+    /// Directory compressed and mapping files are written to, instead of the working directory
+    #[serde(default)]
+    pub output_dir: Option<String>,
+}
+
+fn default_upload_index_path() -> String {
+    "upload_index.sqlite".to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DebugConfig {
     pub save_debug_files: bool,
     pub debug_files: Vec<String>,
+    #[serde(default)]
+    pub rotate: bool,
+    #[serde(default = "default_keep_runs")]
+    pub keep_runs: usize,
+    #[serde(default = "default_runs_dir")]
+    pub runs_dir: String,
+    /// Write the intermediate binary string of each upload (same as `--emit-binary-string`)
+    #[serde(default)]
+    pub emit_binary_string: bool,
+    /// Directory for emitted artifacts; the system temp dir when unset
+    #[serde(default)]
+    pub artifact_dir: Option<String>,
+}
+
+fn default_keep_runs() -> usize {
+    5
+}
+
+fn default_runs_dir() -> String {
+    "debug_runs".to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PerformanceConfig {
     pub memory: MemoryConfig,
     pub compression: CompressionPerformanceConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MemoryConfig {
     pub max_chunk_size: usize,
     pub json_processing_chunk_size: usize,
@@ -261,18 +483,21 @@ pub struct MemoryConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CompressionPerformanceConfig {
     pub optimal_chunk_search_range: Vec<usize>,
     pub compression_threshold: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ValidationConfig {
     pub file: FileValidationConfig,
     pub compression: CompressionValidationConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct FileValidationConfig {
     pub max_size_mb: usize,
     pub allowed_extensions: Vec<String>,
@@ -280,6 +505,7 @@ pub struct FileValidationConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CompressionValidationConfig {
     pub min_ratio: f64,
     pub max_ratio: f64,
@@ -287,12 +513,14 @@ pub struct CompressionValidationConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct UiConfig {
     pub colors: ColorConfig,
     pub messages: MessageConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ColorConfig {
     pub success: String,
     pub error: String,
@@ -302,6 +530,7 @@ pub struct ColorConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MessageConfig {
     pub upload_complete: String,
     pub compression_achieved: String,
@@ -314,6 +543,8 @@ pub enum ConfigError {
     FileNotFound(String),
     ParseError(serde_json::Error),
     IoError(std::io::Error),
+    Invalid(String),
+    AlreadyExists(String),
 }
 
 impl fmt::Display for ConfigError {
@@ -322,26 +553,121 @@ impl fmt::Display for ConfigError {
             ConfigError::FileNotFound(path) => write!(f, "Configuration file not found: {}", path),
             ConfigError::ParseError(e) => write!(f, "Failed to parse configuration: {}", e),
             ConfigError::IoError(e) => write!(f, "IO error reading configuration: {}", e),
+            ConfigError::Invalid(msg) => write!(f, "Invalid configuration: {}", msg),
+            ConfigError::AlreadyExists(path) => write!(f, "Configuration file already exists: {} (use --force to overwrite)", path),
         }
     }
 }
 
 impl Error for ConfigError {}
 
-/// Loads the configuration from the config.json file
+impl Config {
+    /// Checks invariants serde can't express, listing every offending field
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+
+        let range = &self.compression.chunk_size_range;
+        if range.min == 0 {
+            problems.push("compression.chunk_size_range.min must be at least 1".to_string());
+        }
+        if range.min > range.max {
+            problems.push(format!(
+                "compression.chunk_size_range.min ({}) must not exceed max ({})",
+                range.min, range.max
+            ));
+        } else if !(range.min..=range.max).contains(&range.default) {
+            problems.push(format!(
+                "compression.chunk_size_range.default ({}) must be between min ({}) and max ({})",
+                range.default, range.min, range.max
+            ));
+        }
+        for (field, value) in [
+            ("compression.target_compression_ratio", self.compression.target_compression_ratio),
+            ("compression.current_compression_ratio", self.compression.current_compression_ratio),
+        ] {
+            if !(0.0..=100.0).contains(&value) {
+                problems.push(format!("{} ({}) must be between 0 and 100", field, value));
+            }
+        }
+
+        if let Err(e) = self.compression.compression_method.parse::<crate::compression::Codec>() {
+            problems.push(format!("compression.compression_method: {}", e));
+        }
+
+        let ascii = &self.file_processing.ascii_conversion;
+        if ascii.printable_range.min >= ascii.printable_range.max {
+            problems.push(format!(
+                "file_processing.ascii_conversion.printable_range.min ({}) must be less than max ({})",
+                ascii.printable_range.min, ascii.printable_range.max
+            ));
+        }
+        if let Some(max_loss) = ascii.max_loss_percent {
+            if !(0.0..=100.0).contains(&max_loss) {
+                problems.push(format!(
+                    "file_processing.ascii_conversion.max_loss_percent ({}) must be between 0 and 100",
+                    max_loss
+                ));
+            }
+        }
+
+        let ratios = &self.validation.compression;
+        if ratios.min_ratio > ratios.max_ratio {
+            problems.push(format!(
+                "validation.compression.min_ratio ({}) must not exceed max_ratio ({})",
+                ratios.min_ratio, ratios.max_ratio
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems.join("; ")))
+        }
+    }
+}
+
+/// Environment variable naming the configuration file, checked before any other source
+pub const CONFIG_ENV_VAR: &str = "STARK_SQUEEZE_CONFIG";
+/// Configuration file used when neither the environment variable nor a path is given
+pub const DEFAULT_CONFIG_PATH: &str = "config.json";
+
+/// Path given on the command line with `--config`
+static CONFIG_PATH_ARG: OnceLock<PathBuf> = OnceLock::new();
+
+/// Sets the configuration path from the command line. Must be called before the
+/// first `get_config`; later calls are ignored.
+pub fn set_config_path(path: impl Into<PathBuf>) {
+    let _ = CONFIG_PATH_ARG.set(path.into());
+}
+
+/// Resolves the configuration file: `STARK_SQUEEZE_CONFIG`, then `path_arg`, then `config.json`
+pub fn config_path(path_arg: Option<&Path>) -> PathBuf {
+    match std::env::var_os(CONFIG_ENV_VAR) {
+        Some(env_path) if !env_path.is_empty() => PathBuf::from(env_path),
+        _ => path_arg
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH)),
+    }
+}
+
+/// Loads the configuration from the resolved configuration file
 pub fn load_config() -> Result<Config, ConfigError> {
-    let config_path = "config.json";
-    
-    if !Path::new(config_path).exists() {
-        return Err(ConfigError::FileNotFound(config_path.to_string()));
+    load_config_from_path(&config_path(CONFIG_PATH_ARG.get().map(PathBuf::as_path)))
+}
+
+/// Loads the configuration from `path`
+pub fn load_config_from_path(path: &Path) -> Result<Config, ConfigError> {
+    if !path.exists() {
+        return Err(ConfigError::FileNotFound(path.display().to_string()));
     }
-    
-    let config_content = fs::read_to_string(config_path)
+
+    let config_content = fs::read_to_string(path)
         .map_err(ConfigError::IoError)?;
-    
+
     let config: Config = serde_json::from_str(&config_content)
         .map_err(ConfigError::ParseError)?;
-    
+    config.validate()?;
+
     Ok(config)
 }
 
@@ -350,7 +676,7 @@ pub fn load_config_or_default() -> Config {
     match load_config() {
         Ok(config) => config,
         Err(e) => {
-            eprintln!("Warning: Failed to load config.json: {}", e);
+            eprintln!("Warning: Failed to load configuration: {}", e);
             eprintln!("Using default configuration values");
             create_default_config()
         }
@@ -358,14 +684,15 @@ pub fn load_config_or_default() -> Config {
 }
 
 /// Creates a default configuration
-fn create_default_config() -> Config {
+pub(crate) fn create_default_config() -> Config {
     Config {
         version: "1.0.0".to_string(),
         description: "Default StarkSqueeze configuration".to_string(),
         compression: CompressionConfig {
             target_compression_ratio: 66.7,
             current_compression_ratio: 66.7,
-            compression_method: "dictionary_based".to_string(),
+            ratio_tolerance_percent: default_ratio_tolerance_percent(),
+            compression_method: "rle".to_string(),
             chunk_size_range: ChunkSizeRange {
                 min: 2,
                 max: 8,
@@ -395,6 +722,7 @@ fn create_default_config() -> Config {
                 });
                 map
             },
+            backend: CompressionBackend::default(),
         },
         dictionary: DictionaryConfig {
             ascii_combinations: AsciiCombinationsConfig {
@@ -408,6 +736,7 @@ fn create_default_config() -> Config {
                     chunk_size: 100000,
                     json_chunk_size: 10000,
                     flush_interval: 5,
+                    flush_strategy: FlushStrategy::default(),
                     combinations_per_second_estimate: 1000000,
                     fast_system_rate: 5000000,
                     medium_system_rate: 1000000,
@@ -421,6 +750,7 @@ fn create_default_config() -> Config {
                 encoding: "key_value_dictionary_3to1".to_string(),
                 description: "Ultra-compressed JSON with 66.7% compression - fast testing".to_string(),
             },
+            path: None,
         },
         file_processing: FileProcessingConfig {
             ascii_conversion: AsciiConversionConfig {
@@ -433,22 +763,33 @@ fn create_default_config() -> Config {
                     control_chars: "space".to_string(),
                     extended_ascii: "period".to_string(),
                 },
+                max_loss_percent: None,
             },
             binary_string_conversion: BinaryStringConversionConfig {
                 bits_per_byte: 8,
                 format: "{:08b}".to_string(),
             },
+            large_file_warning_mb: 1024,
+            raw_mode: false,
+            min_compress_size_bytes: 64,
+            max_decompressed_mb: 1024,
         },
         upload: UploadConfig {
             hash: HashConfig {
                 algorithm: "sha256".to_string(),
                 short_hash_length: 8,
                 upload_id_length: 16,
+                parallel_hashing: false,
+                parallel_threshold_mb: 64,
             },
             starknet: StarknetConfig {
                 chunk_size: 8,
                 field_element_size: 16,
                 calldata_optimization: true,
+                entrypoint: default_entrypoint(),
+                confirmation_timeout_secs: default_confirmation_timeout_secs(),
+                confirmation_poll_interval_secs: default_confirmation_poll_interval_secs(),
+                max_calldata_felts: default_max_calldata_felts(),
             },
         },
         server: ServerConfig {
@@ -469,6 +810,13 @@ fn create_default_config() -> Config {
                     compression_ratio: "66.7% (3 chars → 1 byte) - fast testing".to_string(),
                 },
             },
+            abort_on_expansion: true,
+            max_uploads_per_day: None,
+            pin_cache_path: default_pin_cache_path(),
+            rate_limit_rps: default_rate_limit_rps(),
+            rate_limit_burst: default_rate_limit_burst(),
+            shutdown_timeout_secs: default_shutdown_timeout_secs(),
+            max_socket_upload_mb: default_max_socket_upload_mb(),
         },
         cli: CliConfig {
             progress: ProgressConfig {
@@ -496,12 +844,12 @@ fn create_default_config() -> Config {
         },
         mapping: MappingConfig {
             minimal_mapping: MinimalMappingConfig {
-                version: "1.0".to_string(),
+                version: "1.1".to_string(),
                 include_compressed_data: true,
                 include_ascii_conversion: true,
             },
             complete_mapping: CompleteMappingConfig {
-                version: "1.0".to_string(),
+                version: "1.1".to_string(),
                 include_reversal_instructions: true,
                 include_metadata: true,
             },
@@ -511,11 +859,17 @@ fn create_default_config() -> Config {
                 enabled: true,
                 gateway: "https://gateway.pinata.cloud/ipfs/".to_string(),
                 pinata_jwt_env: "PINATA_JWT".to_string(),
+                provider: IpfsProvider::default(),
+                web3_storage_token_env: default_web3_storage_token_env(),
+                cost_per_gb_month: default_cost_per_gb_month(),
             },
             local: LocalStorageConfig {
                 mapping_files: true,
                 compressed_files: true,
                 debug_files: false,
+                upload_index: false,
+                upload_index_path: default_upload_index_path(),
+                output_dir: None,
             },
         },
         debug: DebugConfig {
@@ -523,10 +877,15 @@ fn create_default_config() -> Config {
             debug_files: vec![
                 "debug_original.bin".to_string(),
                 "debug_ascii.bin".to_string(),
-                "debug_binary_string.txt".to_string(),
+                "debug_packed.bin".to_string(),
                 "debug_reconstructed_binary_string.txt".to_string(),
                 "debug_reconstructed_ascii.bin".to_string(),
             ],
+            rotate: false,
+            keep_runs: default_keep_runs(),
+            runs_dir: default_runs_dir(),
+            emit_binary_string: false,
+            artifact_dir: None,
         },
         performance: PerformanceConfig {
             memory: MemoryConfig {
@@ -571,15 +930,42 @@ fn create_default_config() -> Config {
 
 /// Saves the current configuration to config.json
 pub fn save_config(config: &Config) -> Result<(), ConfigError> {
+    save_config_to_path(config, &config_path(CONFIG_PATH_ARG.get().map(PathBuf::as_path)))
+}
+
+/// Saves a configuration to a specific file
+pub fn save_config_to_path(config: &Config, path: &Path) -> Result<(), ConfigError> {
     let config_content = serde_json::to_string_pretty(config)
-        .map_err(|e| ConfigError::ParseError(e))?;
+        .map_err(ConfigError::ParseError)?;
     
-    fs::write("config.json", config_content)
+    fs::write(path, config_content)
         .map_err(ConfigError::IoError)?;
     
     Ok(())
 }
 
+/// Writes the default configuration to the resolved config path, returning it.
+/// An existing file is only replaced when `force` is set.
+pub fn init_config(force: bool) -> Result<PathBuf, ConfigError> {
+    let path = config_path(CONFIG_PATH_ARG.get().map(PathBuf::as_path));
+    init_config_at(&path, force)?;
+    Ok(path)
+}
+
+/// Writes the default configuration to `path`; see `init_config`
+pub fn init_config_at(path: &Path, force: bool) -> Result<(), ConfigError> {
+    if !force && path.exists() {
+        return Err(ConfigError::AlreadyExists(path.display().to_string()));
+    }
+    save_config_to_path(&create_default_config(), path)
+}
+
+/// JSON Schema describing config.json, for editor autocompletion and validation
+#[cfg(feature = "schema")]
+pub fn config_schema() -> schemars::schema::RootSchema {
+    schemars::schema_for!(Config)
+}
+
 lazy_static::lazy_static! {
     pub static ref CONFIG: Config = load_config_or_default();
 }
@@ -597,9 +983,84 @@ mod tests {
     fn test_load_default_config() {
         let config = create_default_config();
         assert_eq!(config.version, "1.0.0");
-        assert_eq!(config.compression.target_compression_ratio, 90.0);
-        assert_eq!(config.compression.current_compression_ratio, 80.0);
-        assert_eq!(config.dictionary.ascii_combinations.default_length, 5);
+        assert_eq!(config.compression.target_compression_ratio, 66.7);
+        assert_eq!(config.compression.current_compression_ratio, 66.7);
+        assert_eq!(config.dictionary.ascii_combinations.default_length, 3);
+    }
+
+    #[test]
+    fn test_config_path_from_env_var() {
+        // Initialize the global config first so the variable set below can't leak into it
+        let _ = get_config();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("custom.json");
+        let mut config = create_default_config();
+        config.version = "9.9.9".to_string();
+        config.compression.chunk_size_range.default = 7;
+        fs::write(&path, serde_json::to_string(&config).unwrap()).unwrap();
+        let arg = Path::new("from-flag.json");
+
+        std::env::set_var(CONFIG_ENV_VAR, &path);
+        let resolved = config_path(Some(arg));
+        let loaded = load_config();
+        std::env::remove_var(CONFIG_ENV_VAR);
+
+        assert_eq!(resolved, path);
+        let loaded = loaded.unwrap();
+        let defaults = create_default_config();
+        assert_ne!(loaded.version, defaults.version);
+        assert_eq!(loaded.version, "9.9.9");
+        assert_eq!(loaded.compression.chunk_size_range.default, 7);
+
+        assert_eq!(config_path(Some(arg)), arg);
+        assert_eq!(config_path(None), PathBuf::from(DEFAULT_CONFIG_PATH));
+        assert!(matches!(load_config_from_path(&dir.path().join("missing.json")), Err(ConfigError::FileNotFound(_))));
+    }
+
+    #[test]
+    fn test_chunk_size_is_clamped_to_range() {
+        let range = ChunkSizeRange { min: 2, max: 8, default: 3 };
+        assert_eq!(range.clamp(1), 2);
+        assert_eq!(range.clamp(0), 2);
+        assert_eq!(range.clamp(64), 8);
+        assert_eq!(range.clamp(5), 5);
+    }
+
+    #[test]
+    fn test_invalid_config_is_rejected() {
+        assert!(create_default_config().validate().is_ok());
+
+        let mut config = create_default_config();
+        config.compression.chunk_size_range.min = 9;
+        config.compression.target_compression_ratio = 150.0;
+        config.file_processing.ascii_conversion.printable_range = PrintableRange { min: 126, max: 32 };
+        config.compression.compression_method = "lz77".to_string();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("invalid.json");
+        fs::write(&path, serde_json::to_string(&config).unwrap()).unwrap();
+
+        match load_config_from_path(&path) {
+            Err(ConfigError::Invalid(msg)) => {
+                assert!(msg.contains("chunk_size_range.min (9) must not exceed max (8)"), "{}", msg);
+                assert!(msg.contains("target_compression_ratio (150)"), "{}", msg);
+                assert!(msg.contains("printable_range.min (126) must be less than max (32)"), "{}", msg);
+                assert!(msg.contains("Unknown compression method 'lz77'"), "{}", msg);
+                assert_eq!(msg.matches("; ").count(), 3, "{}", msg);
+            }
+            other => panic!("expected an invalid config error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_init_config_writes_loadable_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        init_config_at(&path, false).unwrap();
+        let config = load_config_from_path(&path).unwrap();
+        assert_eq!(config.version, create_default_config().version);
+
+        assert!(matches!(init_config_at(&path, false), Err(ConfigError::AlreadyExists(_))));
+        init_config_at(&path, true).unwrap();
     }
 
     #[test]
@@ -609,4 +1070,12 @@ mod tests {
         let parsed: Config = serde_json::from_str(&json).unwrap();
         assert_eq!(config.version, parsed.version);
     }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_config_schema_lists_fields() {
+        let schema = serde_json::to_string(&config_schema()).unwrap();
+        assert!(schema.contains("\"target_compression_ratio\""));
+        assert!(schema.contains("\"rate_limit_rps\""));
+    }
 } 
\ No newline at end of file
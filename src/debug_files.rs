@@ -0,0 +1,302 @@
+// Debug Files Module
+// Writes intermediate pipeline artifacts, either to fixed names in the working
+// directory or, with rotation enabled, into one folder per run
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use crate::config::{Config, DebugConfig};
+
+/// Distinguishes runs started within the same timestamp tick
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Set by `--emit-binary-string`
+static EMIT_BINARY_STRING: AtomicBool = AtomicBool::new(false);
+
+/// Requests the binary-string artifact for every upload in this process
+pub fn set_emit_binary_string(emit: bool) {
+    EMIT_BINARY_STRING.store(emit, Ordering::SeqCst);
+}
+
+/// Where the binary-string artifact goes, or `None` when neither the flag nor the config asks for it
+pub fn binary_string_dir(config: &DebugConfig) -> Option<PathBuf> {
+    if !(EMIT_BINARY_STRING.load(Ordering::SeqCst) || config.emit_binary_string) {
+        return None;
+    }
+    Some(config.artifact_dir.as_ref().map(PathBuf::from).unwrap_or_else(std::env::temp_dir))
+}
+
+/// Writes the `{:08b}` expansion of `file_name` into `dir` and returns the artifact path
+pub fn write_binary_string(dir: &Path, file_name: &str, binary_string: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.binary-string.txt", file_name));
+    fs::write(&path, binary_string)?;
+    Ok(path)
+}
+
+/// Directory `DebugRun::start` writes under: the working directory, or a scratch
+/// directory in unit tests so they never leave artifacts in the checkout
+fn default_base() -> PathBuf {
+    if cfg!(test) {
+        let dir = std::env::temp_dir().join("stark_squeeze_test_debug");
+        let _ = fs::create_dir_all(&dir);
+        dir
+    } else {
+        PathBuf::from(".")
+    }
+}
+
+/// Destination for the debug artifacts of a single pipeline run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DebugRun {
+    /// Debug files are disabled
+    #[default]
+    Disabled,
+    /// Files are written to this directory under their fixed names
+    Dir(PathBuf),
+}
+
+impl DebugRun {
+    /// Starts a debug run using the global config, relative to the working directory
+    pub fn start() -> DebugRun {
+        DebugRun::start_with(&default_base(), crate::config::get_config())
+    }
+
+    /// Starts a debug run rooted at `base` only when both `debug.save_debug_files` and
+    /// `storage.local.debug_files` are enabled; otherwise nothing is written
+    pub fn start_with(base: &Path, config: &Config) -> DebugRun {
+        if !config.storage.local.debug_files {
+            return DebugRun::Disabled;
+        }
+        DebugRun::start_in(base, &config.debug)
+    }
+
+    /// Starts a debug run rooted at `base`. With rotation enabled a fresh run folder is
+    /// created under `base/runs_dir` and older runs beyond `keep_runs` are removed.
+    pub fn start_in(base: &Path, config: &DebugConfig) -> DebugRun {
+        if !config.save_debug_files {
+            return DebugRun::Disabled;
+        }
+        if !config.rotate {
+            return DebugRun::Dir(base.to_path_buf());
+        }
+
+        let runs_dir = base.join(&config.runs_dir);
+        let run_name = format!(
+            "run-{}-{:04}",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%.6f"),
+            RUN_COUNTER.fetch_add(1, Ordering::SeqCst) % 10_000
+        );
+        let run_dir = runs_dir.join(run_name);
+        if let Err(e) = fs::create_dir_all(&run_dir) {
+            eprintln!("Warning: could not create debug run folder {}: {}", run_dir.display(), e);
+            return DebugRun::Disabled;
+        }
+        if let Err(e) = prune_runs(&runs_dir, config.keep_runs) {
+            eprintln!("Warning: could not prune old debug runs: {}", e);
+        }
+        DebugRun::Dir(run_dir)
+    }
+
+    /// Writes one debug artifact; failures are reported but never abort the pipeline
+    pub fn write(&self, name: &str, contents: impl AsRef<[u8]>) {
+        if let DebugRun::Dir(dir) = self {
+            let path = dir.join(name);
+            if let Err(e) = fs::write(&path, contents) {
+                eprintln!("Warning: failed to write {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// A configured debug artifact and its state on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugFileEntry {
+    pub name: String,
+    pub path: PathBuf,
+    /// `None` when the file does not exist
+    pub size: Option<u64>,
+    pub modified: Option<std::time::SystemTime>,
+}
+
+impl DebugFileEntry {
+    pub fn exists(&self) -> bool {
+        self.size.is_some()
+    }
+}
+
+/// Folder holding the artifacts of the most recent run: the newest rotated run, or `base`
+fn latest_run_dir(base: &Path, config: &DebugConfig) -> PathBuf {
+    if !config.rotate {
+        return base.to_path_buf();
+    }
+    let newest = fs::read_dir(base.join(&config.runs_dir))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .max();
+    newest.unwrap_or_else(|| base.join(&config.runs_dir))
+}
+
+/// Lists every configured debug file from the latest run, present or not
+pub fn list_debug_files(base: &Path, config: &DebugConfig) -> Vec<DebugFileEntry> {
+    let dir = latest_run_dir(base, config);
+    config
+        .debug_files
+        .iter()
+        .map(|name| {
+            let path = dir.join(name);
+            let metadata = fs::metadata(&path).ok().filter(|m| m.is_file());
+            DebugFileEntry {
+                name: name.clone(),
+                size: metadata.as_ref().map(|m| m.len()),
+                modified: metadata.and_then(|m| m.modified().ok()),
+                path,
+            }
+        })
+        .collect()
+}
+
+/// Removes the configured debug files under `base` and any rotated run folders.
+/// Returns how many files and run folders were removed.
+pub fn clean_debug_files(base: &Path, config: &DebugConfig) -> io::Result<usize> {
+    let mut removed = 0;
+    for name in &config.debug_files {
+        let path = base.join(name);
+        if path.is_file() {
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    let runs_dir = base.join(&config.runs_dir);
+    if runs_dir.is_dir() {
+        removed += fs::read_dir(&runs_dir)?.count();
+        fs::remove_dir_all(&runs_dir)?;
+    }
+    Ok(removed)
+}
+
+/// Removes the oldest run folders so at most `keep_runs` remain
+fn prune_runs(runs_dir: &Path, keep_runs: usize) -> io::Result<()> {
+    let mut runs: Vec<PathBuf> = fs::read_dir(runs_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_dir()
+                && path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("run-"))
+        })
+        .collect();
+    // Run names start with a UTC timestamp, so lexical order is chronological
+    runs.sort();
+
+    let excess = runs.len().saturating_sub(keep_runs.max(1));
+    for old in &runs[..excess] {
+        fs::remove_dir_all(old)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debug_config(rotate: bool, keep_runs: usize) -> DebugConfig {
+        DebugConfig {
+            save_debug_files: true,
+            debug_files: vec!["debug_original.bin".to_string(), "debug_ascii.bin".to_string()],
+            rotate,
+            keep_runs,
+            runs_dir: "debug_runs".to_string(),
+            emit_binary_string: false,
+            artifact_dir: None,
+        }
+    }
+
+    fn write_artifacts(run: &DebugRun, tag: &str) {
+        run.write("debug_original.bin", tag);
+        run.write("debug_ascii.bin", tag);
+    }
+
+    #[test]
+    fn test_rotation_keeps_each_run_separate() {
+        let base = tempfile::tempdir().unwrap();
+        let config = debug_config(true, 5);
+
+        let first = DebugRun::start_in(base.path(), &config);
+        write_artifacts(&first, "first");
+        let second = DebugRun::start_in(base.path(), &config);
+        write_artifacts(&second, "second");
+
+        let (DebugRun::Dir(first_dir), DebugRun::Dir(second_dir)) = (&first, &second) else {
+            panic!("rotation should produce run folders");
+        };
+        assert_ne!(first_dir, second_dir);
+        for (dir, tag) in [(first_dir, "first"), (second_dir, "second")] {
+            assert_eq!(fs::read_to_string(dir.join("debug_original.bin")).unwrap(), tag);
+            assert_eq!(fs::read_to_string(dir.join("debug_ascii.bin")).unwrap(), tag);
+        }
+    }
+
+    #[test]
+    fn test_rotation_prunes_oldest_runs() {
+        let base = tempfile::tempdir().unwrap();
+        let config = debug_config(true, 2);
+
+        let runs: Vec<DebugRun> = (0..4).map(|_| DebugRun::start_in(base.path(), &config)).collect();
+        let remaining = fs::read_dir(base.path().join("debug_runs")).unwrap().count();
+        assert_eq!(remaining, 2);
+        assert!(matches!(&runs[3], DebugRun::Dir(dir) if dir.exists()));
+        assert!(matches!(&runs[0], DebugRun::Dir(dir) if !dir.exists()));
+    }
+
+    #[test]
+    fn test_disabled_and_fixed_name_modes() {
+        let base = tempfile::tempdir().unwrap();
+        let mut config = debug_config(false, 5);
+        assert_eq!(DebugRun::start_in(base.path(), &config), DebugRun::Dir(base.path().to_path_buf()));
+
+        config.save_debug_files = false;
+        let run = DebugRun::start_in(base.path(), &config);
+        assert_eq!(run, DebugRun::Disabled);
+        run.write("debug_original.bin", "ignored");
+        assert!(!base.path().join("debug_original.bin").exists());
+    }
+
+    #[test]
+    fn test_listing_reflects_created_and_absent_files() {
+        let base = tempfile::tempdir().unwrap();
+        let config = debug_config(false, 5);
+        let run = DebugRun::start_in(base.path(), &config);
+        run.write("debug_original.bin", "abc");
+
+        let entries = list_debug_files(base.path(), &config);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "debug_original.bin");
+        assert_eq!(entries[0].size, Some(3));
+        assert!(entries[0].modified.is_some());
+        assert!(!entries[1].exists());
+
+        assert_eq!(clean_debug_files(base.path(), &config).unwrap(), 1);
+        assert!(list_debug_files(base.path(), &config).iter().all(|e| !e.exists()));
+    }
+
+    #[test]
+    fn test_listing_uses_latest_rotated_run() {
+        let base = tempfile::tempdir().unwrap();
+        let config = debug_config(true, 5);
+        write_artifacts(&DebugRun::start_in(base.path(), &config), "first");
+        let latest = DebugRun::start_in(base.path(), &config);
+        latest.write("debug_original.bin", "second");
+
+        let entries = list_debug_files(base.path(), &config);
+        assert!(matches!(&latest, DebugRun::Dir(dir) if entries[0].path.starts_with(dir)));
+        assert_eq!(entries[0].size, Some(6));
+        assert!(!entries[1].exists());
+
+        assert_eq!(clean_debug_files(base.path(), &config).unwrap(), 2);
+        assert!(!base.path().join("debug_runs").exists());
+    }
+}
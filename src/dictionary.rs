@@ -0,0 +1,284 @@
+// Dictionary Module
+// Loads an ASCII combinations dictionary from an explicit path instead of
+// assuming ascii_combinations.json in the working directory
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::mapping::MappingError;
+
+/// Magic bytes opening a binary dictionary
+const BINARY_MAGIC: &[u8; 4] = b"SQD1";
+
+/// A loaded key-value dictionary of fixed-length ASCII combinations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dictionary {
+    pub path: PathBuf,
+    /// Length of every combination, and so the chunk size the dictionary serves
+    pub length: usize,
+    pub combinations: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct DictionaryMetadata {
+    length: usize,
+}
+
+/// On-disk form written by `Dictionary::save_to_file`, with keys sorted so saves are stable
+#[derive(Serialize)]
+struct SortedDictionaryFile<'a> {
+    metadata: DictionaryMetadata,
+    combinations: BTreeMap<&'a str, &'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DictionaryFile {
+    metadata: DictionaryMetadata,
+    #[serde(default)]
+    combinations: HashMap<String, String>,
+}
+
+#[derive(Debug)]
+pub enum DictionaryError {
+    IoError(PathBuf, std::io::Error),
+    WriteError(PathBuf, std::io::Error),
+    ParseError(PathBuf, serde_json::Error),
+    InvalidDictionary(String),
+    ChunkSizeMismatch { dictionary: usize, data: usize },
+    MappingError(MappingError),
+}
+
+impl fmt::Display for DictionaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DictionaryError::IoError(path, e) => write!(f, "Could not read dictionary {}: {}", path.display(), e),
+            DictionaryError::WriteError(path, e) => write!(f, "Could not write dictionary {}: {}", path.display(), e),
+            DictionaryError::ParseError(path, e) => write!(f, "Could not parse dictionary {}: {}", path.display(), e),
+            DictionaryError::InvalidDictionary(msg) => write!(f, "Invalid dictionary: {}", msg),
+            DictionaryError::ChunkSizeMismatch { dictionary, data } => write!(
+                f,
+                "Dictionary combinations are {} characters but the data is chunked by {}",
+                dictionary, data
+            ),
+            DictionaryError::MappingError(e) => write!(f, "Mapping error: {}", e),
+        }
+    }
+}
+
+impl Error for DictionaryError {}
+
+impl From<MappingError> for DictionaryError {
+    fn from(err: MappingError) -> Self {
+        DictionaryError::MappingError(err)
+    }
+}
+
+/// Picks the dictionary to use: the `--dictionary` flag, else the configured `dictionary.path`
+pub fn resolve_dictionary_path(flag: Option<&Path>) -> Option<PathBuf> {
+    flag.map(Path::to_path_buf)
+        .or_else(|| crate::config::get_config().dictionary.path.as_ref().map(PathBuf::from))
+}
+
+/// Loads a dictionary, checking that every combination has the declared length
+pub fn load_dictionary(path: &Path) -> Result<Dictionary, DictionaryError> {
+    let content = fs::read_to_string(path).map_err(|e| DictionaryError::IoError(path.to_path_buf(), e))?;
+    let file: DictionaryFile =
+        serde_json::from_str(&content).map_err(|e| DictionaryError::ParseError(path.to_path_buf(), e))?;
+
+    checked_dictionary(path, file.metadata.length, file.combinations)
+}
+
+/// Builds a `Dictionary`, checking that every combination has the declared length
+fn checked_dictionary(
+    path: &Path,
+    length: usize,
+    combinations: HashMap<String, String>,
+) -> Result<Dictionary, DictionaryError> {
+    if length == 0 {
+        return Err(DictionaryError::InvalidDictionary("metadata.length is 0".to_string()));
+    }
+    if let Some(key) = combinations.keys().find(|key| key.chars().count() != length) {
+        return Err(DictionaryError::InvalidDictionary(format!(
+            "combination {:?} is not {} characters long",
+            key, length
+        )));
+    }
+
+    Ok(Dictionary { path: path.to_path_buf(), length, combinations })
+}
+
+/// Writes `dictionary` in the compact binary format: `SQD1`, the combination length
+/// and entry count as little-endian u32 and u64, then each key and value as a
+/// little-endian u32 byte length followed by its UTF-8 bytes
+pub fn save_dictionary_binary<P: AsRef<Path>>(dictionary: &Dictionary, path: P) -> Result<(), DictionaryError> {
+    let path = path.as_ref();
+    let too_large = |what: &str| DictionaryError::InvalidDictionary(format!("{} is too large for the binary format", what));
+    let mut bytes = Vec::with_capacity(16 + dictionary.combinations.len() * (2 * dictionary.length + 8));
+    bytes.extend_from_slice(BINARY_MAGIC);
+    bytes.extend_from_slice(&u32::try_from(dictionary.length).map_err(|_| too_large("length"))?.to_le_bytes());
+    bytes.extend_from_slice(&(dictionary.combinations.len() as u64).to_le_bytes());
+    for (key, value) in &dictionary.combinations {
+        for field in [key, value] {
+            bytes.extend_from_slice(&u32::try_from(field.len()).map_err(|_| too_large("entry"))?.to_le_bytes());
+            bytes.extend_from_slice(field.as_bytes());
+        }
+    }
+    fs::write(path, bytes).map_err(|e| DictionaryError::WriteError(path.to_path_buf(), e))
+}
+
+/// Loads a dictionary written by `save_dictionary_binary`. Truncated or malformed
+/// files give `InvalidDictionary`.
+pub fn load_dictionary_binary<P: AsRef<Path>>(path: P) -> Result<Dictionary, DictionaryError> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|e| DictionaryError::IoError(path.to_path_buf(), e))?;
+    let mut reader = BinaryReader { bytes: &bytes, pos: 0 };
+    if reader.take(4)? != BINARY_MAGIC {
+        return Err(DictionaryError::InvalidDictionary("not a binary dictionary".to_string()));
+    }
+    let length = reader.u32()? as usize;
+    let count = reader.u64()?;
+    // Each entry needs at least its two length prefixes, so a count larger than that
+    // can only come from a corrupt file and must not drive the allocation
+    let max_count = (bytes.len() - reader.pos) as u64 / 8;
+    if count > max_count {
+        return Err(DictionaryError::InvalidDictionary(format!("truncated: {} entries declared", count)));
+    }
+    let mut combinations = HashMap::with_capacity(count as usize);
+    for _ in 0..count {
+        let key = reader.string()?;
+        let value = reader.string()?;
+        combinations.insert(key, value);
+    }
+    if reader.pos != bytes.len() {
+        return Err(DictionaryError::InvalidDictionary("trailing bytes after the last entry".to_string()));
+    }
+    checked_dictionary(path, length, combinations)
+}
+
+/// Cursor over a binary dictionary
+struct BinaryReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BinaryReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DictionaryError> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.bytes.len()).ok_or_else(|| {
+            DictionaryError::InvalidDictionary(format!("truncated at byte {}", self.pos))
+        })?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, DictionaryError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("4 bytes")))
+    }
+
+    fn u64(&mut self) -> Result<u64, DictionaryError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("8 bytes")))
+    }
+
+    fn string(&mut self) -> Result<String, DictionaryError> {
+        let len = self.u32()? as usize;
+        let start = self.pos;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| DictionaryError::InvalidDictionary(format!("entry at byte {} is not UTF-8", start)))
+    }
+}
+
+impl Dictionary {
+    /// Fails unless data chunked by `chunk_size` lines up with the dictionary's combinations
+    pub fn check_chunk_size(&self, chunk_size: usize) -> Result<(), DictionaryError> {
+        if chunk_size != self.length {
+            return Err(DictionaryError::ChunkSizeMismatch { dictionary: self.length, data: chunk_size });
+        }
+        Ok(())
+    }
+
+    /// Writes the dictionary in the JSON format `load_dictionary` reads, so a saved
+    /// dictionary loads back equal apart from `path`
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), DictionaryError> {
+        let path = path.as_ref();
+        let file = SortedDictionaryFile {
+            metadata: DictionaryMetadata { length: self.length },
+            combinations: self.combinations.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| DictionaryError::InvalidDictionary(format!("could not serialize: {}", e)))?;
+        fs::write(path, json).map_err(|e| DictionaryError::WriteError(path.to_path_buf(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_dictionary_validates_lengths() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = dir.path().join("good.json");
+        fs::write(&good, r#"{"metadata": {"length": 2}, "combinations": {"ab": "a", "cd": "b"}}"#).unwrap();
+        let dictionary = load_dictionary(&good).unwrap();
+        assert_eq!(dictionary.length, 2);
+        assert!(dictionary.check_chunk_size(2).is_ok());
+        assert!(matches!(
+            dictionary.check_chunk_size(3),
+            Err(DictionaryError::ChunkSizeMismatch { dictionary: 2, data: 3 })
+        ));
+
+        let bad = dir.path().join("bad.json");
+        fs::write(&bad, r#"{"metadata": {"length": 2}, "combinations": {"abc": "a"}}"#).unwrap();
+        assert!(matches!(load_dictionary(&bad), Err(DictionaryError::InvalidDictionary(_))));
+        assert!(matches!(load_dictionary(&dir.path().join("missing.json")), Err(DictionaryError::IoError(..))));
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("saved.json");
+        let mut combinations = HashMap::new();
+        combinations.insert("a=".to_string(), "x=y".to_string());
+        combinations.insert("\n\"".to_string(), "line\nbreak".to_string());
+        combinations.insert("zz".to_string(), "".to_string());
+        let dictionary = Dictionary { path: path.clone(), length: 2, combinations };
+
+        dictionary.save_to_file(&path).unwrap();
+        assert_eq!(load_dictionary(&path).unwrap(), dictionary);
+        assert!(matches!(
+            dictionary.save_to_file(dir.path().join("missing/saved.json")),
+            Err(DictionaryError::WriteError(..))
+        ));
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dict.bin");
+        let combinations = (0..500u32).map(|i| (format!("{:03}", i), format!("v{}", i))).collect();
+        let dictionary = Dictionary { path: path.clone(), length: 3, combinations };
+
+        save_dictionary_binary(&dictionary, &path).unwrap();
+        assert_eq!(load_dictionary_binary(&path).unwrap(), dictionary);
+    }
+
+    #[test]
+    fn test_truncated_binary_dictionary_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dict.bin");
+        let combinations = [("ab".to_string(), "x".to_string()), ("cd".to_string(), "y".to_string())].into();
+        save_dictionary_binary(&Dictionary { path: path.clone(), length: 2, combinations }, &path).unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        for len in 0..bytes.len() {
+            fs::write(&path, &bytes[..len]).unwrap();
+            assert!(matches!(load_dictionary_binary(&path), Err(DictionaryError::InvalidDictionary(_))), "length {}", len);
+        }
+        fs::write(&path, b"{\"metadata\": {}}").unwrap();
+        assert!(matches!(load_dictionary_binary(&path), Err(DictionaryError::InvalidDictionary(_))));
+    }
+}
@@ -0,0 +1,73 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::compression::CompressionError;
+use crate::upload_manifest::ManifestError;
+
+/// Top-level error for library entry points that run a whole pipeline
+#[derive(Debug)]
+pub enum StarkSqueezeError {
+    IoError(std::io::Error),
+    InvalidInput(String),
+    ConversionError(String),
+    CompressionError(CompressionError),
+    ManifestError(ManifestError),
+    UploadError(String),
+    /// Looking up an upload on-chain or fetching it from IPFS failed
+    RetrievalError(String),
+    FileTooLarge { size: u64, threshold: u64 },
+    /// Above `validation.file.max_size_mb`
+    FileExceedsMaxSize { size: u64, max_size: u64 },
+    AsciiLossExceeded { loss_percent: f64, max_percent: f64 },
+}
+
+impl fmt::Display for StarkSqueezeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StarkSqueezeError::IoError(e) => write!(f, "IO error: {}", e),
+            StarkSqueezeError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+            StarkSqueezeError::ConversionError(msg) => write!(f, "Conversion error: {}", msg),
+            StarkSqueezeError::CompressionError(e) => write!(f, "Compression error: {}", e),
+            StarkSqueezeError::ManifestError(e) => write!(f, "Manifest error: {}", e),
+            StarkSqueezeError::UploadError(msg) => write!(f, "Upload error: {}", msg),
+            StarkSqueezeError::RetrievalError(msg) => write!(f, "Retrieval error: {}", msg),
+            StarkSqueezeError::FileTooLarge { size, threshold } => write!(
+                f,
+                "File is {:.1} MB, above the {:.1} MB in-memory limit; rerun with --allow-large to read it anyway",
+                *size as f64 / (1024.0 * 1024.0),
+                *threshold as f64 / (1024.0 * 1024.0)
+            ),
+            StarkSqueezeError::FileExceedsMaxSize { size, max_size } => write!(
+                f,
+                "File is {:.1} MB, above the {:.1} MB maximum set by validation.file.max_size_mb; rerun with --disable-file-size-limit to upload it anyway",
+                *size as f64 / (1024.0 * 1024.0),
+                *max_size as f64 / (1024.0 * 1024.0)
+            ),
+            StarkSqueezeError::AsciiLossExceeded { loss_percent, max_percent } => write!(
+                f,
+                "ASCII conversion would alter {:.1}% of bytes (limit {:.1}%); this file is not suitable for the ASCII-based pipeline, rerun with --raw",
+                loss_percent, max_percent
+            ),
+        }
+    }
+}
+
+impl Error for StarkSqueezeError {}
+
+impl From<std::io::Error> for StarkSqueezeError {
+    fn from(err: std::io::Error) -> Self {
+        StarkSqueezeError::IoError(err)
+    }
+}
+
+impl From<CompressionError> for StarkSqueezeError {
+    fn from(err: CompressionError) -> Self {
+        StarkSqueezeError::CompressionError(err)
+    }
+}
+
+impl From<ManifestError> for StarkSqueezeError {
+    fn from(err: ManifestError) -> Self {
+        StarkSqueezeError::ManifestError(err)
+    }
+}
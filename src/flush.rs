@@ -0,0 +1,105 @@
+// Flush Module
+// Decides when the dictionary generators write their accumulated output to disk,
+// trading memory held between writes against the number of writes.
+
+use serde::{Deserialize, Serialize};
+
+/// When generation output is flushed to disk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum FlushStrategy {
+    /// Every `flush_interval` generation chunks (the original behavior)
+    #[default]
+    Chunks,
+    /// Every `every` generated entries
+    Entries { every: usize },
+    /// Once `every` bytes of combinations have accumulated since the last flush
+    Bytes { every: u64 },
+    /// Whenever the combinations held in memory exceed `max_mb`
+    MemoryBudget { max_mb: u64 },
+}
+
+/// Tracks generated entries and bytes against a `FlushStrategy`
+#[derive(Debug)]
+pub struct FlushTracker {
+    strategy: FlushStrategy,
+    chunk_size: usize,
+    flush_interval: usize,
+    entries_since_flush: usize,
+    bytes_since_flush: u64,
+    bytes_held: u64,
+    total_entries: usize,
+}
+
+impl FlushTracker {
+    /// `chunk_size` and `flush_interval` only matter for `FlushStrategy::Chunks`
+    pub fn new(strategy: FlushStrategy, chunk_size: usize, flush_interval: usize) -> Self {
+        Self {
+            strategy,
+            chunk_size,
+            flush_interval,
+            entries_since_flush: 0,
+            bytes_since_flush: 0,
+            bytes_held: 0,
+            total_entries: 0,
+        }
+    }
+
+    /// Records a generated batch and returns whether the output should be flushed now
+    pub fn record(&mut self, entries: usize, bytes: u64) -> bool {
+        self.entries_since_flush += entries;
+        self.bytes_since_flush += bytes;
+        self.bytes_held += bytes;
+        self.total_entries += entries;
+        let due = match self.strategy {
+            FlushStrategy::Chunks => {
+                let every = self.chunk_size.saturating_mul(self.flush_interval);
+                every > 0 && self.total_entries.is_multiple_of(every)
+            }
+            FlushStrategy::Entries { every } => every > 0 && self.entries_since_flush >= every,
+            FlushStrategy::Bytes { every } => every > 0 && self.bytes_since_flush >= every,
+            FlushStrategy::MemoryBudget { max_mb } => self.bytes_held >= max_mb.saturating_mul(1024 * 1024),
+        };
+        if due {
+            self.entries_since_flush = 0;
+            self.bytes_since_flush = 0;
+        }
+        due
+    }
+
+    /// Records a batch and calls `flush` when it is due
+    pub fn record_and_flush<F: FnMut()>(&mut self, entries: usize, bytes: u64, mut flush: F) {
+        if self.record(entries, bytes) {
+            flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds `batches` batches of 100 entries / 1000 bytes and counts the flushes
+    fn count_flushes(strategy: FlushStrategy, batches: usize) -> usize {
+        let mut tracker = FlushTracker::new(strategy, 100, 5);
+        let mut flushes = 0;
+        for _ in 0..batches {
+            tracker.record_and_flush(100, 1000, || flushes += 1);
+        }
+        flushes
+    }
+
+    #[test]
+    fn test_flush_triggers_at_configured_threshold() {
+        assert_eq!(count_flushes(FlushStrategy::Chunks, 12), 2);
+        assert_eq!(count_flushes(FlushStrategy::Entries { every: 300 }, 12), 4);
+        assert_eq!(count_flushes(FlushStrategy::Bytes { every: 2500 }, 12), 4);
+        assert_eq!(count_flushes(FlushStrategy::MemoryBudget { max_mb: 0 }, 12), 12);
+
+        let mut tracker = FlushTracker::new(FlushStrategy::Entries { every: 300 }, 100, 5);
+        assert!(!tracker.record(100, 0));
+        assert!(!tracker.record(100, 0));
+        assert!(tracker.record(100, 0));
+    }
+}
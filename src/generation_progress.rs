@@ -0,0 +1,83 @@
+// Generation Progress Module
+// Records how far an ASCII combination run has got in a `<output>.progress`
+// sidecar, so an interrupted run can pick up where it last flushed
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// State of a generation run as of its last flush
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenerationProgress {
+    /// Combination length being generated
+    pub length: usize,
+    /// Index of the first combination not yet flushed to the output
+    pub next_index: u128,
+    /// Index the run stops before
+    pub end_index: u128,
+    /// Size of the output file at the flush; anything after it is discarded on resume
+    pub bytes_written: u64,
+}
+
+impl GenerationProgress {
+    /// Combinations still to generate
+    pub fn remaining(&self) -> u128 {
+        self.end_index.saturating_sub(self.next_index)
+    }
+}
+
+/// Sidecar path for `output`: the same name with `.progress` appended
+pub fn progress_path(output: impl AsRef<Path>) -> PathBuf {
+    let mut path = output.as_ref().as_os_str().to_owned();
+    path.push(".progress");
+    PathBuf::from(path)
+}
+
+/// Reads the sidecar for `output`, if there is a readable one
+pub fn load_progress(output: impl AsRef<Path>) -> Option<GenerationProgress> {
+    let content = fs::read_to_string(progress_path(output)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Writes the sidecar for `output` through a temporary file and a rename, so an
+/// interruption leaves either the old or the new progress, never a partial one
+pub fn save_progress(output: impl AsRef<Path>, progress: &GenerationProgress) -> io::Result<()> {
+    let path = progress_path(output);
+    let mut temp = path.clone().into_os_string();
+    temp.push(".tmp");
+    let json = serde_json::to_string(progress).map_err(io::Error::other)?;
+    fs::write(&temp, json)?;
+    fs::rename(&temp, &path)
+}
+
+/// Deletes the sidecar for `output` once the run has finished
+pub fn clear_progress(output: impl AsRef<Path>) -> io::Result<()> {
+    match fs::remove_file(progress_path(output)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_round_trip_and_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("combos.txt");
+        assert_eq!(progress_path(&output), dir.path().join("combos.txt.progress"));
+        assert_eq!(load_progress(&output), None);
+
+        let progress = GenerationProgress { length: 3, next_index: 42, end_index: 100, bytes_written: 512 };
+        save_progress(&output, &progress).unwrap();
+        assert_eq!(load_progress(&output), Some(progress));
+        assert_eq!(progress.remaining(), 58);
+        assert!(!dir.path().join("combos.txt.progress.tmp").exists());
+
+        clear_progress(&output).unwrap();
+        assert_eq!(load_progress(&output), None);
+        clear_progress(&output).unwrap();
+    }
+}
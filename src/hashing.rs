@@ -0,0 +1,129 @@
+// Hashing Module
+// Computes the content hash used for upload IDs and URIs, switching to
+// BLAKE3's multi-threaded tree hashing for large inputs
+
+use sha2::{Sha256, Digest};
+use serde::Serialize;
+use std::fmt;
+use crate::config::HashConfig;
+
+/// Hashing strategy applied to a buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashMode {
+    /// Single-threaded SHA-256 (default for small inputs)
+    Sha256,
+    /// BLAKE3 with its built-in parallel tree hashing
+    Blake3Parallel,
+}
+
+impl fmt::Display for HashMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HashMode::Sha256 => write!(f, "sha256"),
+            HashMode::Blake3Parallel => write!(f, "blake3 (parallel)"),
+        }
+    }
+}
+
+/// A 32-byte digest together with the mode that produced it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashOutput {
+    pub digest: [u8; 32],
+    pub mode: HashMode,
+}
+
+/// Picks the hashing mode for a buffer of `len` bytes based on the upload hash config
+pub fn select_hash_mode(len: usize, config: &HashConfig) -> HashMode {
+    let threshold = config.parallel_threshold_mb.saturating_mul(1024 * 1024);
+    if config.parallel_hashing && len >= threshold {
+        HashMode::Blake3Parallel
+    } else {
+        HashMode::Sha256
+    }
+}
+
+/// Hashes `data` with an explicit mode
+pub fn hash_with_mode(data: &[u8], mode: HashMode) -> [u8; 32] {
+    match mode {
+        HashMode::Sha256 => {
+            let mut hasher = Sha256::new();
+            hasher.update(data);
+            hasher.finalize().into()
+        }
+        HashMode::Blake3Parallel => {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update_rayon(data);
+            *hasher.finalize().as_bytes()
+        }
+    }
+}
+
+/// Hashes `data`, using parallel hashing when the config allows it and the input is large enough
+pub fn hash_data(data: &[u8], config: &HashConfig) -> HashOutput {
+    let mode = select_hash_mode(data.len(), config);
+    HashOutput {
+        digest: hash_with_mode(data, mode),
+        mode,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_config(parallel_hashing: bool, parallel_threshold_mb: usize) -> HashConfig {
+        HashConfig {
+            algorithm: "sha256".to_string(),
+            short_hash_length: 8,
+            upload_id_length: 16,
+            parallel_hashing,
+            parallel_threshold_mb,
+        }
+    }
+
+    #[test]
+    fn test_parallel_hash_matches_reference_blake3() {
+        // 8 MiB of non-repeating data spans many BLAKE3 chunks, so rayon actually splits the work
+        let data: Vec<u8> = (0..8 * 1024 * 1024u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect();
+        let parallel = hash_with_mode(&data, HashMode::Blake3Parallel);
+        assert_eq!(&parallel, blake3::hash(&data).as_bytes());
+    }
+
+    #[test]
+    fn test_blake3_known_vector() {
+        // Official BLAKE3 test vector for the empty input
+        let digest = hash_with_mode(b"", HashMode::Blake3Parallel);
+        assert_eq!(
+            hex::encode(digest),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+    }
+
+    #[test]
+    fn test_sha256_mode_unchanged() {
+        let digest = hash_with_mode(b"abc", HashMode::Sha256);
+        assert_eq!(
+            hex::encode(digest),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_mode_selection_respects_threshold() {
+        let config = hash_config(true, 1);
+        assert_eq!(select_hash_mode(1024 * 1024 - 1, &config), HashMode::Sha256);
+        assert_eq!(select_hash_mode(1024 * 1024, &config), HashMode::Blake3Parallel);
+
+        let disabled = hash_config(false, 1);
+        assert_eq!(select_hash_mode(10 * 1024 * 1024, &disabled), HashMode::Sha256);
+    }
+
+    #[test]
+    fn test_hash_data_reports_mode() {
+        let config = hash_config(true, 0);
+        let output = hash_data(b"payload", &config);
+        assert_eq!(output.mode, HashMode::Blake3Parallel);
+        assert_eq!(&output.digest, blake3::hash(b"payload").as_bytes());
+    }
+}
@@ -1,5 +1,10 @@
 use std::env;
-use reqwest::multipart;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use bytes::Bytes;
+use reqwest::{multipart, Body, StatusCode};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use dotenvy::dotenv;
 
@@ -9,7 +14,10 @@ pub enum IpfsError {
     NetworkError(String),
     AuthError(String),
     ApiError(String),
+    /// A 5xx response; the request may succeed if retried
+    ServerError(String),
     ConfigError(String),
+    InvalidCid(String),
 }
 
 impl std::fmt::Display for IpfsError {
@@ -18,50 +26,265 @@ impl std::fmt::Display for IpfsError {
             IpfsError::NetworkError(msg) => write!(f, "Network error: {}", msg),
             IpfsError::AuthError(msg) => write!(f, "Authentication error: {}", msg),
             IpfsError::ApiError(msg) => write!(f, "API error: {}", msg),
+            IpfsError::ServerError(msg) => write!(f, "Server error: {}", msg),
             IpfsError::ConfigError(msg) => write!(f, "Configuration error: {}", msg),
+            IpfsError::InvalidCid(cid) => write!(f, "Invalid CID: {}", cid),
         }
     }
 }
 
 impl std::error::Error for IpfsError {}
 
-/// Pins a file to IPFS using Pinata service
+impl IpfsError {
+    /// Whether the failure is transient: network errors and 5xx responses
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, IpfsError::NetworkError(_) | IpfsError::ServerError(_))
+    }
+}
+
+/// Pinata endpoint for pinning a single file
+const PINATA_PIN_FILE_URL: &str = "https://api.pinata.cloud/pinning/pinFileToIPFS";
+/// Pinata endpoint for removing a pin, followed by `/{cid}`
+const PINATA_UNPIN_URL: &str = "https://api.pinata.cloud/pinning/unpin";
+/// web3.storage upload endpoint
+const WEB3_STORAGE_UPLOAD_URL: &str = "https://api.web3.storage/upload";
+
+/// Service files are pinned to, chosen by `storage.ipfs.provider`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IpfsProvider {
+    /// Pinata, authenticated with the JWT in `pinata_jwt_env`
+    #[default]
+    Pinata,
+    /// web3.storage, authenticated with the API token in `web3_storage_token_env`
+    Web3Storage,
+    /// A Kubo node's RPC API at `url` (e.g. `http://127.0.0.1:5001`), without auth
+    LocalNode { url: String },
+}
+
+impl fmt::Display for IpfsProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IpfsProvider::Pinata => write!(f, "Pinata"),
+            IpfsProvider::Web3Storage => write!(f, "web3.storage"),
+            IpfsProvider::LocalNode { url } => write!(f, "IPFS node at {}", url),
+        }
+    }
+}
+
+impl IpfsProvider {
+    /// URL files are uploaded to
+    pub fn endpoint(&self) -> String {
+        match self {
+            IpfsProvider::Pinata => PINATA_PIN_FILE_URL.to_string(),
+            IpfsProvider::Web3Storage => WEB3_STORAGE_UPLOAD_URL.to_string(),
+            IpfsProvider::LocalNode { url } => format!("{}/api/v0/add?pin=true", url.trim_end_matches('/')),
+        }
+    }
+
+    /// URL that removes the pin on `cid`; web3.storage offers no unpinning
+    pub fn unpin_endpoint(&self, cid: &str) -> Result<String, IpfsError> {
+        match self {
+            IpfsProvider::Pinata => Ok(format!("{}/{}", PINATA_UNPIN_URL, cid)),
+            IpfsProvider::Web3Storage => Err(IpfsError::ConfigError(format!("{} does not support unpinning", self))),
+            IpfsProvider::LocalNode { url } => Ok(format!("{}/api/v0/pin/rm?arg={}", url.trim_end_matches('/'), cid)),
+        }
+    }
+
+    /// Bearer token read from the environment variable configured for this provider
+    fn credentials(&self) -> Result<Option<String>, IpfsError> {
+        let ipfs = &crate::config::get_config().storage.ipfs;
+        let env_var = match self {
+            IpfsProvider::Pinata => &ipfs.pinata_jwt_env,
+            IpfsProvider::Web3Storage => &ipfs.web3_storage_token_env,
+            IpfsProvider::LocalNode { .. } => return Ok(None),
+        };
+        dotenv().ok();
+        env::var(env_var)
+            .map(Some)
+            .map_err(|_| IpfsError::ConfigError(format!("{} not found in environment", env_var)))
+    }
+
+    /// Upload request in this provider's format: multipart `file` for Pinata and Kubo,
+    /// the raw bytes for web3.storage
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        endpoint: &str,
+        token: Option<&str>,
+        file_data: &[u8],
+        filename: &str,
+        progress: Option<&PinProgress>,
+    ) -> Result<reqwest::RequestBuilder, IpfsError> {
+        let request = client.post(endpoint);
+        let request = match token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+        match self {
+            IpfsProvider::Web3Storage => Ok(request
+                .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+                .header("X-NAME", filename)
+                .body(upload_body(file_data, progress))),
+            IpfsProvider::Pinata | IpfsProvider::LocalNode { .. } => {
+                let form = multipart::Form::new().part(
+                    "file",
+                    multipart::Part::stream_with_length(upload_body(file_data, progress), file_data.len() as u64)
+                        .file_name(filename.to_string())
+                        .mime_str("application/octet-stream")
+                        .map_err(|e| IpfsError::ApiError(format!("Failed to create form part: {}", e)))?,
+                );
+                Ok(request.multipart(form))
+            }
+        }
+    }
+
+    /// Field of the JSON response holding the CID
+    fn cid_field(&self) -> &'static str {
+        match self {
+            IpfsProvider::Pinata => "IpfsHash",
+            IpfsProvider::Web3Storage => "cid",
+            IpfsProvider::LocalNode { .. } => "Hash",
+        }
+    }
+}
+
+/// Called with `(bytes_sent, total)` as an upload body is sent
+pub type PinProgress = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+/// Size of the pieces a body is streamed in when progress is reported
+const PROGRESS_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Request body for an upload. Without a callback the bytes are sent in one piece;
+/// with one they are streamed in `PROGRESS_CHUNK_SIZE` pieces, reporting each as it
+/// is handed to the connection.
+fn upload_body(file_data: &[u8], progress: Option<&PinProgress>) -> Body {
+    let Some(progress) = progress.cloned() else {
+        return Body::from(file_data.to_vec());
+    };
+    let total = file_data.len() as u64;
+    if total == 0 {
+        progress(0, 0);
+        return Body::from(Vec::new());
+    }
+    let data = Bytes::copy_from_slice(file_data);
+    let pieces = (0..data.len()).step_by(PROGRESS_CHUNK_SIZE).map(move |start| {
+        let end = (start + PROGRESS_CHUNK_SIZE).min(data.len());
+        progress(end as u64, total);
+        Ok::<_, std::io::Error>(data.slice(start..end))
+    });
+    Body::wrap_stream(futures_util::stream::iter(pieces))
+}
+
+/// Where a pin request goes
+struct PinTarget<'a> {
+    provider: &'a IpfsProvider,
+    endpoint: &'a str,
+    token: Option<&'a str>,
+}
+
+/// Attempts made by `pin_file_to_ipfs`
+pub const DEFAULT_PIN_ATTEMPTS: u32 = 3;
+/// Delay before the first retry of `pin_file_to_ipfs`; doubled for each further retry
+pub const DEFAULT_PIN_BASE_DELAY: Duration = Duration::from_millis(250);
+
+/// Pins a file to IPFS through the configured provider, retrying transient failures
 pub async fn pin_file_to_ipfs(
     file_data: &[u8],
     filename: &str,
 ) -> Result<String, IpfsError> {
-    dotenv().ok();
-    
-    // Get Pinata credentials from environment
-    let jwt_token = env::var("PINATA_JWT")
-        .map_err(|_| IpfsError::ConfigError("PINATA_JWT not found in environment".to_string()))?;
-    
+    pin_file_to_ipfs_with_retry(file_data, filename, DEFAULT_PIN_ATTEMPTS, DEFAULT_PIN_BASE_DELAY).await
+}
+
+/// Pins a file to IPFS through the configured provider, making up to `attempts` requests.
+/// Only network errors and 5xx responses are retried, waiting `base_delay` before
+/// the first retry and doubling it each time.
+pub async fn pin_file_to_ipfs_with_retry(
+    file_data: &[u8],
+    filename: &str,
+    attempts: u32,
+    base_delay: Duration,
+) -> Result<String, IpfsError> {
+    pin_configured(file_data, filename, attempts, base_delay, None).await
+}
+
+/// Pins a file like `pin_file_to_ipfs`, streaming the body and calling `on_progress`
+/// with `(bytes_sent, total)` as it goes. The last call has `bytes_sent == total`;
+/// a retried upload starts counting again from zero.
+pub async fn pin_file_to_ipfs_with_progress(
+    file_data: &[u8],
+    filename: &str,
+    on_progress: impl Fn(u64, u64) + Send + Sync + 'static,
+) -> Result<String, IpfsError> {
+    let progress: PinProgress = Arc::new(on_progress);
+    pin_configured(file_data, filename, DEFAULT_PIN_ATTEMPTS, DEFAULT_PIN_BASE_DELAY, Some(progress)).await
+}
+
+/// Pins through the provider in the loaded configuration
+async fn pin_configured(
+    file_data: &[u8],
+    filename: &str,
+    attempts: u32,
+    base_delay: Duration,
+    progress: Option<PinProgress>,
+) -> Result<String, IpfsError> {
+    let provider = &crate::config::get_config().storage.ipfs.provider;
+    let token = provider.credentials()?;
+    let target = PinTarget { provider, endpoint: &provider.endpoint(), token: token.as_deref() };
+    pin_with_retry(&target, file_data, filename, attempts, base_delay, progress.as_ref()).await
+}
+
+/// Retry loop around `pin_once`
+async fn pin_with_retry(
+    target: &PinTarget<'_>,
+    file_data: &[u8],
+    filename: &str,
+    attempts: u32,
+    base_delay: Duration,
+    progress: Option<&PinProgress>,
+) -> Result<String, IpfsError> {
     // Create HTTP client
     let client = reqwest::Client::new();
-    
-    // Prepare multipart form data
-    let form = multipart::Form::new()
-        .part(
-            "file",
-            multipart::Part::bytes(file_data.to_vec())
-                .file_name(filename.to_string())
-                .mime_str("application/octet-stream")
-                .map_err(|e| IpfsError::ApiError(format!("Failed to create form part: {}", e)))?,
-        );
-    
-    // Send request to Pinata
-    let response = client
-        .post("https://api.pinata.cloud/pinning/pinFileToIPFS")
-        .bearer_auth(&jwt_token)
-        .multipart(form)
+    let mut delay = base_delay;
+    let mut attempt = 1;
+    loop {
+        match pin_once(&client, target, file_data, filename, progress).await {
+            Err(e) if e.is_retryable() && attempt < attempts => {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// A single pin request
+async fn pin_once(
+    client: &reqwest::Client,
+    target: &PinTarget<'_>,
+    file_data: &[u8],
+    filename: &str,
+    progress: Option<&PinProgress>,
+) -> Result<String, IpfsError> {
+    let provider = target.provider;
+    let response = provider
+        .request(client, target.endpoint, target.token, file_data, filename, progress)?
         .send()
         .await
         .map_err(|e| IpfsError::NetworkError(format!("Failed to send request: {}", e)))?;
     
     // Check response status
-    if !response.status().is_success() {
+    let status = response.status();
+    if !status.is_success() {
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(IpfsError::ApiError(format!("Pinata API error: {}", error_text)));
+        let message = format!("{} API error ({}): {}", provider, status, error_text);
+        return Err(match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => IpfsError::AuthError(message),
+            status if status.is_server_error() => IpfsError::ServerError(message),
+            _ => IpfsError::ApiError(message),
+        });
     }
     
     // Parse response JSON
@@ -70,10 +293,314 @@ pub async fn pin_file_to_ipfs(
         .await
         .map_err(|e| IpfsError::ApiError(format!("Failed to parse response: {}", e)))?;
     
-    // Extract IPFS hash (CID)
-    let ipfs_hash = response_json["IpfsHash"]
+    // Extract the CID
+    let field = provider.cid_field();
+    let cid = response_json[field]
         .as_str()
-        .ok_or_else(|| IpfsError::ApiError("No IpfsHash in response".to_string()))?;
+        .ok_or_else(|| IpfsError::ApiError(format!("No {} in {} response", field, provider)))?;
     
-    Ok(ipfs_hash.to_string())
-}
\ No newline at end of file
+    Ok(cid.to_string())
+}
+
+/// Message of the error returned when the CID was not pinned
+pub const ALREADY_UNPINNED: &str = "already unpinned";
+
+/// Removes the pin on `cid` through the configured provider, so the data can be
+/// garbage collected once a file is deleted. A CID that is not pinned gives
+/// `IpfsError::ApiError("already unpinned")`.
+pub async fn unpin_file_from_ipfs(cid: &str) -> Result<(), IpfsError> {
+    validate_cid(cid)?;
+    let provider = &crate::config::get_config().storage.ipfs.provider;
+    let endpoint = provider.unpin_endpoint(cid)?;
+    let token = provider.credentials()?;
+    let target = PinTarget { provider, endpoint: &endpoint, token: token.as_deref() };
+    unpin_once(&reqwest::Client::new(), &target).await
+}
+
+/// A single unpin request: `DELETE` for Pinata, `POST` for the Kubo RPC API
+async fn unpin_once(client: &reqwest::Client, target: &PinTarget<'_>) -> Result<(), IpfsError> {
+    let request = match target.provider {
+        IpfsProvider::LocalNode { .. } => client.post(target.endpoint),
+        _ => client.delete(target.endpoint),
+    };
+    let request = match target.token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    };
+    let response = request
+        .send()
+        .await
+        .map_err(|e| IpfsError::NetworkError(format!("Failed to send request: {}", e)))?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+    // Kubo answers a missing pin with a 500 whose message says "not pinned"
+    if status == StatusCode::NOT_FOUND || error_text.contains("not pinned") {
+        return Err(IpfsError::ApiError(ALREADY_UNPINNED.to_string()));
+    }
+    let message = format!("{} API error ({}): {}", target.provider, status, error_text);
+    Err(match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => IpfsError::AuthError(message),
+        status if status.is_server_error() => IpfsError::ServerError(message),
+        _ => IpfsError::ApiError(message),
+    })
+}
+
+/// Checks that `cid` looks like a CIDv0 (`Qm` + 44 base58 characters) or a
+/// base32 CIDv1 (`b` followed by lowercase base32), before any request is made
+pub fn validate_cid(cid: &str) -> Result<(), IpfsError> {
+    const BASE58: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    let valid = if cid.starts_with("Qm") {
+        cid.len() == 46 && cid.chars().all(|c| BASE58.contains(c))
+    } else if let Some(rest) = cid.strip_prefix('b') {
+        rest.len() >= 32 && rest.chars().all(|c| c.is_ascii_lowercase() || ('2'..='7').contains(&c))
+    } else {
+        false
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(IpfsError::InvalidCid(cid.to_string()))
+    }
+}
+
+/// Link to `cid` on the gateway at `gateway` (e.g. `https://host/ipfs/`)
+pub fn gateway_url(gateway: &str, cid: &str) -> String {
+    format!("{}/{}", gateway.trim_end_matches('/'), cid)
+}
+
+/// Downloads the bytes stored under `cid` from the configured IPFS gateway
+pub async fn fetch_file_from_ipfs(cid: &str) -> Result<Vec<u8>, IpfsError> {
+    let gateway = &crate::config::get_config().storage.ipfs.gateway;
+    fetch_from_gateway(gateway, cid).await
+}
+
+/// `fetch_file_from_ipfs` against an explicit gateway base URL (e.g. `https://host/ipfs/`)
+async fn fetch_from_gateway(gateway: &str, cid: &str) -> Result<Vec<u8>, IpfsError> {
+    validate_cid(cid)?;
+    let url = gateway_url(gateway, cid);
+    let response = reqwest::get(&url)
+        .await
+        .map_err(|e| IpfsError::NetworkError(format!("Failed to fetch {}: {}", url, e)))?;
+    let status = response.status();
+    if status != StatusCode::OK {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(IpfsError::ApiError(format!("Gateway error ({}): {}", status, error_text)));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| IpfsError::NetworkError(format!("Failed to read response body: {}", e)))?;
+    Ok(bytes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode as AxumStatus, routing::{get, post}, Json, Router};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    fn target<'a>(provider: &'a IpfsProvider, endpoint: &'a str, token: Option<&'a str>) -> PinTarget<'a> {
+        PinTarget { provider, endpoint, token }
+    }
+
+    /// Serves a pin endpoint answering each request with the next status in `statuses`
+    /// (200 once they run out), returning its URL and a request counter
+    async fn mock_pinata(statuses: Vec<u16>) -> (String, Arc<AtomicU32>) {
+        let requests = Arc::new(AtomicU32::new(0));
+        let counter = requests.clone();
+        let app = Router::new().route(
+            "/pin",
+            post(move || {
+                let n = counter.fetch_add(1, Ordering::SeqCst) as usize;
+                let status = statuses.get(n).copied().unwrap_or(200);
+                async move {
+                    let status = AxumStatus::from_u16(status).unwrap();
+                    (status, Json(serde_json::json!({ "IpfsHash": "QmRetried" })))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}/pin", addr), requests)
+    }
+
+    #[tokio::test]
+    async fn test_pin_retries_server_errors_then_succeeds() {
+        let (url, requests) = mock_pinata(vec![503, 502]).await;
+        let cid = pin_with_retry(&target(&IpfsProvider::Pinata, &url, Some("jwt")), b"data", "f.bin", 3, Duration::from_millis(1), None).await.unwrap();
+        assert_eq!(cid, "QmRetried");
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_pin_does_not_retry_client_errors() {
+        let (url, requests) = mock_pinata(vec![401]).await;
+        let result = pin_with_retry(&target(&IpfsProvider::Pinata, &url, Some("jwt")), b"data", "f.bin", 3, Duration::from_millis(1), None).await;
+        assert!(matches!(result, Err(IpfsError::AuthError(_))));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        let (url, requests) = mock_pinata(vec![500, 500, 500]).await;
+        let result = pin_with_retry(&target(&IpfsProvider::Pinata, &url, Some("jwt")), b"data", "f.bin", 3, Duration::from_millis(1), None).await;
+        assert!(matches!(result, Err(IpfsError::ServerError(_))));
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    /// What a mock provider saw of the last upload
+    #[derive(Debug, Default, Clone)]
+    struct SeenRequest {
+        authorization: Option<String>,
+        content_type: String,
+        name_header: Option<String>,
+        body: Vec<u8>,
+    }
+
+    /// Serves `path` answering every upload with `response`, recording the request
+    async fn mock_provider(path: &str, response: serde_json::Value) -> (String, Arc<std::sync::Mutex<SeenRequest>>) {
+        let seen = Arc::new(std::sync::Mutex::new(SeenRequest::default()));
+        let recorder = seen.clone();
+        let app = Router::new().route(
+            path,
+            post(move |headers: axum::http::HeaderMap, body: axum::body::Bytes| {
+                let header = |name: &str| headers.get(name).map(|v| v.to_str().unwrap().to_string());
+                *recorder.lock().unwrap() = SeenRequest {
+                    authorization: header("authorization"),
+                    content_type: header("content-type").unwrap_or_default(),
+                    name_header: header("x-name"),
+                    body: body.to_vec(),
+                };
+                async move { Json(response) }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), seen)
+    }
+
+    #[tokio::test]
+    async fn test_each_provider_request_and_response_shape() {
+        let data = b"provider payload";
+
+        let (base, seen) = mock_provider("/pinning/pinFileToIPFS", serde_json::json!({ "IpfsHash": "QmPinata", "PinSize": 16 })).await;
+        let url = format!("{}/pinning/pinFileToIPFS", base);
+        let cid = pin_with_retry(&target(&IpfsProvider::Pinata, &url, Some("jwt")), data, "a.bin", 1, Duration::ZERO, None).await.unwrap();
+        assert_eq!(cid, "QmPinata");
+        let request = seen.lock().unwrap().clone();
+        assert_eq!(request.authorization.as_deref(), Some("Bearer jwt"));
+        assert!(request.content_type.starts_with("multipart/form-data"));
+
+        let (base, seen) = mock_provider("/upload", serde_json::json!({ "cid": "bafyweb3" })).await;
+        let url = format!("{}/upload", base);
+        let cid = pin_with_retry(&target(&IpfsProvider::Web3Storage, &url, Some("token")), data, "a.bin", 1, Duration::ZERO, None).await.unwrap();
+        assert_eq!(cid, "bafyweb3");
+        let request = seen.lock().unwrap().clone();
+        assert_eq!(request.authorization.as_deref(), Some("Bearer token"));
+        assert_eq!(request.name_header.as_deref(), Some("a.bin"));
+        assert_eq!(request.body, data);
+
+        let (base, seen) = mock_provider("/api/v0/add", serde_json::json!({ "Name": "a.bin", "Hash": "QmKubo", "Size": "24" })).await;
+        let provider = IpfsProvider::LocalNode { url: format!("{}/", base) };
+        assert_eq!(provider.endpoint(), format!("{}/api/v0/add?pin=true", base));
+        let cid = pin_with_retry(&target(&provider, &provider.endpoint(), None), data, "a.bin", 1, Duration::ZERO, None).await.unwrap();
+        assert_eq!(cid, "QmKubo");
+        let request = seen.lock().unwrap().clone();
+        assert_eq!(request.authorization, None);
+        assert!(request.content_type.starts_with("multipart/form-data"));
+
+        // A response in another provider's shape has no CID where this one expects it
+        let (base, _) = mock_provider("/upload", serde_json::json!({ "IpfsHash": "QmPinata" })).await;
+        let result = pin_with_retry(&target(&IpfsProvider::Web3Storage, &format!("{}/upload", base), Some("t")), data, "a.bin", 1, Duration::ZERO, None).await;
+        assert!(matches!(result, Err(IpfsError::ApiError(msg)) if msg.contains("No cid")));
+    }
+
+    #[tokio::test]
+    async fn test_progress_reaches_total() {
+        let data = vec![7u8; 3 * PROGRESS_CHUNK_SIZE + 100];
+        for provider in [IpfsProvider::Pinata, IpfsProvider::Web3Storage] {
+            let (base, seen) = mock_provider("/upload", serde_json::json!({ "IpfsHash": "QmBig", "cid": "QmBig" })).await;
+            let url = format!("{}/upload", base);
+            let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let recorder = calls.clone();
+            let progress: PinProgress = Arc::new(move |sent, total| recorder.lock().unwrap().push((sent, total)));
+
+            let cid = pin_with_retry(&target(&provider, &url, None), &data, "big.bin", 1, Duration::ZERO, Some(&progress)).await.unwrap();
+            assert_eq!(cid, "QmBig");
+            let calls = calls.lock().unwrap().clone();
+            assert_eq!(calls.len(), 4, "{}", provider);
+            assert!(calls.windows(2).all(|w| w[0].0 < w[1].0));
+            assert_eq!(calls.last(), Some(&(data.len() as u64, data.len() as u64)));
+            assert!(seen.lock().unwrap().body.len() >= data.len());
+        }
+    }
+
+    #[test]
+    fn test_provider_config_format() {
+        let provider: IpfsProvider = serde_json::from_str(r#"{"type": "local_node", "url": "http://127.0.0.1:5001"}"#).unwrap();
+        assert_eq!(provider, IpfsProvider::LocalNode { url: "http://127.0.0.1:5001".to_string() });
+        assert_eq!(serde_json::from_str::<IpfsProvider>(r#"{"type": "web3_storage"}"#).unwrap(), IpfsProvider::Web3Storage);
+        assert_eq!(serde_json::to_value(IpfsProvider::default()).unwrap(), serde_json::json!({ "type": "pinata" }));
+    }
+
+    const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+    #[tokio::test]
+    async fn test_unpin_success_and_already_unpinned() {
+        let app = Router::new().route(
+            "/pinning/unpin/:cid",
+            axum::routing::delete(|axum::extract::Path(cid): axum::extract::Path<String>, headers: axum::http::HeaderMap| async move {
+                assert_eq!(headers.get("authorization").unwrap(), "Bearer jwt");
+                if cid == CID { AxumStatus::OK } else { AxumStatus::NOT_FOUND }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let client = reqwest::Client::new();
+
+        let url = format!("http://{}/pinning/unpin/{}", addr, CID);
+        unpin_once(&client, &target(&IpfsProvider::Pinata, &url, Some("jwt"))).await.unwrap();
+
+        let url = format!("http://{}/pinning/unpin/QmGone", addr);
+        let result = unpin_once(&client, &target(&IpfsProvider::Pinata, &url, Some("jwt"))).await;
+        assert!(matches!(result, Err(IpfsError::ApiError(msg)) if msg == ALREADY_UNPINNED));
+
+        assert_eq!(IpfsProvider::Pinata.unpin_endpoint(CID).unwrap(), format!("https://api.pinata.cloud/pinning/unpin/{}", CID));
+        assert!(matches!(IpfsProvider::Web3Storage.unpin_endpoint(CID), Err(IpfsError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_validate_cid() {
+        assert!(validate_cid(CID).is_ok());
+        assert!(validate_cid("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi").is_ok());
+        for bad in ["", "Qm123", "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbd0", "../etc/passwd", "bafyBEIG"] {
+            assert!(matches!(validate_cid(bad), Err(IpfsError::InvalidCid(_))), "{:?} should be rejected", bad);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_gateway() {
+        let app = Router::new()
+            .route(&format!("/ipfs/{}", CID), get(|| async { vec![0u8, 1, 2, 255] }))
+            .fallback(|| async { (AxumStatus::NOT_FOUND, "no such cid") });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let gateway = format!("http://{}/ipfs/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        assert_eq!(fetch_from_gateway(&gateway, CID).await.unwrap(), vec![0u8, 1, 2, 255]);
+        let missing = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdH";
+        assert!(matches!(fetch_from_gateway(&gateway, missing).await, Err(IpfsError::ApiError(_))));
+    }
+
+    #[test]
+    fn test_gateway_url_joins_with_one_slash() {
+        let expected = format!("https://ipfs.example.com/ipfs/{}", CID);
+        assert_eq!(gateway_url("https://ipfs.example.com/ipfs/", CID), expected);
+        assert_eq!(gateway_url("https://ipfs.example.com/ipfs", CID), expected);
+    }
+}
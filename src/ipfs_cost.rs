@@ -0,0 +1,127 @@
+// IPFS Cost Module
+// Estimates how many bytes a batch of uploads would pin and what that
+// storage costs per month
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::ascii_converter::convert_to_printable_ascii;
+use crate::compression::compress_file;
+
+const BYTES_PER_GB: f64 = 1_000_000_000.0;
+
+/// Pinned size of a single file
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinnedFile {
+    pub path: PathBuf,
+    pub original_size: u64,
+    pub pinned_size: u64,
+}
+
+/// Totals for a batch of files
+#[derive(Debug, Clone, PartialEq)]
+pub struct IpfsCostReport {
+    pub files: Vec<PinnedFile>,
+    pub total_pinned_bytes: u64,
+    pub cost_per_gb_month: f64,
+    pub monthly_cost: f64,
+}
+
+/// Bytes the upload pipeline would pin for `data`; mirrors its encode and compress steps
+pub fn pinned_size(data: &[u8], raw: bool) -> io::Result<u64> {
+    let encoded = if raw {
+        data.to_vec()
+    } else {
+        let (ascii, _) = convert_to_printable_ascii(data).map_err(io::Error::other)?;
+        ascii.iter().map(|b| format!("{:08b}", b)).collect::<String>().into_bytes()
+    };
+    let compressed = compress_file(&encoded).map_err(io::Error::other)?;
+    Ok(compressed.len() as u64)
+}
+
+/// Expands the given paths into a sorted list of files, walking directories recursively
+pub fn collect_files(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    fn walk(path: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+        if path.is_dir() {
+            for entry in fs::read_dir(path)? {
+                walk(&entry?.path(), files)?;
+            }
+        } else {
+            files.push(path.to_path_buf());
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    for path in paths {
+        walk(path, &mut files)?;
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Monthly storage cost of `bytes` at `cost_per_gb_month`
+pub fn monthly_cost(bytes: u64, cost_per_gb_month: f64) -> f64 {
+    bytes as f64 / BYTES_PER_GB * cost_per_gb_month
+}
+
+/// Sums the pinned sizes of every file under `paths` and prices them
+pub fn estimate_ipfs_cost(paths: &[PathBuf], raw: bool, cost_per_gb_month: f64) -> io::Result<IpfsCostReport> {
+    let mut files = Vec::new();
+    for path in collect_files(paths)? {
+        let data = fs::read(&path)?;
+        files.push(PinnedFile {
+            original_size: data.len() as u64,
+            pinned_size: pinned_size(&data, raw)?,
+            path,
+        });
+    }
+
+    let total_pinned_bytes = files.iter().map(|f| f.pinned_size).sum();
+    Ok(IpfsCostReport {
+        files,
+        total_pinned_bytes,
+        cost_per_gb_month,
+        monthly_cost: monthly_cost(total_pinned_bytes, cost_per_gb_month),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_sums_pinned_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), b"hello").unwrap();
+        fs::create_dir(dir.path().join("nested")).unwrap();
+        fs::write(dir.path().join("nested/b.bin"), [0u8; 20]).unwrap();
+        let paths = vec![dir.path().to_path_buf()];
+
+        let raw = estimate_ipfs_cost(&paths, true, 0.15).unwrap();
+        assert_eq!(raw.files.len(), 2);
+        // "hello" has no runs and is stored (5 + headers); the zeros become one RLE packet (2 + headers)
+        let headers = crate::compression::HEADER_LEN as u64 + 1;
+        assert_eq!(raw.total_pinned_bytes, 5 + 2 + 2 * headers);
+        assert!((raw.monthly_cost - raw.total_pinned_bytes as f64 / 1e9 * 0.15).abs() < 1e-15);
+
+        // The ASCII pipeline compresses an 8-character binary string per input byte
+        let ascii = estimate_ipfs_cost(&paths, false, 0.15).unwrap();
+        let expected: u64 = [b"hello".to_vec(), vec![0u8; 20]]
+            .iter()
+            .map(|data| {
+                let (ascii, _) = crate::ascii_converter::convert_to_printable_ascii(data).unwrap();
+                let binary: String = ascii.iter().map(|b| format!("{:08b}", b)).collect();
+                compress_file(binary.as_bytes()).unwrap().len() as u64
+            })
+            .sum();
+        assert_eq!(ascii.total_pinned_bytes, expected);
+        assert!(ascii.total_pinned_bytes < 25 * 8);
+    }
+
+    #[test]
+    fn test_monthly_cost_per_gb() {
+        assert_eq!(monthly_cost(2_000_000_000, 0.15), 0.3);
+        assert_eq!(monthly_cost(0, 0.15), 0.0);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod ascii_combinations;
 pub mod ascii_converter;
 pub mod cli;
 pub mod compression;
@@ -6,10 +7,34 @@ pub mod starknet_client;
 pub mod utils;
 pub mod ipfs_client;
 pub mod config;
+pub mod error;
+pub mod hashing;
+pub mod upload_manifest;
+pub mod chunk_detection;
+pub mod debug_files;
+pub mod output;
+pub mod selftest;
+pub mod ipfs_cost;
+pub mod upload_index;
+pub mod dictionary;
+pub mod backend;
+pub mod benchmark;
+pub mod sidecar;
+pub mod flush;
+pub mod storage;
+pub mod streaming;
+pub mod generation_progress;
+pub mod threads;
+pub mod binary_chunks;
+pub mod progress;
+pub mod pin_cache;
+pub mod analysis;
+pub mod archive;
 
 // Re-export commonly used items
 pub use ascii_converter::convert_to_printable_ascii;
-pub use cli::{main_menu, upload_data_cli, generate_ultra_compressed_ascii_combinations_cli};
+pub use cli::{main_menu, upload_data_cli, generate_ultra_compressed_ascii_combinations_cli, UploadSummary};
+pub use error::StarkSqueezeError;
 pub use mapping::{MappingError};
 pub use starknet_client::upload_data;
 pub use utils::short_string_to_felt;
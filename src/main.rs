@@ -1,17 +1,382 @@
-use stark_squeeze::cli::{main_menu, generate_ultra_compressed_ascii_combinations_cli};
+use std::path::PathBuf;
+
+use clap::{ArgAction, Parser, Subcommand};
+use stark_squeeze::cli::{main_menu, generate_ultra_compressed_ascii_combinations_cli, resume_upload_cli, compress_file_cli, compress_reader_cli, compress_dir_cli, selftest_cli, verify_cli, VerifySource, migrate_mapping_cli, config_init_cli, ratio_self_check_cli, analyze_cli, archive_create_cli, archive_extract_cli, reconstruct_uri_cli, decompress_path_cli, report_ipfs_cost_cli, history_cli, debug_ls_cli, debug_clean_cli, upload_data_cli_with, print_upload_summary, estimate_upload_cli_with, print_upload_estimate, ChunkSizeChoice, CompressCliOptions, UploadCliOptions};
+use stark_squeeze::mapping::MappingFormat;
+use stark_squeeze::storage::{PinataStorage, StarknetUploader};
+
+/// Compress files and store their mappings on IPFS and Starknet.
+/// Without a command, opens the interactive menu.
+#[derive(Debug, Parser)]
+#[command(name = "stark_squeeze", version)]
+struct Cli {
+    /// Print only results, for scripts
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    /// Print more detail: -v for per-step details, -vv for per-chunk debug output
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+    /// Write each upload's binary string expansion to the debug artifact directory
+    #[arg(long, global = true)]
+    emit_binary_string: bool,
+    /// Configuration file to use instead of config.json (STARK_SQUEEZE_CONFIG takes precedence)
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Check the configured compression ratio against one measured on a built-in corpus before running
+    #[arg(long, global = true)]
+    self_check: bool,
+    /// Worker threads for parallel conversion and hashing (0 uses every core)
+    #[arg(long, global = true, value_name = "N", default_value_t = 0)]
+    threads: usize,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Compress, pin and record a file without any prompts
+    Upload {
+        /// File to upload
+        #[arg(long)]
+        file: PathBuf,
+        /// Don't pin the compressed file to IPFS
+        #[arg(long)]
+        no_ipfs: bool,
+        /// Don't submit to Starknet; finish later with resume-upload
+        #[arg(long)]
+        no_starknet: bool,
+        /// Read files above large_file_warning_mb without asking
+        #[arg(long)]
+        allow_large: bool,
+        /// Upload files above validation.file.max_size_mb
+        #[arg(long)]
+        disable_file_size_limit: bool,
+        /// Print the estimated fee and calldata size, then exit without pinning or sending anything
+        #[arg(long)]
+        estimate: bool,
+        /// Dictionary codec chunk size, clamped to compression.chunk_size_range, or `auto` to pick the best
+        #[arg(long, value_name = "N|auto")]
+        chunk_size: Option<ChunkSizeChoice>,
+    },
+    /// Generate the ultra-compressed ASCII combinations dictionary
+    #[command(long_flag = "generate")]
+    Generate,
+    /// Estimate the IPFS storage cost of files or directories
+    #[command(long_flag = "report-ipfs-cost")]
+    ReportIpfsCost {
+        /// Files or directories to estimate
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Estimate for raw mode, without ASCII expansion
+        #[arg(long)]
+        raw: bool,
+    },
+    /// Run the built-in round-trip checks
+    Selftest,
+    /// List debug artifacts (same as `debug ls`)
+    #[command(long_flag = "list-debug-files", hide = true)]
+    ListDebugFiles,
+    /// Inspect or remove debug artifacts
+    Debug {
+        #[command(subcommand)]
+        action: DebugAction,
+    },
+    /// List past uploads from the local index
+    History {
+        /// Filter by file name, hash, CID or transaction hash
+        query: Option<String>,
+    },
+    /// Finish an interrupted upload from its manifest
+    ResumeUpload {
+        /// Upload manifest (prompted for when omitted)
+        manifest: Option<PathBuf>,
+    },
+    /// Compress a file
+    #[command(long_flag = "compress")]
+    Compress {
+        /// File to compress (prompted for when omitted)
+        #[arg(long, value_name = "PATH")]
+        file: Option<PathBuf>,
+        /// Compress everything read from stdin instead of a file; no sidecar applies to it
+        #[arg(long, conflicts_with = "file", requires_all = ["file_type", "output"])]
+        stdin: bool,
+        /// Extension to treat stdin input as having (e.g. txt), since it has no file name
+        #[arg(long, value_name = "EXT", requires = "stdin")]
+        file_type: Option<String>,
+        /// Compressed file to write, or - to stream the compressed bytes to stdout with all other output on stderr
+        #[arg(long, value_name = "PATH", conflicts_with = "stdout")]
+        output: Option<PathBuf>,
+        /// Print a single JSON result object to stdout and nothing else
+        #[arg(long, requires = "file", conflicts_with = "stdout")]
+        json: bool,
+        /// Read files above large_file_warning_mb without asking
+        #[arg(long)]
+        allow_large: bool,
+        /// Chunk the file's raw bytes instead of its ASCII conversion
+        #[arg(long)]
+        raw: bool,
+        /// Refuse files whose ASCII conversion alters more than this percentage of bytes
+        #[arg(long, value_name = "PERCENT", value_parser = parse_percent)]
+        max_ascii_loss: Option<f64>,
+        /// Dictionary to compress with
+        #[arg(long, value_name = "PATH")]
+        dictionary: Option<PathBuf>,
+        /// Mapping layout: minimal or complete
+        #[arg(long, value_name = "FORMAT")]
+        mapping_format: Option<MappingFormat>,
+        /// Write compressed bytes to stdout; all other output goes to stderr
+        #[arg(long)]
+        stdout: bool,
+        /// Gzip the compressed output, adding .gz to its name
+        #[arg(long)]
+        gzip: bool,
+        /// Directory for the compressed and mapping files (created if missing)
+        #[arg(long, value_name = "DIR")]
+        output_dir: Option<PathBuf>,
+        /// Chunk size to use instead of the detected one, clamped to compression.chunk_size_range, or `auto` to pick the best
+        #[arg(long, value_name = "N|auto")]
+        chunk_size: Option<ChunkSizeChoice>,
+    },
+    /// Compress every file in a directory, continuing past failures
+    CompressDir {
+        /// Directory whose files are compressed
+        #[arg(long, value_name = "PATH")]
+        dir: PathBuf,
+        /// Also compress files in subdirectories
+        #[arg(long)]
+        recursive: bool,
+        /// Read files above large_file_warning_mb; without it they are skipped
+        #[arg(long)]
+        allow_large: bool,
+        /// Chunk each file's raw bytes instead of its ASCII conversion
+        #[arg(long)]
+        raw: bool,
+        /// Directory for the compressed and mapping files (created if missing); subdirectories are mirrored under it
+        #[arg(long, value_name = "DIR")]
+        output_dir: Option<PathBuf>,
+    },
+    /// Check that a compressed or mapping file restores the original byte for byte
+    Verify {
+        /// Compressed file to check
+        #[arg(long, value_name = "PATH", required_unless_present = "mapping", conflicts_with = "mapping")]
+        compressed: Option<PathBuf>,
+        /// Mapping file to check
+        #[arg(long, value_name = "PATH")]
+        mapping: Option<PathBuf>,
+        /// Original file to compare against
+        #[arg(long, value_name = "PATH")]
+        original: PathBuf,
+    },
+    /// Report a file's entropy and predicted compressibility without compressing it
+    Analyze {
+        /// File to analyze
+        #[arg(long, value_name = "PATH")]
+        file: PathBuf,
+    },
+    /// Restore an upload from its on-chain record and the compressed file on IPFS
+    Reconstruct {
+        /// URI the upload was recorded under
+        #[arg(long)]
+        uri: String,
+        /// Where to write the restored file
+        #[arg(long, value_name = "PATH")]
+        out: PathBuf,
+        /// IPFS CID of the compressed file; defaults to the one recorded on-chain
+        #[arg(long)]
+        cid: Option<String>,
+        /// Treat the upload as raw, without ASCII conversion, whatever was recorded on-chain
+        #[arg(long)]
+        raw: bool,
+    },
+    /// Bundle compressed files into one .sqza archive, or restore them
+    Archive {
+        #[command(subcommand)]
+        action: ArchiveAction,
+    },
+    /// Manage the configuration file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Upgrade a mapping file to the current format version, keeping the original as .map.bak
+    MigrateMapping {
+        /// Mapping file to upgrade
+        #[arg(long, value_name = "PATH")]
+        file: PathBuf,
+    },
+    /// Restore a file written by compress
+    Decompress {
+        /// Compressed file to restore
+        #[arg(long, value_name = "PATH")]
+        file: PathBuf,
+        /// Where to write the restored file; defaults to the compressed file's name without `.txt`
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ArchiveAction {
+    /// Compress files into an archive, stored under their file names
+    Create {
+        /// Archive to write
+        #[arg(long, value_name = "PATH")]
+        out: PathBuf,
+        /// Files to bundle
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Restore every file of an archive to its original name
+    Extract {
+        /// Archive to read
+        archive: PathBuf,
+        /// Directory to restore into (created if missing)
+        #[arg(long, value_name = "DIR")]
+        dir: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigAction {
+    /// Write the default configuration to the config path (config.json unless overridden)
+    Init {
+        /// Overwrite an existing configuration file
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum DebugAction {
+    /// List debug artifacts
+    Ls,
+    /// Remove debug artifacts
+    Clean,
+}
+
+/// Parses a percentage between 0 and 100
+fn parse_percent(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(percent),
+        _ => Err("expected a percentage between 0 and 100".to_string()),
+    }
+}
 
 #[tokio::main]
 async fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    
-    // Check if --generate flag is provided (JSON format with 90% compression)
-    if args.len() > 1 && args[1] == "--generate" {
-        generate_ultra_compressed_ascii_combinations_cli().await;
-    } else if args.len() > 1 && args[1] == "--compress" {
-        // compress_file_cli().await; // This line is removed as per the edit hint.
-    } else if args.len() > 1 && args[1] == "--decompress" {
-        // decompress_file_cli().await; // This line is removed as per the edit hint.
-    } else {
-        main_menu().await;
+    let cli = Cli::parse();
+    // Must be set before anything reads the configuration
+    if let Some(config) = cli.config {
+        stark_squeeze::config::set_config_path(config);
+    }
+    if cli.quiet {
+        stark_squeeze::output::set_quiet(true);
+    }
+    stark_squeeze::output::set_verbosity(cli.verbose);
+    if cli.emit_binary_string {
+        stark_squeeze::debug_files::set_emit_binary_string(true);
+    }
+    if let Err(e) = stark_squeeze::threads::init_global_thread_pool(cli.threads) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+    if cli.self_check {
+        ratio_self_check_cli();
+    }
+
+    match cli.command {
+        None => main_menu().await,
+        Some(Command::Upload { file, no_ipfs, no_starknet, allow_large, disable_file_size_limit, estimate, chunk_size }) => {
+            let options = UploadCliOptions { allow_large, skip_ipfs: no_ipfs, skip_starknet: no_starknet, disable_file_size_limit, chunk_size };
+            if estimate {
+                match estimate_upload_cli_with(&file, options, &PinataStorage, &StarknetUploader).await {
+                    Ok(estimate) => print_upload_estimate(&estimate),
+                    Err(e) => {
+                        eprintln!("Estimate failed: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                return;
+            }
+            match upload_data_cli_with(Some(file), options, &PinataStorage, &StarknetUploader).await {
+                Ok(summary) => print_upload_summary(&summary),
+                Err(e) => {
+                    eprintln!("Upload failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Command::Generate) => generate_ultra_compressed_ascii_combinations_cli().await,
+        Some(Command::ReportIpfsCost { paths, raw }) => report_ipfs_cost_cli(&paths, raw),
+        Some(Command::Selftest) => std::process::exit(selftest_cli()),
+        Some(Command::ListDebugFiles) | Some(Command::Debug { action: DebugAction::Ls }) => debug_ls_cli(),
+        Some(Command::Debug { action: DebugAction::Clean }) => debug_clean_cli(),
+        Some(Command::History { query }) => history_cli(query.as_deref()),
+        Some(Command::ResumeUpload { manifest }) => resume_upload_cli(manifest).await,
+        Some(Command::Compress { file, stdin, file_type, output, json, allow_large, raw, max_ascii_loss, dictionary, mapping_format, stdout, gzip, output_dir, chunk_size }) => {
+            // `--output -` is `--stdout` under another name
+            let (output, stdout) = match output {
+                Some(path) if path.as_os_str() == "-" => (None, true),
+                output => (output, stdout),
+            };
+            // Compressed bytes go to stdout; every other message moves to stderr
+            if stdout {
+                stark_squeeze::output::set_data_on_stdout(true);
+            }
+            // Decided before anything is printed, so the JSON object is all stdout carries
+            if json {
+                stark_squeeze::output::set_json(true);
+            }
+            let options = CompressCliOptions {
+                mapping_format: mapping_format.unwrap_or_default(),
+                allow_large,
+                max_ascii_loss,
+                raw,
+                dictionary,
+                file,
+                gzip,
+                output_dir,
+                chunk_size,
+                output,
+                file_type,
+            };
+            let summary = if stdin {
+                compress_reader_cli(std::io::stdin().lock(), options).await
+            } else {
+                compress_file_cli(options).await
+            };
+            match summary {
+                Some(summary) if json => {
+                    if let Err(e) = stark_squeeze::output::print_json(&summary) {
+                        eprintln!("Failed to encode result: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                Some(_) => {}
+                None => std::process::exit(1),
+            }
+        }
+        Some(Command::CompressDir { dir, recursive, allow_large, raw, output_dir }) => {
+            let options = CompressCliOptions { allow_large, raw, output_dir, ..Default::default() };
+            match compress_dir_cli(&dir, recursive, options).await {
+                Some(summary) if summary.failures.is_empty() => {}
+                _ => std::process::exit(1),
+            }
+        }
+        Some(Command::Verify { compressed, mapping, original }) => {
+            let source = match (compressed, mapping) {
+                (Some(path), _) => VerifySource::Compressed(path),
+                (None, Some(path)) => VerifySource::Mapping(path),
+                (None, None) => unreachable!("clap requires --compressed or --mapping"),
+            };
+            std::process::exit(verify_cli(&source, &original));
+        }
+        Some(Command::Reconstruct { uri, out, cid, raw }) => std::process::exit(reconstruct_uri_cli(&uri, cid, &out, raw).await),
+        Some(Command::Analyze { file }) => std::process::exit(analyze_cli(&file)),
+        Some(Command::Archive { action: ArchiveAction::Create { out, files } }) => std::process::exit(archive_create_cli(&out, &files)),
+        Some(Command::Archive { action: ArchiveAction::Extract { archive, dir } }) => std::process::exit(archive_extract_cli(&archive, &dir)),
+        Some(Command::Config { action: ConfigAction::Init { force } }) => std::process::exit(config_init_cli(force)),
+        Some(Command::MigrateMapping { file }) => std::process::exit(migrate_mapping_cli(&file)),
+        Some(Command::Decompress { file, out }) => std::process::exit(decompress_path_cli(&file, out.as_deref())),
     }
-} 
\ No newline at end of file
+}
@@ -3,10 +3,21 @@ use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 use std::fs;
+use std::str::FromStr;
+use crate::ascii_converter::{convert_to_printable_ascii_parallel, original_bytes, restore_from_reversible};
+use crate::compression::create_chunk_mapping;
+use crate::debug_files::DebugRun;
+use crate::hashing::{hash_with_mode, HashMode};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AsciiConversionInfo {
-    pub conversion_map: HashMap<u8, u8>, // converted -> original
+    /// `(position, original)` for every byte the conversion altered
+    #[serde(default)]
+    pub original_bytes: Vec<(usize, u8)>,
+    /// Converted -> original, as written before 1.2. Several originals can share a
+    /// converted byte, so this is only used for old files lacking `original_bytes`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub conversion_map: HashMap<u8, u8>,
     pub reverse_map: HashMap<u8, u8>,    // original -> converted
     pub stats: ConversionStatsInfo,
     pub was_conversion_needed: bool,
@@ -19,12 +30,204 @@ pub struct ConversionStatsInfo {
     pub conversion_percentage: f64,
 }
 
+/// Mapping format version this build writes. 1.1 added the minimal mapping checksum,
+/// 1.2 positional ASCII reversal; files without a `version` field are 1.0
+pub const MAPPING_VERSION: &str = "1.2";
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MinimalMapping {
     pub chunk_size: usize,
     pub code_to_chunk: std::collections::HashMap<u16, Vec<u8>>,
     pub compressed_data: Vec<u8>,
     pub ascii_conversion: Option<AsciiConversionInfo>, // Only if needed
+    /// Chunks hold the file's raw bytes; no ASCII conversion was applied
+    #[serde(default)]
+    pub raw: bool,
+    /// `compressed_data` holds the file verbatim; compression was skipped
+    #[serde(default)]
+    pub stored: bool,
+    /// CRC32 of `compressed_data`, written on save; absent in files saved before checksums
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<u32>,
+}
+
+impl MinimalMapping {
+    /// CRC32 of `compressed_data`
+    pub fn compute_checksum(&self) -> u32 {
+        crc32fast::hash(&self.compressed_data)
+    }
+
+    /// Checks `compressed_data` against the stored checksum. Older files without one
+    /// are accepted with a warning.
+    pub fn verify_checksum(&self) -> Result<(), MappingError> {
+        match self.checksum {
+            Some(expected) => {
+                let actual = self.compute_checksum();
+                if actual == expected {
+                    Ok(())
+                } else {
+                    Err(MappingError::InvalidMapping(format!(
+                        "Checksum mismatch: expected {:08x}, got {:08x}; the mapping file is corrupted or truncated",
+                        expected, actual
+                    )))
+                }
+            }
+            None => {
+                eprintln!("Warning: mapping has no checksum, skipping integrity verification");
+                Ok(())
+            }
+        }
+    }
+
+    /// Checks that the mapping can be decoded: every code in `compressed_data` has a chunk,
+    /// chunks are `chunk_size` bytes long (only the final one may be shorter), and there is
+    /// data whenever there are chunks.
+    pub fn validate(&self) -> Result<(), MappingError> {
+        if self.stored {
+            return Ok(());
+        }
+        if self.code_to_chunk.is_empty() {
+            return if self.compressed_data.is_empty() {
+                Ok(())
+            } else {
+                Err(MappingError::InvalidMapping("compressed_data is not empty but code_to_chunk is".to_string()))
+            };
+        }
+        if self.compressed_data.is_empty() {
+            return Err(MappingError::InvalidMapping(format!(
+                "compressed_data is empty but code_to_chunk has {} chunks",
+                self.code_to_chunk.len()
+            )));
+        }
+        if self.chunk_size == 0 {
+            return Err(MappingError::InvalidMapping("chunk_size is 0 but code_to_chunk is not empty".to_string()));
+        }
+
+        let mut codes: Vec<&u16> = self.code_to_chunk.keys().collect();
+        codes.sort();
+        for code in codes {
+            let len = self.code_to_chunk[code].len();
+            if len == 0 || len > self.chunk_size {
+                return Err(MappingError::InvalidMapping(format!(
+                    "Chunk for code {} is {} bytes, expected 1..={} (chunk_size)",
+                    code, len, self.chunk_size
+                )));
+            }
+        }
+
+        let last = self.compressed_data.len() - 1;
+        for (position, &code) in self.compressed_data.iter().enumerate() {
+            let chunk = self.code_to_chunk.get(&(code as u16)).ok_or_else(|| {
+                MappingError::InvalidMapping(format!("Code {} at position {} is not in code_to_chunk", code, position))
+            })?;
+            if chunk.len() < self.chunk_size && position != last {
+                return Err(MappingError::InvalidMapping(format!(
+                    "Code {} at position {} maps to a {}-byte chunk; only the final chunk may be shorter than chunk_size {}",
+                    code, position, chunk.len(), self.chunk_size
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which mapping layout gets written alongside a compressed file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MappingFormat {
+    /// Chunk dictionary and codes only
+    #[default]
+    Minimal,
+    /// Minimal mapping plus file metadata, reversal instructions and integrity hashes
+    Complete,
+}
+
+impl fmt::Display for MappingFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MappingFormat::Minimal => write!(f, "minimal"),
+            MappingFormat::Complete => write!(f, "complete"),
+        }
+    }
+}
+
+impl FromStr for MappingFormat {
+    type Err = MappingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minimal" => Ok(MappingFormat::Minimal),
+            "complete" => Ok(MappingFormat::Complete),
+            other => Err(MappingError::InvalidMapping(format!(
+                "Unknown mapping format '{}' (expected minimal or complete)",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileMetadata {
+    pub file_name: String,
+    pub file_extension: String,
+    pub original_size: usize,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntegrityHashes {
+    /// SHA-256 of the original file bytes
+    pub original_sha256: String,
+    /// SHA-256 of `compressed_data`, checked before reconstruction
+    pub compressed_data_sha256: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompleteMapping {
+    pub version: String,
+    pub metadata: FileMetadata,
+    pub chunk_size: usize,
+    pub code_to_chunk: HashMap<u16, Vec<u8>>,
+    pub compressed_data: Vec<u8>,
+    pub ascii_conversion: Option<AsciiConversionInfo>,
+    #[serde(default)]
+    pub raw: bool,
+    #[serde(default)]
+    pub stored: bool,
+    pub reversal_instructions: Vec<String>,
+    pub integrity: IntegrityHashes,
+}
+
+/// Any mapping file the reconstruction path understands
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum MappingFile {
+    Complete(CompleteMapping),
+    Minimal(MinimalMapping),
+}
+
+impl MappingFile {
+    pub fn format(&self) -> MappingFormat {
+        match self {
+            MappingFile::Complete(_) => MappingFormat::Complete,
+            MappingFile::Minimal(_) => MappingFormat::Minimal,
+        }
+    }
+
+    /// Chunk size the data was actually split by
+    pub fn chunk_size(&self) -> usize {
+        match self {
+            MappingFile::Complete(m) => m.chunk_size,
+            MappingFile::Minimal(m) => m.chunk_size,
+        }
+    }
+}
+
+/// Outcome of reconstructing a file from a mapping
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconstructionReport {
+    pub format: MappingFormat,
+    /// Whether the output matched the recorded original hash (complete mappings only)
+    pub original_hash_verified: Option<bool>,
 }
 
 
@@ -66,17 +269,254 @@ impl From<std::io::Error> for MappingError {
     }
 }
 
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(hash_with_mode(data, HashMode::Sha256))
+}
+
+/// Builds a minimal mapping for `data`.
+/// Mapping codes are stored as single bytes, so if `chunk_size` yields more than
+/// 256 unique chunks the mapping falls back to 1-byte chunks.
+/// In `raw` mode the file's bytes are chunked directly, skipping ASCII conversion.
+pub fn create_minimal_mapping(data: &[u8], chunk_size: usize, raw: bool) -> Result<MinimalMapping, MappingError> {
+    let (ascii_data, stats) = if raw {
+        (data.to_vec(), Default::default())
+    } else {
+        convert_to_printable_ascii_parallel(data).map_err(|e| MappingError::ConversionError(e.to_string()))?
+    };
+
+    // Too many unique chunks for u8 codes (or even u16, on high-entropy input) means
+    // falling back to single bytes, which always fit
+    let chunk_mapping = match create_chunk_mapping(&ascii_data, chunk_size) {
+        Ok(mapping) if mapping.chunk_to_code.len() <= 256 => mapping,
+        _ => create_chunk_mapping(&ascii_data, 1).map_err(|e| MappingError::ConversionError(e.to_string()))?,
+    };
+
+    let compressed_data = ascii_data
+        .chunks(chunk_mapping.chunk_size)
+        .map(|chunk| chunk_mapping.chunk_to_code[chunk] as u8)
+        .collect();
+
+    let ascii_conversion = if stats.converted_bytes > 0 {
+        let mut reverse_map = HashMap::new();
+        for (&original, &converted) in data.iter().zip(&ascii_data) {
+            if original != converted {
+                reverse_map.entry(original).or_insert(converted);
+            }
+        }
+        Some(AsciiConversionInfo {
+            original_bytes: original_bytes(data, &stats),
+            conversion_map: HashMap::new(),
+            reverse_map,
+            stats: ConversionStatsInfo {
+                total_bytes: stats.total_bytes,
+                converted_bytes: stats.converted_bytes,
+                conversion_percentage: stats.converted_bytes as f64 / stats.total_bytes as f64 * 100.0,
+            },
+            was_conversion_needed: true,
+        })
+    } else {
+        None
+    };
+
+    Ok(MinimalMapping {
+        chunk_size: chunk_mapping.chunk_size,
+        code_to_chunk: chunk_mapping.code_to_chunk,
+        compressed_data,
+        ascii_conversion,
+        raw,
+        stored: false,
+        checksum: None,
+    })
+}
+
+fn file_metadata(data: &[u8], file_name: &str) -> FileMetadata {
+    let file_extension = std::path::Path::new(file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_string();
+    FileMetadata {
+        file_name: file_name.to_string(),
+        file_extension,
+        original_size: data.len(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+/// Builds a complete mapping for `data`, recording metadata about `file_name`
+pub fn create_complete_mapping(data: &[u8], file_name: &str, chunk_size: usize, raw: bool) -> Result<CompleteMapping, MappingError> {
+    let minimal = create_minimal_mapping(data, chunk_size, raw)?;
+
+    let mut reversal_instructions = vec![
+        "Look up each byte of compressed_data as a code in code_to_chunk".to_string(),
+        if raw {
+            "Concatenate the chunks in order to recover the original bytes".to_string()
+        } else {
+            "Concatenate the chunks in order to recover the ASCII-converted data".to_string()
+        },
+    ];
+    if minimal.ascii_conversion.is_some() {
+        reversal_instructions.push("Put back each (position, byte) pair in ascii_conversion.original_bytes".to_string());
+    }
+    reversal_instructions.push("Compare the SHA-256 of the result with integrity.original_sha256".to_string());
+
+    Ok(CompleteMapping {
+        version: MAPPING_VERSION.to_string(),
+        metadata: file_metadata(data, file_name),
+        chunk_size: minimal.chunk_size,
+        integrity: IntegrityHashes {
+            original_sha256: sha256_hex(data),
+            compressed_data_sha256: sha256_hex(&minimal.compressed_data),
+        },
+        code_to_chunk: minimal.code_to_chunk,
+        compressed_data: minimal.compressed_data,
+        ascii_conversion: minimal.ascii_conversion,
+        raw: minimal.raw,
+        stored: false,
+        reversal_instructions,
+    })
+}
+
+/// Builds a mapping that stores `data` as-is, for files too small to benefit from compression
+pub fn create_stored_mapping(data: &[u8], file_name: &str, format: MappingFormat) -> MappingFile {
+    let minimal = MinimalMapping {
+        chunk_size: 0,
+        code_to_chunk: HashMap::new(),
+        compressed_data: data.to_vec(),
+        ascii_conversion: None,
+        raw: true,
+        stored: true,
+        checksum: None,
+    };
+    match format {
+        MappingFormat::Minimal => MappingFile::Minimal(minimal),
+        MappingFormat::Complete => MappingFile::Complete(CompleteMapping {
+            version: MAPPING_VERSION.to_string(),
+            metadata: file_metadata(data, file_name),
+            chunk_size: 0,
+            integrity: IntegrityHashes {
+                original_sha256: sha256_hex(data),
+                compressed_data_sha256: sha256_hex(data),
+            },
+            code_to_chunk: minimal.code_to_chunk,
+            compressed_data: minimal.compressed_data,
+            ascii_conversion: None,
+            raw: true,
+            stored: true,
+            reversal_instructions: vec![
+                "compressed_data holds the original bytes unchanged".to_string(),
+                "Compare the SHA-256 of the result with integrity.original_sha256".to_string(),
+            ],
+        }),
+    }
+}
+
+/// Builds a mapping for `data` in the requested format
+pub fn create_mapping(
+    data: &[u8],
+    file_name: &str,
+    chunk_size: usize,
+    format: MappingFormat,
+    raw: bool,
+) -> Result<MappingFile, MappingError> {
+    match format {
+        MappingFormat::Minimal => Ok(MappingFile::Minimal(create_minimal_mapping(data, chunk_size, raw)?)),
+        MappingFormat::Complete => Ok(MappingFile::Complete(create_complete_mapping(data, file_name, chunk_size, raw)?)),
+    }
+}
 
+/// Saves a mapping of either format to a JSON file
+pub fn save_mapping(mapping: &MappingFile, file_path: impl AsRef<std::path::Path>) -> Result<(), MappingError> {
+    let json_content = match mapping {
+        MappingFile::Minimal(minimal) => minimal_mapping_json(minimal)?,
+        MappingFile::Complete(_) => serde_json::to_string_pretty(mapping)?,
+    };
+    fs::write(file_path, json_content)?;
+    Ok(())
+}
 
+/// Serializes a minimal mapping with its format version and a freshly computed checksum
+fn minimal_mapping_json(mapping: &MinimalMapping) -> Result<String, MappingError> {
+    let mut value = serde_json::to_value(mapping)?;
+    value["version"] = serde_json::Value::from(MAPPING_VERSION);
+    value["checksum"] = serde_json::Value::from(mapping.compute_checksum());
+    Ok(serde_json::to_string_pretty(&value)?)
+}
 
+/// Saves a complete mapping to a JSON file
+pub fn save_complete_mapping(mapping: &CompleteMapping, file_path: &str) -> Result<(), MappingError> {
+    let json_content = serde_json::to_string_pretty(mapping)?;
+    fs::write(file_path, json_content)?;
+    Ok(())
+}
 
+/// Loads a mapping file in either the minimal or complete format
+pub fn load_mapping(file_path: &str) -> Result<MappingFile, MappingError> {
+    let mapping_content = fs::read_to_string(file_path)?;
+    mapping_from_value(serde_json::from_str(&mapping_content)?)
+}
 
+/// Parses a mapping of either format, verifying a minimal mapping's checksum
+fn mapping_from_value(value: serde_json::Value) -> Result<MappingFile, MappingError> {
+    // Only complete mappings carry integrity hashes; a complete mapping would otherwise
+    // also parse as a minimal one
+    if value.get("integrity").is_some() {
+        Ok(MappingFile::Complete(serde_json::from_value(value)?))
+    } else {
+        let minimal: MinimalMapping = serde_json::from_value(value)?;
+        minimal.verify_checksum()?;
+        Ok(MappingFile::Minimal(minimal))
+    }
+}
 
+/// Upgrades the mapping file at `path` to `MAPPING_VERSION` in place, keeping the
+/// original as `<path>.bak`. Current files are left untouched, and versions this build
+/// does not know are refused rather than rewritten.
+pub fn migrate_mapping(path: &str) -> Result<(), MappingError> {
+    let original = fs::read_to_string(path)?;
+    let mut value: serde_json::Value = serde_json::from_str(&original)?;
+    if !value.is_object() {
+        return Err(MappingError::InvalidMapping("Mapping file is not a JSON object".to_string()));
+    }
+    let version = match value.get("version") {
+        None => "1.0".to_string(),
+        Some(serde_json::Value::String(version)) => version.clone(),
+        Some(other) => return Err(MappingError::InvalidMapping(format!("Mapping version {} is not a string", other))),
+    };
+    match version.as_str() {
+        MAPPING_VERSION => return Ok(()),
+        "1.0" => migrate_from_v1_0(&mut value)?,
+        // The converted bytes' positions were never recorded, so the value map stays
+        "1.1" => value["version"] = serde_json::Value::from(MAPPING_VERSION),
+        other => {
+            return Err(MappingError::InvalidMapping(format!(
+                "Unsupported mapping version {}; this build understands up to {}",
+                other, MAPPING_VERSION
+            )))
+        }
+    }
+    // Make sure the result loads before replacing anything
+    let migrated = serde_json::to_string_pretty(&value)?;
+    mapping_from_value(value)?;
+    fs::write(format!("{}.bak", path), original)?;
+    fs::write(path, migrated)?;
+    Ok(())
+}
 
+/// 1.0 → 1.1: minimal mappings gain the checksum of their compressed data
+fn migrate_from_v1_0(value: &mut serde_json::Value) -> Result<(), MappingError> {
+    if value.get("integrity").is_none() {
+        let minimal: MinimalMapping = serde_json::from_value(value.clone())?;
+        minimal.verify_checksum()?;
+        value["checksum"] = serde_json::Value::from(minimal.compute_checksum());
+    }
+    value["version"] = serde_json::Value::from(MAPPING_VERSION);
+    Ok(())
+}
 
 /// Saves a minimal mapping to a JSON file
 pub fn save_minimal_mapping(mapping: &MinimalMapping, file_path: &str) -> Result<(), MappingError> {
-    let json_content = serde_json::to_string_pretty(mapping)?;
+    let json_content = minimal_mapping_json(mapping)?;
     fs::write(file_path, json_content)?;
     Ok(())
 }
@@ -85,6 +525,7 @@ pub fn save_minimal_mapping(mapping: &MinimalMapping, file_path: &str) -> Result
 pub fn load_minimal_mapping(file_path: &str) -> Result<MinimalMapping, MappingError> {
     let mapping_content = fs::read_to_string(file_path)?;
     let mapping: MinimalMapping = serde_json::from_str(&mapping_content)?;
+    mapping.verify_checksum()?;
     Ok(mapping)
 }
 
@@ -95,7 +536,83 @@ pub fn reconstruct_from_minimal_mapping(
 ) -> Result<(), MappingError> {
     // Load the minimal mapping
     let mapping = load_minimal_mapping(mapping_file_path)?;
-    
+    let original_bytes = decode_minimal_mapping(&mapping)?;
+
+    // Write the reconstructed file
+    fs::write(output_file_path, original_bytes)?;
+
+    Ok(())
+}
+
+/// Reconstructs the original file from a minimal or complete mapping.
+/// Complete mappings have their compressed data checked against the recorded hash first.
+pub fn reconstruct_from_mapping(
+    mapping_file_path: &str,
+    output_file_path: &str,
+) -> Result<ReconstructionReport, MappingError> {
+    let mapping = load_mapping(mapping_file_path)?;
+    let format = mapping.format();
+    let (original_bytes, original_hash_verified) = decode_mapping_file(mapping)?;
+
+    fs::write(output_file_path, original_bytes)?;
+
+    Ok(ReconstructionReport { format, original_hash_verified })
+}
+
+/// Recovers the bytes a mapping of either format describes, in memory. The flag is
+/// whether they match the original hash, for complete mappings only
+pub fn decode_mapping_file(mapping: MappingFile) -> Result<(Vec<u8>, Option<bool>), MappingError> {
+    match mapping {
+        MappingFile::Minimal(minimal) => Ok((decode_minimal_mapping(&minimal)?, None)),
+        MappingFile::Complete(complete) => {
+            let (bytes, verified) = decode_complete_mapping(complete)?;
+            Ok((bytes, Some(verified)))
+        }
+    }
+}
+
+/// Recovers the original bytes from a complete mapping, failing unless they match
+/// `integrity.original_sha256`
+pub fn reverse_compression(mapping: CompleteMapping) -> Result<Vec<u8>, MappingError> {
+    let (bytes, verified) = decode_complete_mapping(mapping)?;
+    if !verified {
+        return Err(MappingError::InvalidMapping(
+            "Reconstructed data does not match the original hash".to_string(),
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Decodes a complete mapping after checking its compressed data hash, returning the
+/// bytes and whether they match the recorded original hash
+fn decode_complete_mapping(complete: CompleteMapping) -> Result<(Vec<u8>, bool), MappingError> {
+    if sha256_hex(&complete.compressed_data) != complete.integrity.compressed_data_sha256 {
+        return Err(MappingError::InvalidMapping(
+            "Compressed data does not match its integrity hash".to_string(),
+        ));
+    }
+    let original_sha256 = complete.integrity.original_sha256;
+    let minimal = MinimalMapping {
+        chunk_size: complete.chunk_size,
+        code_to_chunk: complete.code_to_chunk,
+        compressed_data: complete.compressed_data,
+        ascii_conversion: complete.ascii_conversion,
+        raw: complete.raw,
+        stored: complete.stored,
+        checksum: None,
+    };
+    let bytes = decode_minimal_mapping(&minimal)?;
+    let verified = sha256_hex(&bytes) == original_sha256;
+    Ok((bytes, verified))
+}
+
+/// Decodes the chunk codes of a minimal mapping back into the original bytes
+pub(crate) fn decode_minimal_mapping(mapping: &MinimalMapping) -> Result<Vec<u8>, MappingError> {
+    mapping.validate()?;
+    if mapping.stored {
+        return Ok(mapping.compressed_data.clone());
+    }
+
     // Step 1: Decompress using chunk mapping to get binary string
     let mut binary_string = String::new();
     for &byte in &mapping.compressed_data {
@@ -105,7 +622,8 @@ pub fn reconstruct_from_minimal_mapping(
         // Convert chunk bytes back to binary string (8-bit representation)
         binary_string.push_str(&vec_u8_to_bin_string(chunk));
     }
-    fs::write("debug_reconstructed_binary_string.txt", &binary_string).expect("Failed to write debug_reconstructed_binary_string.txt");
+    let debug_run = DebugRun::start();
+    debug_run.write("debug_reconstructed_binary_string.txt", &binary_string);
     
     // Step 2: Convert binary string back to ASCII bytes
     let mut ascii_bytes = Vec::new();
@@ -120,22 +638,24 @@ pub fn reconstruct_from_minimal_mapping(
             ascii_bytes.push(byte);
         }
     }
-    fs::write("debug_reconstructed_ascii.bin", &ascii_bytes).expect("Failed to write debug_reconstructed_ascii.bin");
+    debug_run.write("debug_reconstructed_ascii.bin", &ascii_bytes);
     
-    // Step 3: Reverse ASCII conversion if needed
-    let mut original_bytes = ascii_bytes;
-    if let Some(ascii_info) = &mapping.ascii_conversion {
-        for byte in &mut original_bytes {
-            if let Some(&original_byte) = ascii_info.conversion_map.get(byte) {
-                *byte = original_byte;
+    // Step 3: Reverse ASCII conversion if needed (raw mappings were never converted)
+    match (mapping.raw, &mapping.ascii_conversion) {
+        // Pre-1.2 mappings only have the ambiguous value map
+        (false, Some(ascii_info)) if ascii_info.original_bytes.is_empty() => {
+            let mut original_bytes = ascii_bytes;
+            for byte in &mut original_bytes {
+                if let Some(&original_byte) = ascii_info.conversion_map.get(byte) {
+                    *byte = original_byte;
+                }
             }
+            Ok(original_bytes)
         }
+        (false, Some(ascii_info)) => restore_from_reversible(&ascii_bytes, &ascii_info.original_bytes)
+            .map_err(|e| MappingError::InvalidMapping(e.to_string())),
+        _ => Ok(ascii_bytes),
     }
-    
-    // Write the reconstructed file
-    fs::write(output_file_path, original_bytes)?;
-    
-    Ok(())
 }
 
 /// Shows information about a minimal mapping file
@@ -148,6 +668,8 @@ pub fn analyze_minimal_mapping(mapping_file_path: &str) -> Result<(), MappingErr
     println!("  • Chunk size: {}", mapping.chunk_size);
     println!("  • Number of unique chunks: {}", mapping.code_to_chunk.len());
     println!("  • Compressed data size: {} bytes", mapping.compressed_data.len());
+    println!("  • Raw mode (no ASCII conversion): {}", mapping.raw);
+    println!("  • Stored uncompressed: {}", mapping.stored);
     println!("  • ASCII conversion needed: {}", mapping.ascii_conversion.is_some());
     
     if let Some(ascii_info) = &mapping.ascii_conversion {
@@ -175,6 +697,246 @@ pub fn analyze_minimal_mapping(mapping_file_path: &str) -> Result<(), MappingErr
     Ok(())
 }
 
-fn vec_u8_to_bin_string(chunk: &Vec<u8>) -> String {
+fn vec_u8_to_bin_string(chunk: &[u8]) -> String {
     chunk.iter().map(|b| format!("{:08b}", b)).collect::<Vec<_>>().join("")
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &[u8] = b"The quick brown fox jumps over the lazy dog. The quick brown fox jumps again.";
+
+    fn round_trip(format: MappingFormat) -> (Vec<u8>, ReconstructionReport) {
+        let dir = tempfile::tempdir().unwrap();
+        let mapping_path = dir.path().join("sample.txt.map");
+        let output_path = dir.path().join("sample.txt");
+
+        let mapping = create_mapping(SAMPLE, "sample.txt", 4, format, false).unwrap();
+        save_mapping(&mapping, mapping_path.to_str().unwrap()).unwrap();
+        let report = reconstruct_from_mapping(mapping_path.to_str().unwrap(), output_path.to_str().unwrap()).unwrap();
+        (fs::read(&output_path).unwrap(), report)
+    }
+
+    #[test]
+    fn test_minimal_mapping_round_trip() {
+        let (output, report) = round_trip(MappingFormat::Minimal);
+        assert_eq!(output, SAMPLE);
+        assert_eq!(report.format, MappingFormat::Minimal);
+        assert_eq!(report.original_hash_verified, None);
+    }
+
+    #[test]
+    fn test_minimal_mapping_checksum_detects_flipped_byte() {
+        let dir = tempfile::tempdir().unwrap();
+        let mapping_path = dir.path().join("sample.txt.map");
+        let mapping_path = mapping_path.to_str().unwrap();
+        let mapping = create_minimal_mapping(SAMPLE, 4, false).unwrap();
+        save_minimal_mapping(&mapping, mapping_path).unwrap();
+        assert_eq!(load_minimal_mapping(mapping_path).unwrap().checksum, Some(mapping.compute_checksum()));
+
+        let mut value: serde_json::Value = serde_json::from_str(&fs::read_to_string(mapping_path).unwrap()).unwrap();
+        let first = value["compressed_data"][0].as_u64().unwrap();
+        value["compressed_data"][0] = serde_json::Value::from(first ^ 1);
+        fs::write(mapping_path, value.to_string()).unwrap();
+        match load_minimal_mapping(mapping_path) {
+            Err(MappingError::InvalidMapping(msg)) => assert!(msg.contains("Checksum mismatch"), "{}", msg),
+            other => panic!("expected a checksum failure, got {:?}", other),
+        }
+        assert!(matches!(load_mapping(mapping_path), Err(MappingError::InvalidMapping(_))));
+
+        // Files written before checksums existed still load
+        value.as_object_mut().unwrap().remove("checksum");
+        value["compressed_data"][0] = serde_json::Value::from(first);
+        fs::write(mapping_path, value.to_string()).unwrap();
+        assert_eq!(load_minimal_mapping(mapping_path).unwrap().checksum, None);
+    }
+
+    #[test]
+    fn test_complete_mapping_round_trip() {
+        let (output, report) = round_trip(MappingFormat::Complete);
+        assert_eq!(output, SAMPLE);
+        assert_eq!(report.format, MappingFormat::Complete);
+        assert_eq!(report.original_hash_verified, Some(true));
+    }
+
+    #[test]
+    fn test_complete_mapping_detects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let mapping_path = dir.path().join("sample.txt.map");
+        let mut mapping = create_complete_mapping(SAMPLE, "sample.txt", 4, false).unwrap();
+        assert_eq!(mapping.metadata.original_size, SAMPLE.len());
+        assert_eq!(mapping.metadata.file_extension, "txt");
+        mapping.compressed_data.swap(0, 1);
+        save_complete_mapping(&mapping, mapping_path.to_str().unwrap()).unwrap();
+
+        let output_path = dir.path().join("out.txt");
+        let result = reconstruct_from_mapping(mapping_path.to_str().unwrap(), output_path.to_str().unwrap());
+        assert!(matches!(result, Err(MappingError::InvalidMapping(_))));
+    }
+
+    #[test]
+    fn test_mapping_demo_round_trip() {
+        // Same vector as examples/mapping_demo.rs
+        let data = b"StarkSqueeze maps repeated chunks to short codes. StarkSqueeze maps them back.";
+        let dir = tempfile::tempdir().unwrap();
+        let mapping_path = dir.path().join("demo.txt.map");
+
+        let mapping = create_complete_mapping(data, "demo.txt", 4, false).unwrap();
+        save_mapping(&MappingFile::Complete(mapping), &mapping_path).unwrap();
+        let loaded = match load_mapping(mapping_path.to_str().unwrap()).unwrap() {
+            MappingFile::Complete(mapping) => mapping,
+            MappingFile::Minimal(_) => panic!("expected a complete mapping"),
+        };
+        assert_eq!(reverse_compression(loaded).unwrap(), data);
+
+        let mut tampered = create_complete_mapping(data, "demo.txt", 4, false).unwrap();
+        tampered.integrity.original_sha256 = sha256_hex(b"something else");
+        assert!(matches!(reverse_compression(tampered), Err(MappingError::InvalidMapping(_))));
+    }
+
+    #[test]
+    fn test_raw_mode_round_trips_binary_data() {
+        let dir = tempfile::tempdir().unwrap();
+        // Every byte value, including control and extended bytes the ASCII pipeline would alter
+        let data: Vec<u8> = (0..4096u32).map(|i| (i.wrapping_mul(2654435761) >> 11) as u8).collect();
+
+        for format in [MappingFormat::Minimal, MappingFormat::Complete] {
+            let mapping_path = dir.path().join(format!("blob.{}.map", format));
+            let output_path = dir.path().join(format!("blob.{}.bin", format));
+            let mapping = create_mapping(&data, "blob.bin", 4, format, true).unwrap();
+            save_mapping(&mapping, mapping_path.to_str().unwrap()).unwrap();
+
+            let report = reconstruct_from_mapping(mapping_path.to_str().unwrap(), output_path.to_str().unwrap()).unwrap();
+            assert_eq!(fs::read(&output_path).unwrap(), data);
+            if format == MappingFormat::Complete {
+                assert_eq!(report.original_hash_verified, Some(true));
+            }
+        }
+
+        let minimal = create_minimal_mapping(&data, 4, true).unwrap();
+        assert!(minimal.raw);
+        assert!(minimal.ascii_conversion.is_none());
+    }
+
+    #[test]
+    fn test_repeated_control_characters_reconstruct_exactly() {
+        let dir = tempfile::tempdir().unwrap();
+        // Every control byte several times over: many convert to the same printable byte
+        let data: Vec<u8> = (0..4).flat_map(|_| (0..32u8).chain(*b"text\r\n\t\0\x7f\x1b")).collect();
+        let mapping = create_minimal_mapping(&data, 4, false).unwrap();
+        let info = mapping.ascii_conversion.as_ref().unwrap();
+        assert_eq!(info.original_bytes.len(), info.stats.converted_bytes);
+
+        let mapping_path = dir.path().join("controls.map");
+        let output_path = dir.path().join("controls.bin");
+        save_minimal_mapping(&mapping, mapping_path.to_str().unwrap()).unwrap();
+        reconstruct_from_minimal_mapping(mapping_path.to_str().unwrap(), output_path.to_str().unwrap()).unwrap();
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+    }
+
+    #[test]
+    fn test_migrate_v1_0_minimal_mapping() {
+        let dir = tempfile::tempdir().unwrap();
+        let mapping_path = dir.path().join("sample.txt.map");
+        let mapping_path = mapping_path.to_str().unwrap();
+        // A 1.0 file: no version and no checksum
+        let legacy = serde_json::to_string(&create_minimal_mapping(SAMPLE, 4, false).unwrap()).unwrap();
+        assert!(!legacy.contains("\"version\"") && !legacy.contains("\"checksum\""));
+        fs::write(mapping_path, &legacy).unwrap();
+
+        migrate_mapping(mapping_path).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&fs::read_to_string(mapping_path).unwrap()).unwrap();
+        assert_eq!(value["version"], MAPPING_VERSION);
+        assert!(value["checksum"].is_u64());
+        assert_eq!(fs::read_to_string(format!("{}.bak", mapping_path)).unwrap(), legacy);
+        let output_path = dir.path().join("sample.txt");
+        reconstruct_from_mapping(mapping_path, output_path.to_str().unwrap()).unwrap();
+        assert_eq!(fs::read(&output_path).unwrap(), SAMPLE);
+
+        // Already current: nothing to do
+        migrate_mapping(mapping_path).unwrap();
+
+        let mut future = value.clone();
+        future["version"] = serde_json::Value::from("9.0");
+        fs::write(mapping_path, future.to_string()).unwrap();
+        assert!(matches!(migrate_mapping(mapping_path), Err(MappingError::InvalidMapping(_))));
+        assert_eq!(fs::read_to_string(mapping_path).unwrap(), future.to_string());
+    }
+
+    fn mapping(chunk_size: usize, chunks: &[(u16, &[u8])], compressed_data: &[u8]) -> MinimalMapping {
+        MinimalMapping {
+            chunk_size,
+            code_to_chunk: chunks.iter().map(|&(code, chunk)| (code, chunk.to_vec())).collect(),
+            compressed_data: compressed_data.to_vec(),
+            ascii_conversion: None,
+            raw: true,
+            stored: false,
+            checksum: None,
+        }
+    }
+
+    fn validation_error(mapping: &MinimalMapping) -> String {
+        match mapping.validate() {
+            Err(MappingError::InvalidMapping(msg)) => msg,
+            other => panic!("expected InvalidMapping, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_well_formed_mappings() {
+        let data = b"abcabcab".to_vec();
+        assert!(create_minimal_mapping(&data, 3, true).unwrap().validate().is_ok());
+        assert!(mapping(2, &[(0, b"ab"), (1, b"c")], &[0, 0, 1]).validate().is_ok());
+        assert!(mapping(2, &[], &[]).validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_missing_code() {
+        let msg = validation_error(&mapping(2, &[(0, b"ab")], &[0, 5]));
+        assert!(msg.contains("Code 5 at position 1"), "{}", msg);
+    }
+
+    #[test]
+    fn test_validate_rejects_short_chunk_before_the_end() {
+        let msg = validation_error(&mapping(2, &[(0, b"ab"), (1, b"c")], &[1, 0]));
+        assert!(msg.contains("Code 1 at position 0"), "{}", msg);
+    }
+
+    #[test]
+    fn test_validate_rejects_oversized_and_empty_chunks() {
+        let msg = validation_error(&mapping(2, &[(0, b"ab"), (1, b"abc")], &[0, 1]));
+        assert!(msg.contains("code 1 is 3 bytes"), "{}", msg);
+        let msg = validation_error(&mapping(2, &[(0, b"")], &[0]));
+        assert!(msg.contains("code 0 is 0 bytes"), "{}", msg);
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_data_and_zero_chunk_size() {
+        let msg = validation_error(&mapping(2, &[(0, b"ab")], &[]));
+        assert!(msg.contains("compressed_data is empty"), "{}", msg);
+        let msg = validation_error(&mapping(0, &[(0, b"ab")], &[0]));
+        assert!(msg.contains("chunk_size is 0"), "{}", msg);
+        let msg = validation_error(&mapping(2, &[], &[0]));
+        assert!(msg.contains("code_to_chunk is"), "{}", msg);
+    }
+
+    #[test]
+    fn test_reconstruction_validates_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let mapping_path = dir.path().join("bad.map");
+        save_minimal_mapping(&mapping(2, &[(0, b"ab")], &[0, 9]), mapping_path.to_str().unwrap()).unwrap();
+        let result = reconstruct_from_minimal_mapping(
+            mapping_path.to_str().unwrap(),
+            dir.path().join("out").to_str().unwrap(),
+        );
+        assert!(matches!(result, Err(MappingError::InvalidMapping(msg)) if msg.contains("Code 9")));
+    }
+
+    #[test]
+    fn test_mapping_format_parsing() {
+        assert_eq!("minimal".parse::<MappingFormat>().unwrap(), MappingFormat::Minimal);
+        assert_eq!("complete".parse::<MappingFormat>().unwrap(), MappingFormat::Complete);
+        assert!("full".parse::<MappingFormat>().is_err());
+        assert_eq!(MappingFormat::default(), MappingFormat::Minimal);
+    }
+}
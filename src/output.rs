@@ -0,0 +1,289 @@
+// Output Module
+// Routes CLI status output so it can be silenced with --quiet, expanded with -v/-vv,
+// moved to stderr when stdout carries compressed data, or dropped entirely when
+// stdout carries a single JSON result. Errors are always written to stderr and
+// are not affected.
+
+use std::cell::{Cell, RefCell};
+use std::fmt::Display;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+static DATA_ON_STDOUT: AtomicBool = AtomicBool::new(false);
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Everything written while capturing, split by stream
+#[derive(Debug, Default)]
+pub(crate) struct CapturedOutput {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<String>,
+}
+
+/// Verbosity of `-v`, which adds per-step details
+pub const VERBOSE: u8 = 1;
+/// Verbosity of `-vv`, which adds per-chunk debug output
+pub const DEBUG: u8 = 2;
+
+thread_local! {
+    /// Per-thread override of the quiet flag, so tests don't affect each other
+    static QUIET_OVERRIDE: Cell<Option<bool>> = const { Cell::new(None) };
+    /// Per-thread override of the verbosity
+    static VERBOSITY_OVERRIDE: Cell<Option<u8>> = const { Cell::new(None) };
+    /// Per-thread override of the data-on-stdout flag
+    static DATA_ON_STDOUT_OVERRIDE: Cell<Option<bool>> = const { Cell::new(None) };
+    /// Per-thread override of the JSON flag
+    static JSON_OVERRIDE: Cell<Option<bool>> = const { Cell::new(None) };
+    /// Output captured instead of printed, when capturing is active on this thread
+    static CAPTURED: RefCell<Option<CapturedOutput>> = const { RefCell::new(None) };
+}
+
+/// Enables or disables quiet mode for the whole process
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::SeqCst);
+}
+
+/// Whether informational output is currently suppressed
+pub fn is_quiet() -> bool {
+    QUIET_OVERRIDE.with(|o| o.get()).unwrap_or_else(|| QUIET.load(Ordering::SeqCst))
+}
+
+/// Sets how many `-v` flags were given, for the whole process
+pub fn set_verbosity(level: u8) {
+    VERBOSITY.store(level, Ordering::SeqCst);
+}
+
+/// Current verbosity: 0 normally, `VERBOSE` or `DEBUG` with `-v` or `-vv`
+pub fn verbosity() -> u8 {
+    VERBOSITY_OVERRIDE.with(|o| o.get()).unwrap_or_else(|| VERBOSITY.load(Ordering::SeqCst))
+}
+
+/// Reserves stdout for compressed data, moving all status and result text to stderr
+pub fn set_data_on_stdout(enabled: bool) {
+    DATA_ON_STDOUT.store(enabled, Ordering::SeqCst);
+}
+
+/// Whether stdout is reserved for compressed data
+pub fn data_on_stdout() -> bool {
+    DATA_ON_STDOUT_OVERRIDE
+        .with(|o| o.get())
+        .unwrap_or_else(|| DATA_ON_STDOUT.load(Ordering::SeqCst))
+}
+
+/// Reserves stdout for one JSON document, silencing all status and result text
+pub fn set_json(enabled: bool) {
+    JSON.store(enabled, Ordering::SeqCst);
+}
+
+/// Whether stdout is reserved for a JSON document
+pub fn is_json() -> bool {
+    JSON_OVERRIDE.with(|o| o.get()).unwrap_or_else(|| JSON.load(Ordering::SeqCst))
+}
+
+fn emit(line: String) {
+    emit_to(line, data_on_stdout());
+}
+
+fn emit_to(line: String, to_stderr: bool) {
+    let captured = CAPTURED.with(|c| match c.borrow_mut().as_mut() {
+        Some(captured) => {
+            if to_stderr {
+                captured.stderr.push(line.clone());
+            } else {
+                captured.stdout.extend_from_slice(line.as_bytes());
+                captured.stdout.push(b'\n');
+            }
+            true
+        }
+        None => false,
+    });
+    if captured {
+        return;
+    }
+    if to_stderr {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
+    }
+}
+
+/// Writes compressed bytes to stdout exactly as given: no newline, no text conversion
+pub fn write_data(data: &[u8]) -> std::io::Result<()> {
+    let captured = CAPTURED.with(|c| match c.borrow_mut().as_mut() {
+        Some(captured) => {
+            captured.stdout.extend_from_slice(data);
+            true
+        }
+        None => false,
+    });
+    if captured {
+        return Ok(());
+    }
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(data)?;
+    stdout.flush()
+}
+
+/// Writes `value` as a single line of JSON, the only output of JSON mode
+pub fn print_json(value: &impl serde::Serialize) -> serde_json::Result<()> {
+    emit(serde_json::to_string(value)?);
+    Ok(())
+}
+
+/// Prints an informational line unless quiet or JSON mode is on
+pub fn status_line(line: String) {
+    if !is_quiet() && !is_json() {
+        emit(line);
+    }
+}
+
+/// Prints a line that only shows at `level` verbosity or above, and never in quiet or JSON mode
+pub fn detail_line(level: u8, line: String) {
+    if verbosity() >= level && !is_quiet() && !is_json() {
+        emit(line);
+    }
+}
+
+/// Prints an error to stderr, whatever the quiet, verbosity or JSON settings
+pub fn error_line(line: String) {
+    emit_to(line, true);
+}
+
+/// Prints the outcome of a command: the full message normally, only `terse` in quiet
+/// mode, and nothing in JSON mode
+pub fn result(verbose: impl Display, terse: impl Display) {
+    if is_json() {
+        return;
+    }
+    if is_quiet() {
+        emit(terse.to_string());
+    } else {
+        emit(verbose.to_string());
+    }
+}
+
+/// Hides a progress bar or spinner when quiet or JSON mode is on
+pub fn hide_if_quiet(bar: &indicatif::ProgressBar) {
+    if is_quiet() || is_json() {
+        bar.set_draw_target(indicatif::ProgressDrawTarget::hidden());
+    }
+}
+
+/// `println!` replacement for informational output that respects quiet mode
+macro_rules! status {
+    () => {
+        $crate::output::status_line(String::new())
+    };
+    ($($arg:tt)*) => {
+        $crate::output::status_line(format!($($arg)*))
+    };
+}
+pub(crate) use status;
+
+/// `status!` for lines shown only at the given verbosity, e.g. `detail!(output::DEBUG, ...)`
+macro_rules! detail {
+    ($level:expr, $($arg:tt)*) => {
+        $crate::output::detail_line($level, format!($($arg)*))
+    };
+}
+pub(crate) use detail;
+
+/// Sets quiet mode for the current thread only and starts capturing its output
+#[cfg(test)]
+pub(crate) fn start_capture(quiet: bool) {
+    QUIET_OVERRIDE.with(|o| o.set(Some(quiet)));
+    CAPTURED.with(|c| *c.borrow_mut() = Some(CapturedOutput::default()));
+}
+
+/// Like `start_capture`, at the given verbosity on the current thread
+#[cfg(test)]
+pub(crate) fn start_capture_with_verbosity(level: u8) {
+    start_capture(false);
+    VERBOSITY_OVERRIDE.with(|o| o.set(Some(level)));
+}
+
+/// Like `start_capture`, with stdout reserved for data on the current thread
+#[cfg(test)]
+pub(crate) fn start_capture_with_data_on_stdout() {
+    start_capture(false);
+    DATA_ON_STDOUT_OVERRIDE.with(|o| o.set(Some(true)));
+}
+
+/// Like `start_capture`, with stdout reserved for JSON on the current thread
+#[cfg(test)]
+pub(crate) fn start_capture_with_json() {
+    start_capture(false);
+    JSON_OVERRIDE.with(|o| o.set(Some(true)));
+}
+
+/// Stops capturing and returns both streams
+#[cfg(test)]
+pub(crate) fn finish_capture_streams() -> CapturedOutput {
+    QUIET_OVERRIDE.with(|o| o.set(None));
+    VERBOSITY_OVERRIDE.with(|o| o.set(None));
+    DATA_ON_STDOUT_OVERRIDE.with(|o| o.set(None));
+    JSON_OVERRIDE.with(|o| o.set(None));
+    CAPTURED.with(|c| c.borrow_mut().take().unwrap_or_default())
+}
+
+/// Stops capturing and returns the lines printed to stdout since `start_capture`
+#[cfg(test)]
+pub(crate) fn finish_capture() -> Vec<String> {
+    let stdout = finish_capture_streams().stdout;
+    String::from_utf8_lossy(&stdout).lines().map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_mode_keeps_only_results() {
+        start_capture(true);
+        status!("Chunk size: {}", 8);
+        result("✅ Compression complete! Compressed: a.txt", "a.txt");
+        assert_eq!(finish_capture(), vec!["a.txt".to_string()]);
+
+        start_capture(false);
+        status!("Chunk size: {}", 8);
+        result("✅ Compression complete! Compressed: a.txt", "a.txt");
+        assert_eq!(
+            finish_capture(),
+            vec!["Chunk size: 8".to_string(), "✅ Compression complete! Compressed: a.txt".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_verbosity_levels_gate_detail_lines() {
+        for (level, expected) in [(0, 1), (VERBOSE, 2), (DEBUG, 3)] {
+            start_capture_with_verbosity(level);
+            status!("Chunk size: {}", 8);
+            detail!(VERBOSE, "ASCII loss: 0%");
+            detail!(DEBUG, "Chunk 0: \"abcd\"");
+            error_line("Error: boom".to_string());
+            let captured = finish_capture_streams();
+            assert_eq!(String::from_utf8_lossy(&captured.stdout).lines().count(), expected);
+            assert_eq!(captured.stderr, vec!["Error: boom".to_string()]);
+        }
+
+        start_capture(true);
+        VERBOSITY_OVERRIDE.with(|o| o.set(Some(DEBUG)));
+        detail!(DEBUG, "Chunk 0");
+        error_line("Error: boom".to_string());
+        let captured = finish_capture_streams();
+        assert!(captured.stdout.is_empty());
+        assert_eq!(captured.stderr.len(), 1);
+    }
+
+    #[test]
+    fn test_data_on_stdout_moves_text_to_stderr() {
+        start_capture_with_data_on_stdout();
+        status!("Chunk size: {}", 8);
+        write_data(&[0, 159, 10, 255]).unwrap();
+        result("✅ Compression complete!", "-");
+        let captured = finish_capture_streams();
+        assert_eq!(captured.stdout, vec![0, 159, 10, 255]);
+        assert_eq!(captured.stderr, vec!["Chunk size: 8".to_string(), "✅ Compression complete!".to_string()]);
+    }
+}
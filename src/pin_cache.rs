@@ -0,0 +1,74 @@
+// Pin Cache Module
+// SQLite record of content already pinned to IPFS, keyed by the SHA-256 of the
+// uploaded bytes, so identical uploads reuse the first CID instead of pinning again
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Handle to the `pinned_files` table. The connection sits behind a lock so the
+/// cache can be shared between requests.
+#[derive(Debug)]
+pub struct PinCache {
+    conn: Mutex<Connection>,
+}
+
+impl PinCache {
+    /// Opens the cache at `path`, creating the file and table if needed
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<PinCache> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Cache that lives only as long as the handle
+    pub fn open_in_memory() -> rusqlite::Result<PinCache> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> rusqlite::Result<PinCache> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS pinned_files (
+                sha256 TEXT PRIMARY KEY,
+                cid TEXT NOT NULL,
+                pinned_at TEXT NOT NULL
+            );",
+        )?;
+        Ok(PinCache { conn: Mutex::new(conn) })
+    }
+
+    /// CID pinned for content with this hex SHA-256, if any
+    pub fn get(&self, sha256: &str) -> rusqlite::Result<Option<String>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        conn.query_row("SELECT cid FROM pinned_files WHERE sha256 = ?1", params![sha256], |row| row.get(0))
+            .optional()
+    }
+
+    /// Cheap query confirming the database and table are usable
+    pub fn ping(&self) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        conn.query_row("SELECT 1 FROM pinned_files LIMIT 1", [], |_| Ok(())).optional()?;
+        Ok(())
+    }
+
+    /// Records that content with this hex SHA-256 is pinned as `cid`
+    pub fn insert(&self, sha256: &str, cid: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        conn.execute(
+            "INSERT OR REPLACE INTO pinned_files (sha256, cid, pinned_at) VALUES (?1, ?2, ?3)",
+            params![sha256, cid, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_cid_is_found_by_hash() {
+        let cache = PinCache::open_in_memory().unwrap();
+        assert_eq!(cache.get("abc").unwrap(), None);
+        cache.insert("abc", "bafy-1").unwrap();
+        assert_eq!(cache.get("abc").unwrap().as_deref(), Some("bafy-1"));
+    }
+}
@@ -0,0 +1,73 @@
+// Progress Module
+// One progress abstraction for the long-running generators, reported to a terminal
+// bar by the CLI and to nothing in tests
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Receives progress from a long-running task
+pub trait ProgressReporter {
+    /// Begins a task of `total` steps
+    fn start(&mut self, total: u64);
+    /// Records `n` more completed steps
+    fn inc(&mut self, n: u64);
+    fn set_message(&mut self, msg: &str);
+    /// Ends the task, leaving `msg` as the final state
+    fn finish(&mut self, msg: &str);
+}
+
+/// Reports to an `indicatif` progress bar, hidden in quiet or JSON mode
+#[derive(Debug, Clone)]
+pub struct IndicatifReporter {
+    bar: ProgressBar,
+}
+
+impl IndicatifReporter {
+    /// Bar in the style shared by the generators
+    pub fn new() -> Self {
+        let bar = ProgressBar::new(0);
+        crate::output::hide_if_quiet(&bar);
+        bar.set_style(
+            ProgressStyle::default_bar()
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+        Self { bar }
+    }
+}
+
+impl Default for IndicatifReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressReporter for IndicatifReporter {
+    fn start(&mut self, total: u64) {
+        self.bar.set_length(total);
+        self.bar.set_position(0);
+    }
+
+    fn inc(&mut self, n: u64) {
+        self.bar.inc(n);
+    }
+
+    fn set_message(&mut self, msg: &str) {
+        self.bar.set_message(msg.to_string());
+    }
+
+    fn finish(&mut self, msg: &str) {
+        self.bar.finish_with_message(msg.to_string());
+    }
+}
+
+/// Discards all progress
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopReporter;
+
+impl ProgressReporter for NoopReporter {
+    fn start(&mut self, _total: u64) {}
+    fn inc(&mut self, _n: u64) {}
+    fn set_message(&mut self, _msg: &str) {}
+    fn finish(&mut self, _msg: &str) {}
+}
@@ -0,0 +1,203 @@
+// Self-Test Module
+// Runs a built-in battery of round-trip checks over the core transforms so
+// users and CI can validate a build with a single command
+
+use crate::ascii_converter::convert_to_printable_ascii;
+use crate::benchmark::sample_inputs;
+use crate::compression::{compress_bytes, compress_data, create_chunk_mapping, pack_10bit_values, unpack_10bit_values, CompressOptions};
+use crate::utils::{felt_to_short_string, short_string_to_felt};
+
+/// Result of a single self-test check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl SelfTestCheck {
+    fn new(name: &'static str, result: Result<String, String>) -> Self {
+        match result {
+            Ok(detail) => SelfTestCheck { name, passed: true, detail },
+            Err(detail) => SelfTestCheck { name, passed: false, detail },
+        }
+    }
+}
+
+/// ASCII conversion of all 256 byte values: printable bytes must survive the
+/// convert+reverse round trip and every output byte must be printable
+fn check_ascii_round_trip() -> Result<String, String> {
+    let input: Vec<u8> = (0..=255u8).collect();
+    let (converted, stats) = convert_to_printable_ascii(&input).map_err(|e| e.to_string())?;
+
+    if let Some(pos) = converted.iter().position(|b| !(32..=126).contains(b)) {
+        return Err(format!("byte {} converted to non-printable 0x{:02X}", pos, converted[pos]));
+    }
+
+    // Reverse with the same first-occurrence map the mapping files record
+    let mut reverse = std::collections::HashMap::new();
+    for (&original, &ascii) in input.iter().zip(&converted) {
+        if original != ascii {
+            reverse.entry(ascii).or_insert(original);
+        }
+    }
+    for byte in 32..=126u8 {
+        if converted[byte as usize] != byte {
+            return Err(format!("printable byte 0x{:02X} was modified", byte));
+        }
+    }
+    let restored = converted
+        .iter()
+        .zip(&input)
+        .filter(|&(&ascii, &original)| reverse.get(&ascii).copied().unwrap_or(ascii) == original)
+        .count();
+
+    Ok(format!(
+        "{} bytes converted, {}/256 restored exactly (conversion of non-printable bytes is lossy)",
+        stats.converted_bytes, restored
+    ))
+}
+
+/// Chunk-map compress+decompress of several patterns at every configured chunk size
+fn check_chunk_round_trip() -> Result<String, String> {
+    let mut state = 0x9E3779B9u32;
+    let patterns: Vec<(&str, Vec<u8>)> = vec![
+        ("zeros", vec![0u8; 257]),
+        ("repeating", b"abcabcabcabcabcabcab".to_vec()),
+        ("all bytes", (0..=255u8).collect()),
+        ("pseudo-random", (0..1000).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        }).collect()),
+        ("empty", Vec::new()),
+    ];
+
+    let mut runs = 0;
+    for (name, data) in &patterns {
+        for chunk_size in 1..=8 {
+            let mapping = create_chunk_mapping(data, chunk_size).map_err(|e| format!("{}: {}", name, e))?;
+            let codes = compress_data(data, &mapping).map_err(|e| format!("{}: {}", name, e))?;
+            let restored: Vec<u8> = codes
+                .iter()
+                .flat_map(|code| mapping.code_to_chunk[code].iter().copied())
+                .collect();
+            if &restored != data {
+                return Err(format!("{} pattern failed at chunk size {}", name, chunk_size));
+            }
+            runs += 1;
+        }
+    }
+    Ok(format!("{} pattern/chunk-size combinations", runs))
+}
+
+/// Felt short-string encode+decode on sample strings
+fn check_felt_round_trip() -> Result<String, String> {
+    let samples = ["a", "png", "starksqueeze", "0123456789abcdef", "abcdefghijklmnopqrstuvwxyz01234"];
+    for sample in samples {
+        let felt = short_string_to_felt(sample).map_err(|e| format!("{}: {}", sample, e))?;
+        let decoded = felt_to_short_string(felt).map_err(|e| format!("{}: {}", sample, e))?;
+        if decoded != sample {
+            return Err(format!("'{}' decoded as '{}'", sample, decoded));
+        }
+    }
+    Ok(format!("{} sample strings", samples.len()))
+}
+
+/// 10-bit pack+unpack over the full 10-bit value range
+fn check_10bit_round_trip() -> Result<String, String> {
+    let values: Vec<u16> = (0..1024).collect();
+    let packed = pack_10bit_values(&values).map_err(|e| e.to_string())?;
+    let unpacked = unpack_10bit_values(&packed, values.len()).map_err(|e| e.to_string())?;
+    if unpacked != values {
+        return Err(format!("{} values in, {} values out", values.len(), unpacked.len()));
+    }
+    Ok(format!("{} values", values.len()))
+}
+
+/// Runs every self-test check
+pub fn run_selftest() -> Vec<SelfTestCheck> {
+    vec![
+        SelfTestCheck::new("ascii convert/reverse", check_ascii_round_trip()),
+        SelfTestCheck::new("chunk map compress/decompress", check_chunk_round_trip()),
+        SelfTestCheck::new("felt encode/decode", check_felt_round_trip()),
+        SelfTestCheck::new("10-bit pack/unpack", check_10bit_round_trip()),
+    ]
+}
+
+/// Exit code for a set of checks: 0 when all passed, 1 otherwise
+pub fn exit_code(checks: &[SelfTestCheck]) -> i32 {
+    if checks.iter().all(|c| c.passed) { 0 } else { 1 }
+}
+
+/// Size of each generated input in the ratio self-check corpus
+const RATIO_CORPUS_SIZE: usize = 64 * 1024;
+
+/// Compression ratio measured on the built-in corpus against the configured one,
+/// both as the percentage of bytes saved
+#[derive(Debug, Clone, PartialEq)]
+pub struct RatioCheck {
+    pub configured: f64,
+    pub measured: f64,
+    pub tolerance: f64,
+}
+
+impl RatioCheck {
+    /// Warning to print when the measured ratio is further than `tolerance` from the configured one
+    pub fn warning(&self) -> Option<String> {
+        let deviation = (self.measured - self.configured).abs();
+        (deviation > self.tolerance).then(|| {
+            format!(
+                "measured compression ratio {:.1}% is {:.1} points from the configured {:.1}% (tolerance {:.1}); update compression.current_compression_ratio",
+                self.measured, deviation, self.configured, self.tolerance
+            )
+        })
+    }
+}
+
+/// Compresses the built-in corpus with the configured codec and compares the bytes
+/// saved with `configured`
+pub fn check_compression_ratio(configured: f64, tolerance: f64) -> Result<RatioCheck, String> {
+    let (mut original, mut compressed) = (0u64, 0u64);
+    for input in sample_inputs(RATIO_CORPUS_SIZE) {
+        let outcome = compress_bytes(&input.data, CompressOptions::default()).map_err(|e| format!("{}: {}", input.name, e))?;
+        original += outcome.original_size;
+        compressed += outcome.compressed_size;
+    }
+    let measured = (1.0 - compressed as f64 / original.max(1) as f64) * 100.0;
+    Ok(RatioCheck { configured, measured, tolerance })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selftest_passes_on_correct_build() {
+        let checks = run_selftest();
+        for check in &checks {
+            assert!(check.passed, "{} failed: {}", check.name, check.detail);
+        }
+        assert_eq!(exit_code(&checks), 0);
+    }
+
+    #[test]
+    fn test_failed_check_sets_exit_code() {
+        let checks = vec![
+            SelfTestCheck::new("ok", Ok(String::new())),
+            SelfTestCheck::new("broken", Err("mismatch".to_string())),
+        ];
+        assert_eq!(exit_code(&checks), 1);
+    }
+
+    #[test]
+    fn test_ratio_check_warns_on_absurd_configured_ratio() {
+        let check = check_compression_ratio(1000.0, 10.0).unwrap();
+        assert!(check.measured <= 100.0);
+        assert!(check.warning().unwrap().contains("1000.0%"));
+
+        let honest = RatioCheck { configured: check.measured, ..check };
+        assert!(honest.warning().is_none());
+    }
+}
@@ -1,24 +1,36 @@
 use axum::{
-    extract::{Multipart, State},
-    http::{StatusCode, HeaderMap, Method},
-    response::{Json, IntoResponse},
-    routing::{post, get},
+    body::Body,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, Multipart, Query, Request, State,
+    },
+    http::{header, StatusCode, HeaderMap, HeaderValue, Method},
+    middleware::{self, Next},
+    response::{Json, IntoResponse, Response},
+    routing::{delete, post, get},
     Router,
 };
-use tower_http::cors::{CorsLayer, Any};
+use tower_http::cors::{AllowOrigin, CorsLayer, Any};
 use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use std::fs;
 use tracing::{info, error, warn};
-use sha2::{Sha256, Digest};
 use anyhow::Result;
 
 use stark_squeeze::{
-    ascii_converter::convert_to_printable_ascii,
-    compression::compress_file,
-    starknet_client::upload_data,
-    ipfs_client::pin_file_to_ipfs,
+    compression::{compress_file, compress_file_with_codec, decompress_file, Codec, CompressionError},
+    starknet_client::{encode_upload_metadata, upload_data},
+    ipfs_client::{gateway_url, IpfsError, ALREADY_UNPINNED},
+    storage::{PinataStorage, Storage},
+    config::get_config,
+    dictionary::load_dictionary,
+    hashing::{hash_data, hash_with_mode, HashMode},
+    pin_cache::PinCache,
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -39,6 +51,56 @@ pub struct CompressionResponse {
     pub mapping_file: Option<String>,
     pub upload_timestamp: Option<i64>,
     pub file_type: Option<String>,
+    /// False when compression would have expanded the data and the original was pinned instead
+    #[serde(default)]
+    pub compression_beneficial: Option<bool>,
+}
+
+impl CompressionResponse {
+    /// Error body shared by the file endpoints
+    fn failure(error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            file_url: None,
+            ipfs_cid: None,
+            compression_ratio: None,
+            original_size: None,
+            compressed_size: None,
+            error: Some(error.into()),
+            mapping_file: None,
+            upload_timestamp: None,
+            file_type: None,
+            compression_beneficial: None,
+        }
+    }
+}
+
+/// Progress of one stage of a compression, streamed over `/ws/compress`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompressionProgress {
+    /// `upload`, `compression` or `ipfs_pinning`
+    pub stage: String,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+impl CompressionProgress {
+    fn new(stage: &str, bytes_done: u64, bytes_total: u64) -> Self {
+        Self { stage: stage.to_string(), bytes_done, bytes_total }
+    }
+}
+
+/// Receives progress events as a compression runs
+pub type ProgressFn = Arc<dyn Fn(CompressionProgress) + Send + Sync>;
+
+/// First message of a `/ws/compress` session, announcing the file whose bytes follow
+/// as binary messages
+#[derive(Debug, Deserialize)]
+pub struct SocketUploadStart {
+    pub file_name: String,
+    pub size: u64,
+    #[serde(default)]
+    pub owner: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -50,12 +112,141 @@ pub struct ServerStatus {
     pub total_files_processed: usize,
 }
 
+/// A file processed by the `/compress` endpoint, as returned by `/files`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileRecord {
+    pub id: String,
+    pub file_name: String,
+    pub file_type: Option<String>,
+    pub original_size: Option<usize>,
+    pub compressed_size: Option<usize>,
+    pub compression_ratio: Option<f64>,
+    pub ipfs_cid: Option<String>,
+    pub file_url: Option<String>,
+    pub upload_timestamp: Option<i64>,
+    /// Who uploaded the file, as given by `upload_owner`; kept out of listings
+    #[serde(skip)]
+    pub owner: Option<String>,
+}
+
+impl FileRecord {
+    fn from_response(file_name: &str, response: &CompressionResponse, owner: &str) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            file_name: file_name.to_string(),
+            file_type: response.file_type.clone(),
+            original_size: response.original_size,
+            compressed_size: response.compressed_size,
+            compression_ratio: response.compression_ratio,
+            ipfs_cid: response.ipfs_cid.clone(),
+            file_url: response.file_url.clone(),
+            upload_timestamp: response.upload_timestamp,
+            owner: Some(owner.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListFilesQuery {
+    /// Stream rows as newline-delimited JSON instead of a single array
+    #[serde(default)]
+    pub stream: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DeleteFileQuery {
+    /// The `owner` form field the file was uploaded with, for callers without an API key
+    pub owner: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct AppState {
     pub dictionary_loaded: bool,
     pub dictionary_path: Option<String>,
     pub total_files_processed: usize,
+    /// Original bytes of every file compressed so far
+    pub bytes_in_total: u64,
+    /// Bytes pinned for every file compressed so far
+    pub bytes_out_total: u64,
     pub start_time: std::time::Instant,
+    pub files: Vec<FileRecord>,
+    /// Key required in `X-API-Key` by the file endpoints; open access when unset
+    pub api_key: Option<String>,
+    /// Uploads each owner may make per UTC day; unlimited when unset
+    pub max_uploads_per_day: Option<u32>,
+    pub upload_quota: UploadQuota,
+    /// CIDs already pinned, by SHA-256 of the upload; every upload is pinned when unset
+    pub pin_cache: Option<Arc<PinCache>>,
+    /// Per-IP request limit; unlimited when unset
+    pub rate_limiter: Option<RateLimiter>,
+    /// Origins browsers may call the API from; any origin when unset
+    pub cors_origins: Option<Vec<HeaderValue>>,
+}
+
+/// Uploads accepted per owner on the current UTC day
+#[derive(Debug, Default)]
+pub struct UploadQuota {
+    day: Option<chrono::NaiveDate>,
+    uploads_today: std::collections::HashMap<String, u32>,
+}
+
+impl UploadQuota {
+    /// Counts an upload by `owner` on `today` unless they already reached `limit`.
+    /// Counts start over whenever the day changes.
+    pub fn try_record(&mut self, owner: &str, limit: u32, today: chrono::NaiveDate) -> bool {
+        if self.day != Some(today) {
+            self.day = Some(today);
+            self.uploads_today.clear();
+        }
+        let count = self.uploads_today.entry(owner.to_string()).or_insert(0);
+        if *count >= limit {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+}
+
+/// Token bucket per client IP: each holds up to `burst` tokens, refilled at
+/// `requests_per_second`, and every request takes one
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    buckets: std::collections::HashMap<IpAddr, TokenBucket>,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Bucket count above which refilled buckets are dropped, bounding memory under many clients
+const RATE_LIMIT_PRUNE_THRESHOLD: usize = 10_000;
+
+impl RateLimiter {
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        Self { requests_per_second, burst: burst.max(1) as f64, buckets: std::collections::HashMap::new() }
+    }
+
+    /// Takes a token for `client` at `now`, or returns how long until one is available
+    pub fn check(&mut self, client: IpAddr, now: Instant) -> Result<(), Duration> {
+        if self.buckets.len() > RATE_LIMIT_PRUNE_THRESHOLD {
+            let refill = Duration::from_secs_f64(self.burst / self.requests_per_second);
+            self.buckets.retain(|_, bucket| now.duration_since(bucket.updated) < refill);
+        }
+        let bucket = self.buckets.entry(client).or_insert(TokenBucket { tokens: self.burst, updated: now });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.requests_per_second).min(self.burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.requests_per_second))
+        }
+    }
 }
 
 impl AppState {
@@ -64,26 +255,105 @@ impl AppState {
             dictionary_loaded: false,
             dictionary_path: None,
             total_files_processed: 0,
+            bytes_in_total: 0,
+            bytes_out_total: 0,
             start_time: std::time::Instant::now(),
+            files: Vec::new(),
+            api_key: None,
+            max_uploads_per_day: None,
+            upload_quota: UploadQuota::default(),
+            pin_cache: None,
+            rate_limiter: None,
+            cors_origins: None,
         }
     }
 }
 
+impl AppState {
+    /// Counts a successfully compressed file towards the status and metrics totals
+    pub fn record_compression(&mut self, file_name: &str, result: &CompressionResponse, owner: &str) {
+        self.total_files_processed += 1;
+        self.bytes_in_total += result.original_size.unwrap_or(0) as u64;
+        self.bytes_out_total += result.compressed_size.unwrap_or(0) as u64;
+        self.files.push(FileRecord::from_response(file_name, result, owner));
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub type SharedState = Arc<Mutex<AppState>>;
 
+/// Environment variable holding the API key for `/compress` and `/decompress`
+const API_KEY_ENV_VAR: &str = "STARK_SQUEEZE_API_KEY";
+/// Request header carrying the API key
+const API_KEY_HEADER: &str = "x-api-key";
+/// Environment variable holding the comma-separated CORS origin allow-list
+const CORS_ORIGINS_ENV_VAR: &str = "SERVER_CORS_ORIGINS";
+
+/// Parses a comma-separated list of origins such as `https://app.example.com`,
+/// rejecting anything that is not a bare `scheme://host[:port]`
+fn parse_cors_origins(list: &str) -> Result<Vec<HeaderValue>> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+        .map(|origin| {
+            let url = url::Url::parse(origin).map_err(|e| anyhow::anyhow!("Invalid CORS origin '{}': {}", origin, e))?;
+            let bare = matches!(url.scheme(), "http" | "https")
+                && url.host_str().is_some()
+                && url.path() == "/"
+                && url.query().is_none()
+                && url.fragment().is_none()
+                && !origin.ends_with('/');
+            if !bare {
+                anyhow::bail!("Invalid CORS origin '{}': expected scheme://host[:port]", origin);
+            }
+            Ok(HeaderValue::from_str(origin)?)
+        })
+        .collect()
+}
+
 /// Initialize the server and generate dictionary
 async fn initialize_server() -> Result<SharedState> {
     info!("🚀 Initializing Stark Squeeze Server...");
     
-    let state = Arc::new(Mutex::new(AppState::new()));
+    let mut app_state = AppState::new();
+    app_state.api_key = std::env::var(API_KEY_ENV_VAR).ok().filter(|key| !key.is_empty());
+    if let Ok(origins) = std::env::var(CORS_ORIGINS_ENV_VAR) {
+        let origins = parse_cors_origins(&origins)?;
+        info!("🌍 CORS limited to {} origins", origins.len());
+        app_state.cors_origins = Some(origins);
+    } else {
+        warn!("⚠️ {} is not set; any website may call the API", CORS_ORIGINS_ENV_VAR);
+    }
+    app_state.max_uploads_per_day = get_config().server.max_uploads_per_day;
+    let server_config = &get_config().server;
+    if server_config.rate_limit_rps > 0.0 {
+        app_state.rate_limiter = Some(RateLimiter::new(server_config.rate_limit_rps, server_config.rate_limit_burst));
+    }
+    let pin_cache_path = &server_config.pin_cache_path;
+    match PinCache::open(pin_cache_path) {
+        Ok(cache) => app_state.pin_cache = Some(Arc::new(cache)),
+        Err(e) => warn!("⚠️ Pin cache {} unavailable, identical uploads will be pinned again: {}", pin_cache_path, e),
+    }
+    if app_state.api_key.is_some() {
+        info!("🔐 API key required for /compress and /decompress");
+    } else {
+        warn!("⚠️ {} is not set; /compress and /decompress are open to anyone", API_KEY_ENV_VAR);
+    }
+    let state = Arc::new(Mutex::new(app_state));
     
     // Generate dictionary if it doesn't exist
-    let dictionary_path = "ascii_combinations.json";
+    let config = get_config();
+    let dictionary_path = config.dictionary.path.as_deref().unwrap_or(&config.server.dictionary.path);
     if !std::path::Path::new(dictionary_path).exists() {
         info!("📚 Dictionary not found. Generating ASCII combinations dictionary...");
         
         // Run the dictionary generation
-        match generate_dictionary().await {
+        match generate_dictionary(dictionary_path).await {
             Ok(_) => {
                 info!("✅ Dictionary generated successfully");
                 let mut state_guard = state.lock().await;
@@ -96,6 +366,10 @@ async fn initialize_server() -> Result<SharedState> {
             }
         }
     } else {
+        if let Err(e) = load_dictionary(std::path::Path::new(dictionary_path)) {
+            error!("❌ {}", e);
+            return Err(e.into());
+        }
         info!("✅ Dictionary found at {}", dictionary_path);
         let mut state_guard = state.lock().await;
         state_guard.dictionary_loaded = true;
@@ -107,7 +381,7 @@ async fn initialize_server() -> Result<SharedState> {
 }
 
 /// Generate the ASCII combinations dictionary
-async fn generate_dictionary() -> Result<()> {
+async fn generate_dictionary(path: &str) -> Result<()> {
     info!("🔤 Generating ASCII combinations dictionary...");
     
     // This would call your existing dictionary generation logic
@@ -122,18 +396,36 @@ async fn generate_dictionary() -> Result<()> {
         "combinations": {}
     });
     
-    fs::write("ascii_combinations.json", serde_json::to_string_pretty(&dictionary_data)?)?;
+    fs::write(path, serde_json::to_string_pretty(&dictionary_data)?)?;
     
     Ok(())
 }
 
 /// Health check endpoint
-async fn health_check() -> impl IntoResponse {
-    Json(serde_json::json!({
-        "status": "healthy",
-        "service": "stark-squeeze",
-        "version": "1.0.0"
-    }))
+async fn health_check(State(state): State<SharedState>) -> impl IntoResponse {
+    let pin_cache = state.lock().await.pin_cache.clone();
+    let db = match pin_cache.map(|cache| cache.ping()) {
+        None => "disabled",
+        Some(Ok(())) => "up",
+        Some(Err(e)) => {
+            error!("❌ Pin cache health check failed: {}", e);
+            "down"
+        }
+    };
+    let (status, health) = if db == "down" {
+        (StatusCode::SERVICE_UNAVAILABLE, "degraded")
+    } else {
+        (StatusCode::OK, "healthy")
+    };
+    (
+        status,
+        Json(serde_json::json!({
+            "status": health,
+            "db": db,
+            "service": "stark-squeeze",
+            "version": "1.0.0"
+        })),
+    )
 }
 
 /// Server status endpoint
@@ -154,16 +446,75 @@ async fn server_status(State(state): State<SharedState>) -> impl IntoResponse {
     Json(status)
 }
 
+/// Content type of the Prometheus text exposition format
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Renders the compression counters and uptime in the Prometheus text format
+fn render_metrics(state: &AppState) -> String {
+    let metrics: [(&str, &str, &str, String); 4] = [
+        (
+            "stark_squeeze_files_processed_total",
+            "counter",
+            "Files compressed since the server started",
+            state.total_files_processed.to_string(),
+        ),
+        ("stark_squeeze_bytes_in_total", "counter", "Original bytes received for compression", state.bytes_in_total.to_string()),
+        ("stark_squeeze_bytes_out_total", "counter", "Bytes pinned after compression", state.bytes_out_total.to_string()),
+        (
+            "stark_squeeze_uptime_seconds",
+            "gauge",
+            "Seconds since the server started",
+            format!("{:.3}", state.start_time.elapsed().as_secs_f64()),
+        ),
+    ];
+    metrics
+        .iter()
+        .map(|(name, kind, help, value)| format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n"))
+        .collect()
+}
+
+/// Prometheus metrics endpoint
+async fn metrics(State(state): State<SharedState>) -> impl IntoResponse {
+    let body = render_metrics(&*state.lock().await);
+    ([(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], body)
+}
+
+/// Owner of uploads made without an API key or `owner` field
+const ANONYMOUS_OWNER: &str = "anonymous";
+
+/// Who an upload counts against: the API key sent (hashed, never stored as-is), else the
+/// `owner` form field, else everyone anonymous together
+fn upload_owner(headers: &HeaderMap, owner_field: Option<&str>) -> String {
+    if let Some(key) = headers.get(API_KEY_HEADER) {
+        let digest = hash_with_mode(key.as_bytes(), HashMode::Sha256);
+        return format!("key:{}", hex::encode(&digest[..8]));
+    }
+    match owner_field.map(str::trim) {
+        Some(owner) if !owner.is_empty() => format!("owner:{}", owner),
+        _ => ANONYMOUS_OWNER.to_string(),
+    }
+}
+
 /// Compress file endpoint
 async fn compress_file_endpoint(
     State(state): State<SharedState>,
+    headers: HeaderMap,
     mut multipart: Multipart,
 ) -> Result<impl IntoResponse, (StatusCode, Json<CompressionResponse>)> {
     let mut file_data = Vec::new();
     let mut file_name = String::new();
+    let mut owner_field = None;
     
-    // Extract file from multipart form data
-    while let Some(field) = multipart.next_field().await.unwrap() {
+    // Extract file from multipart form data; a malformed body is the client's error
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => {
+                warn!("⚠️ Rejected malformed multipart body: {}", e);
+                return Err((StatusCode::BAD_REQUEST, Json(CompressionResponse::failure(format!("Invalid multipart body: {}", e)))));
+            }
+        };
         let name = field.name().unwrap_or("").to_string();
         
         if name == "file" {
@@ -174,54 +525,221 @@ async fn compress_file_endpoint(
             if let Ok(data) = field.bytes().await {
                 file_data = data.to_vec();
             }
+        } else if name == "owner" {
+            owner_field = field.text().await.ok();
         }
     }
     
     if file_data.is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(CompressionResponse {
-                success: false,
-                file_url: None,
-                ipfs_cid: None,
-                compression_ratio: None,
-                original_size: None,
-                compressed_size: None,
-                error: Some("No file data provided".to_string()),
-                mapping_file: None,
-                upload_timestamp: None,
-                file_type: None,
-            })
-        ));
+        return Err((StatusCode::BAD_REQUEST, Json(CompressionResponse::failure("No file data provided"))));
     }
+
+    // Enforce the daily quota before spending any Pinata or Starknet resources
+    let owner = upload_owner(&headers, owner_field.as_deref());
+    check_upload_quota(&state, &owner).await?;
     
-    info!("📁 Processing file: {} ({} bytes)", file_name, file_data.len());
+    info!(file_name = %file_name, size = file_data.len(), "📁 Processing file");
     
     // Process the file through your compression pipeline
-    match process_file_compression(&file_name, &file_data).await {
+    let pin_cache = state.lock().await.pin_cache.clone();
+    match process_file_compression(&file_name, &file_data, pin_cache.as_deref(), None).await {
         Ok(result) => {
-            let mut state_guard = state.lock().await;
-            state_guard.total_files_processed += 1;
+            state.lock().await.record_compression(&file_name, &result, &owner);
             Ok(Json(result))
         }
         Err(e) => {
-            error!("❌ Compression failed: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(CompressionResponse {
-                    success: false,
-                    file_url: None,
-                    ipfs_cid: None,
-                    compression_ratio: None,
-                    original_size: None,
-                    compressed_size: None,
-                    error: Some(e.to_string()),
-                    mapping_file: None,
-                    upload_timestamp: None,
-                    file_type: None,
-                })
+            error!(file_name = %file_name, error = %e, "❌ Compression failed");
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(CompressionResponse::failure(e.to_string()))))
+        }
+    }
+}
+
+/// Counts an upload by `owner` against the daily quota, failing with `429` once it is used up
+async fn check_upload_quota(state: &SharedState, owner: &str) -> Result<(), (StatusCode, Json<CompressionResponse>)> {
+    let mut state_guard = state.lock().await;
+    if let Some(limit) = state_guard.max_uploads_per_day {
+        let today = chrono::Utc::now().date_naive();
+        if !state_guard.upload_quota.try_record(owner, limit, today) {
+            warn!(owner = %owner, limit, "⚠️ Daily upload limit reached");
+            return Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(CompressionResponse::failure(format!(
+                    "Daily upload limit of {} reached; try again after midnight UTC",
+                    limit
+                ))),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// WebSocket version of `/compress` that reports progress while it works: the client
+/// sends a `SocketUploadStart` text message, then the file as binary messages. Each stage
+/// sends `CompressionProgress` events, and the session ends with the `CompressionResponse`.
+async fn compress_socket_endpoint(State(state): State<SharedState>, headers: HeaderMap, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| compress_over_socket(socket, state, headers, PinataStorage))
+}
+
+/// Sends `value` as a JSON text message; false once the client has gone
+async fn send_json(socket: &mut WebSocket, value: &impl Serialize) -> bool {
+    match serde_json::to_string(value) {
+        Ok(text) => socket.send(Message::Text(text)).await.is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Runs one `/ws/compress` session on `socket`, pinning through `storage`
+async fn compress_over_socket(mut socket: WebSocket, state: SharedState, headers: HeaderMap, storage: impl Storage) {
+    let start = match socket.recv().await {
+        Some(Ok(Message::Text(text))) => serde_json::from_str::<SocketUploadStart>(&text).map_err(|e| e.to_string()),
+        _ => Err("expected a text message with file_name and size".to_string()),
+    };
+    let start = match start {
+        Ok(start) => start,
+        Err(e) => {
+            send_json(&mut socket, &CompressionResponse::failure(format!("Invalid start message: {}", e))).await;
+            return;
+        }
+    };
+
+    // The announced size is the client's word, so bound it before buffering anything
+    let max_size = get_config().server.max_socket_upload_mb.saturating_mul(1024 * 1024);
+    if start.size > max_size {
+        let error = format!("File is {} bytes; uploads are limited to {} bytes", start.size, max_size);
+        send_json(&mut socket, &CompressionResponse::failure(error)).await;
+        return;
+    }
+
+    let mut file_data = Vec::new();
+    while (file_data.len() as u64) < start.size {
+        match socket.recv().await {
+            Some(Ok(Message::Binary(chunk))) => {
+                if (file_data.len() + chunk.len()) as u64 > start.size {
+                    let error = format!("Received more than the announced {} bytes", start.size);
+                    send_json(&mut socket, &CompressionResponse::failure(error)).await;
+                    return;
+                }
+                file_data.extend_from_slice(&chunk);
+                let event = CompressionProgress::new("upload", file_data.len() as u64, start.size);
+                if !send_json(&mut socket, &event).await {
+                    return;
+                }
+            }
+            Some(Ok(Message::Ping(_) | Message::Pong(_))) => {}
+            _ => {
+                warn!(file_name = %start.file_name, received = file_data.len(), "⚠️ WebSocket upload ended early");
+                return;
+            }
+        }
+    }
+    if file_data.is_empty() || file_data.len() as u64 != start.size {
+        let error = format!("Expected {} bytes, received {}", start.size, file_data.len());
+        send_json(&mut socket, &CompressionResponse::failure(error)).await;
+        return;
+    }
+
+    let owner = upload_owner(&headers, start.owner.as_deref());
+    if let Err((_, Json(response))) = check_upload_quota(&state, &owner).await {
+        send_json(&mut socket, &response).await;
+        return;
+    }
+    info!(file_name = %start.file_name, size = file_data.len(), "📁 Processing file over WebSocket");
+
+    // Events are forwarded while the pipeline runs; the channel closes when it finishes
+    let (sender, mut events) = tokio::sync::mpsc::unbounded_channel();
+    let progress: ProgressFn = Arc::new(move |event| {
+        let _ = sender.send(event);
+    });
+    let pin_cache = state.lock().await.pin_cache.clone();
+    let (file_name, data, storage) = (&start.file_name, &file_data, &storage);
+    let pipeline = async move {
+        let abort_on_expansion = get_config().server.abort_on_expansion;
+        process_file_compression_with(file_name, data, abort_on_expansion, storage, pin_cache.as_deref(), Some(progress)).await
+    };
+    let forward = async {
+        while let Some(event) = events.recv().await {
+            send_json(&mut socket, &event).await;
+        }
+    };
+    let (result, ()) = tokio::join!(pipeline, forward);
+
+    let response = match result {
+        Ok(response) => {
+            state.lock().await.record_compression(&start.file_name, &response, &owner);
+            response
+        }
+        Err(e) => {
+            error!(file_name = %start.file_name, error = %e, "❌ Compression failed");
+            CompressionResponse::failure(e.to_string())
+        }
+    };
+    send_json(&mut socket, &response).await;
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+/// Decompress file endpoint: reverses `compress_file` and returns the bytes as an attachment
+async fn decompress_file_endpoint(
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, (StatusCode, Json<CompressionResponse>)> {
+    let mut file_data = Vec::new();
+    let mut file_name = String::new();
+
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => {
+                return Err((StatusCode::BAD_REQUEST, Json(CompressionResponse::failure(format!("Invalid multipart body: {}", e)))));
+            }
+        };
+        if field.name() == Some("file") {
+            file_name = field.file_name().unwrap_or_default().to_string();
+            file_data = field
+                .bytes()
+                .await
+                .map_err(|e| (StatusCode::BAD_REQUEST, Json(CompressionResponse::failure(format!("Failed to read file: {}", e)))))?
+                .to_vec();
+        }
+    }
+
+    if file_data.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, Json(CompressionResponse::failure("No file data provided"))));
+    }
+
+    info!(file_name = %file_name, size = file_data.len(), "📂 Decompressing file");
+
+    match decompress_file(&file_data) {
+        Ok(bytes) => {
+            let disposition = format!("attachment; filename=\"{}\"", decompressed_file_name(&file_name));
+            Ok((
+                [
+                    (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+                    (header::CONTENT_DISPOSITION, disposition),
+                ],
+                Body::from(bytes),
             ))
         }
+        Err(CompressionError::Io(e)) => {
+            error!("❌ Decompression failed: {}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(CompressionResponse::failure(e.to_string()))))
+        }
+        Err(e) => Err((StatusCode::BAD_REQUEST, Json(CompressionResponse::failure(e.to_string())))),
+    }
+}
+
+/// Download name for a decompressed upload: the upload's name without the `.txt` the CLI
+/// adds, keeping only characters that are safe in a header
+fn decompressed_file_name(upload_name: &str) -> String {
+    let name: String = upload_name
+        .strip_suffix(".txt")
+        .unwrap_or(upload_name)
+        .chars()
+        .filter(|c| c.is_ascii_graphic() && *c != '"' && *c != '\\' && *c != '/')
+        .collect();
+    if name.is_empty() {
+        "decompressed.bin".to_string()
+    } else {
+        name
     }
 }
 
@@ -229,7 +747,63 @@ async fn compress_file_endpoint(
 async fn process_file_compression(
     file_name: &str,
     file_data: &[u8],
+    pin_cache: Option<&PinCache>,
+    progress: Option<ProgressFn>,
 ) -> Result<CompressionResponse> {
+    process_file_compression_with(
+        file_name,
+        file_data,
+        get_config().server.abort_on_expansion,
+        &PinataStorage,
+        pin_cache,
+        progress,
+    )
+    .await
+}
+
+/// Bytes to pin for a file, and whether compressing it paid off
+#[derive(Debug, PartialEq)]
+struct PinPayload {
+    bytes: Vec<u8>,
+    beneficial: bool,
+}
+
+/// Picks the compressed bytes, or the original behind the stored header when compression
+/// would expand it and `abort_on_expansion` is set. Either way `/decompress` restores it.
+fn pin_payload(original: &[u8], compressed: Vec<u8>, abort_on_expansion: bool) -> Result<PinPayload> {
+    let beneficial = compressed.len() < original.len();
+    if !beneficial && abort_on_expansion {
+        let stored = compress_file_with_codec(original, Codec::None).map_err(|e| anyhow::anyhow!("Compression failed: {}", e))?;
+        Ok(PinPayload { bytes: stored, beneficial })
+    } else {
+        Ok(PinPayload { bytes: compressed, beneficial })
+    }
+}
+
+/// Percentage saved, as recorded on-chain; 0 when the data did not shrink
+fn compressed_by(original_size: usize, compressed_size: usize) -> u8 {
+    if compressed_size < original_size {
+        ((original_size - compressed_size) * 100 / original_size) as u8
+    } else {
+        0
+    }
+}
+
+/// `process_file_compression` pinning through the given storage, skipping the pin when
+/// `pin_cache` already holds a CID for the same bytes. `progress` hears about each stage.
+async fn process_file_compression_with(
+    file_name: &str,
+    file_data: &[u8],
+    abort_on_expansion: bool,
+    storage: &impl Storage,
+    pin_cache: Option<&PinCache>,
+    progress: Option<ProgressFn>,
+) -> Result<CompressionResponse> {
+    let report = |stage: &str, done: usize, total: usize| {
+        if let Some(progress) = &progress {
+            progress(CompressionProgress::new(stage, done as u64, total as u64));
+        }
+    };
     let original_size = file_data.len();
     let upload_timestamp = chrono::Utc::now().timestamp();
     
@@ -240,54 +814,84 @@ async fn process_file_compression(
         .unwrap_or("unknown")
         .to_string();
     
-    // Step 1: Convert to printable ASCII (keeping this for now)
-    let (ascii_buffer, _ascii_stats) = convert_to_printable_ascii(file_data)
-        .map_err(|e| anyhow::anyhow!("ASCII conversion failed: {}", e))?;
-    
-    // Step 2: Convert ASCII buffer to binary string
-    let binary_string: String = ascii_buffer.iter()
-        .map(|&byte| format!("{:08b}", byte))
-        .collect();
-    
-    // Step 3: Mock compression (keeping original data)
-    let bytes = binary_string.as_bytes();
-    let encoded_data = compress_file(bytes)
+    // Step 1: Compress the file's bytes as they are, so `/decompress` restores them exactly
+    report("compression", 0, original_size);
+    let encoded_data = compress_file(file_data)
         .map_err(|e| anyhow::anyhow!("Compression failed: {}", e))?;
+    report("compression", original_size, original_size);
+    
+    // Step 2: Decide what to pin; expanded output is only pinned when abort_on_expansion is off
+    let encoded_size = encoded_data.len();
+    let payload = pin_payload(file_data, encoded_data, abort_on_expansion)?;
+    if !payload.beneficial {
+        warn!(file_name = %file_name, original_size, compressed_size = encoded_size, "⚠️ Compression would expand the file");
+    }
     
-    // Step 4: Calculate compression metrics (mock - no actual compression)
-    let compressed_size = encoded_data.len();
-    let compression_ratio = ((compressed_size as f64 / original_size as f64) * 100.0) as f64;
+    // Step 3: Generate hash of the pinned bytes for file identification
+    let hash_output = hash_data(&payload.bytes, &get_config().upload.hash);
+    info!(size = payload.bytes.len(), mode = %hash_output.mode, "🔑 Hashed compressed data");
+    let short_hash = hex::encode(&hash_output.digest[..8]);
     
-    // Step 5: Generate hash for file identification
-    let mut hasher = Sha256::new();
-    let encoded_data_bytes: Vec<u8> = encoded_data.iter().flat_map(|x| x.to_be_bytes()).collect();
-    hasher.update(&encoded_data_bytes);
-    let hash = hasher.finalize();
-    let short_hash = hex::encode(&hash[..8]);
+    // Step 4: Calculate compression metrics for the bytes actually pinned
+    let compressed_size = payload.bytes.len();
+    let compression_ratio = if payload.beneficial { (compressed_size as f64 / original_size as f64) * 100.0 } else { 100.0 };
     
-    // Step 6: Upload original file to IPFS via Pinata
-    let ipfs_cid = match pin_file_to_ipfs(file_data, file_name).await {
+    // Step 5: Upload to IPFS via Pinata, unless the same bytes were pinned before
+    let content_hash = hex::encode(hash_with_mode(file_data, HashMode::Sha256));
+    let cached_cid = pin_cache.and_then(|cache| match cache.get(&content_hash) {
+        Ok(cid) => cid,
+        Err(e) => {
+            warn!(file_name = %file_name, error = %e, "⚠️ Pin cache lookup failed");
+            None
+        }
+    });
+    let pinned = match cached_cid {
+        Some(cid) => {
+            info!(file_name = %file_name, cid = %cid, "♻️ Identical content already pinned");
+            Ok(cid)
+        }
+        None => {
+            let total = payload.bytes.len();
+            report("ipfs_pinning", 0, total);
+            let on_progress: stark_squeeze::ipfs_client::PinProgress = match &progress {
+                Some(progress) => {
+                    let progress = progress.clone();
+                    Arc::new(move |sent, total| progress(CompressionProgress::new("ipfs_pinning", sent, total)))
+                }
+                None => Arc::new(|_, _| {}),
+            };
+            let pinned = storage.pin_with_progress(payload.bytes, file_name.to_string(), on_progress).await;
+            if let Ok(cid) = &pinned {
+                report("ipfs_pinning", total, total);
+                if let Some(Err(e)) = pin_cache.map(|cache| cache.insert(&content_hash, cid)) {
+                    warn!(file_name = %file_name, error = %e, "⚠️ Failed to record pin in cache");
+                }
+            }
+            pinned
+        }
+    };
+    let ipfs_cid = match pinned {
         Ok(cid) => {
-            info!("✅ File pinned to IPFS: {}", cid);
+            info!(file_name = %file_name, cid = %cid, "✅ File pinned to IPFS");
             Some(cid)
         }
         Err(e) => {
-            warn!("⚠️ IPFS upload failed: {}", e);
+            warn!(file_name = %file_name, error = %e, "⚠️ IPFS upload failed");
             None
         }
     };
     
-    // Step 7: Generate file URLs
+    // Step 6: Generate file URLs
     let file_url = if let Some(ref cid) = ipfs_cid {
-        Some(format!("https://gateway.pinata.cloud/ipfs/{}", cid))
+        Some(gateway_url(&get_config().storage.ipfs.gateway, cid))
     } else {
         // Fallback to local URL if IPFS upload failed
         Some(format!("http://localhost:8080/files/{}", short_hash))
     };
     
-    // Step 8: Upload to Starknet (optional - you can disable this for testing)
+    // Step 7: Upload to Starknet (optional - you can disable this for testing)
     let _starknet_url = if std::env::var("ENABLE_STARKNET_UPLOAD").unwrap_or_default() == "true" {
-        match upload_to_starknet(&short_hash, file_name, original_size, compressed_size).await {
+        match upload_to_starknet(&short_hash, file_name, original_size, compressed_size, ipfs_cid.as_deref()).await {
             Ok(url) => Some(url),
             Err(e) => {
                 warn!("⚠️ Starknet upload failed: {}", e);
@@ -298,8 +902,13 @@ async fn process_file_compression(
         None
     };
     
-    info!("✅ File processed successfully: {} -> {} bytes ({:.1}% compression)", 
-          original_size, compressed_size, 100.0 - compression_ratio);
+    info!(
+        file_name = %file_name,
+        original_size,
+        compressed_size,
+        reduction_percent = 100.0 - compression_ratio,
+        "✅ File processed successfully"
+    );
     
     Ok(CompressionResponse {
         success: true,
@@ -312,6 +921,7 @@ async fn process_file_compression(
         mapping_file: None,
         upload_timestamp: Some(upload_timestamp),
         file_type: Some(file_type),
+        compression_beneficial: Some(payload.beneficial),
     })
 }
 
@@ -321,13 +931,10 @@ async fn upload_to_starknet(
     file_format: &str,
     original_size: usize,
     compressed_size: usize,
+    ipfs_cid: Option<&str>,
 ) -> Result<String> {
     // Prepare data for upload
-    let compressed_by = if compressed_size < original_size {
-        ((original_size - compressed_size) * 100 / original_size) as u8
-    } else {
-        0
-    };
+    let compressed_by = compressed_by(original_size, compressed_size);
     
     // Create minimal arrays for on-chain storage
     let chunk_mappings = vec![starknet::core::types::FieldElement::from(0u32)];
@@ -335,7 +942,7 @@ async fn upload_to_starknet(
     let byte_mappings = vec![0u8];
     let byte_values = vec![starknet::core::types::FieldElement::from(0u32)];
     let reconstruction_steps = vec![starknet::core::types::FieldElement::from(0u32)];
-    let metadata = vec![starknet::core::types::FieldElement::from(0u32)];
+    let metadata = encode_upload_metadata(ipfs_cid, true);
     
     upload_data(
         uri,
@@ -350,11 +957,112 @@ async fn upload_to_starknet(
         byte_values,
         reconstruction_steps,
         metadata,
+        false, // don't hold the HTTP request open while the transaction confirms
     ).await.map_err(|e| anyhow::anyhow!("Starknet upload failed: {}", e))?;
     
     Ok(format!("starknet://{}", uri))
 }
 
+/// Content type for newline-delimited JSON listings
+const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Whether the client asked for a streamed NDJSON listing
+fn wants_ndjson(headers: &HeaderMap, query: &ListFilesQuery) -> bool {
+    query.stream
+        || headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|accept| accept.split(',').any(|t| t.trim().starts_with(NDJSON_CONTENT_TYPE)))
+}
+
+/// List processed files, as a JSON array or streamed as NDJSON
+async fn list_files(
+    State(state): State<SharedState>,
+    Query(query): Query<ListFilesQuery>,
+    headers: HeaderMap,
+) -> axum::response::Response {
+    let files = state.lock().await.files.clone();
+
+    if !wants_ndjson(&headers, &query) {
+        return Json(files).into_response();
+    }
+
+    // Serialize one row at a time so large listings are never held as a single body
+    let rows = futures_util::stream::iter(files.into_iter().map(|file| {
+        serde_json::to_vec(&file).map(|mut line| {
+            line.push(b'\n');
+            line
+        })
+    }));
+
+    (
+        [(header::CONTENT_TYPE, NDJSON_CONTENT_TYPE)],
+        Body::from_stream(rows),
+    )
+        .into_response()
+}
+
+/// Removes a file from the listing and unpins its CID. Only the owner that uploaded the
+/// file may delete it, and anonymous callers may not delete anything; a CID that is
+/// already unpinned still counts as deleted
+async fn delete_file_with(state: &SharedState, file_id: &str, caller: &str, storage: &impl Storage) -> Response {
+    if caller == ANONYMOUS_OWNER {
+        warn!(file_id = %file_id, "⚠️ Rejected anonymous delete");
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(CompressionResponse::failure("Deleting a file requires an API key or the owner it was uploaded with")),
+        )
+            .into_response();
+    }
+    let cid = {
+        let state_guard = state.lock().await;
+        let Some(record) = state_guard.files.iter().find(|file| file.id == file_id) else {
+            return (StatusCode::NOT_FOUND, Json(CompressionResponse::failure("File not found"))).into_response();
+        };
+        if record.owner.as_deref() != Some(caller) {
+            warn!(file_id = %file_id, "⚠️ Rejected delete by a caller that does not own the file");
+            return (StatusCode::FORBIDDEN, Json(CompressionResponse::failure("File belongs to another owner"))).into_response();
+        }
+        record.ipfs_cid.clone()
+    };
+    let cid_field = cid.clone().unwrap_or_default();
+
+    // Unpin before forgetting the file, so a failed unpin can be retried
+    if let Some(cid) = cid {
+        match storage.unpin(cid.clone()).await {
+            Ok(()) => info!(file_id = %file_id, cid = %cid, "📌 Unpinned from IPFS"),
+            Err(IpfsError::ApiError(message)) if message == ALREADY_UNPINNED => {
+                info!(file_id = %file_id, cid = %cid, "📌 Already unpinned")
+            }
+            Err(e) => {
+                error!(file_id = %file_id, cid = %cid, error = %e, "❌ Unpin failed");
+                return (StatusCode::BAD_GATEWAY, Json(CompressionResponse::failure(format!("Unpin failed: {}", e)))).into_response();
+            }
+        }
+    }
+
+    let mut state_guard = state.lock().await;
+    let before = state_guard.files.len();
+    state_guard.files.retain(|file| file.id != file_id);
+    if state_guard.files.len() == before {
+        // Deleted by a concurrent request while unpinning
+        return (StatusCode::NOT_FOUND, Json(CompressionResponse::failure("File not found"))).into_response();
+    }
+    info!(file_id = %file_id, owner = %caller, cid = %cid_field, "🗑️ File deleted");
+    Json(serde_json::json!({ "success": true, "id": file_id })).into_response()
+}
+
+/// Delete file endpoint; the caller is resolved like an upload, from the API key or `?owner=`
+async fn delete_file(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    axum::extract::Path(file_id): axum::extract::Path<String>,
+    Query(query): Query<DeleteFileQuery>,
+) -> Response {
+    let caller = upload_owner(&headers, query.owner.as_deref());
+    delete_file_with(&state, &file_id, &caller, &PinataStorage).await
+}
+
 /// Download compressed file endpoint
 async fn download_file(axum::extract::Path(file_id): axum::extract::Path<String>) -> impl IntoResponse {
     let mapping_file = format!("{}.map", file_id);
@@ -367,50 +1075,221 @@ async fn download_file(axum::extract::Path(file_id): axum::extract::Path<String>
     // For now, return the mapping file
     match fs::read(&mapping_file) {
         Ok(data) => {
-            let headers = HeaderMap::from_iter(vec![
-                ("content-type".parse().unwrap(), "application/json".parse().unwrap()),
-                ("content-disposition".parse().unwrap(), format!("attachment; filename=\"{}\"", mapping_file).parse().unwrap()),
-            ]);
+            let mut headers = HeaderMap::new();
+            headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            // The id comes from the URL, so it may not be a valid header value
+            match HeaderValue::from_str(&format!("attachment; filename=\"{}\"", mapping_file)) {
+                Ok(disposition) => {
+                    headers.insert(header::CONTENT_DISPOSITION, disposition);
+                }
+                Err(e) => warn!("⚠️ Omitting content-disposition for {:?}: {}", mapping_file, e),
+            }
             (StatusCode::OK, headers, data).into_response()
         }
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Failed to read file").into_response()
+        Err(e) => {
+            error!("❌ Failed to read {}: {}", mapping_file, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to read file").into_response()
+        }
+    }
+}
+
+/// Rejects requests without the configured API key; everything passes when none is configured
+async fn require_api_key(State(state): State<SharedState>, request: Request, next: Next) -> Response {
+    let expected = state.lock().await.api_key.clone();
+    if let Some(expected) = expected {
+        let provided = request.headers().get(API_KEY_HEADER).map(HeaderValue::as_bytes);
+        if !provided.is_some_and(|provided| constant_time_eq(provided, expected.as_bytes())) {
+            warn!(path = %request.uri().path(), "⚠️ Rejected request without a valid API key");
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(CompressionResponse::failure("Missing or invalid API key")),
+            )
+                .into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Answers `429` with `Retry-After` once the client IP has used up its requests
+async fn rate_limit(State(state): State<SharedState>, request: Request, next: Next) -> Response {
+    let client = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED), |ConnectInfo(addr)| addr.ip());
+    let limited = match state.lock().await.rate_limiter.as_mut() {
+        Some(limiter) => limiter.check(client, Instant::now()).err(),
+        None => None,
+    };
+    if let Some(wait) = limited {
+        let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+        warn!(client = %client, path = %request.uri().path(), "⚠️ Rate limit exceeded");
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, retry_after.to_string())],
+            Json(CompressionResponse::failure("Too many requests; slow down")),
+        )
+            .into_response();
     }
+    next.run(request).await
+}
+
+/// Compares two byte strings in time that depends only on their lengths
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
 }
 
 /// Create the router with all endpoints
-fn create_router(state: SharedState) -> Router {
-    // Configure CORS
+async fn create_router(state: SharedState) -> Router {
+    // Configure CORS: the configured origins, or any when none are
+    let allow_origin = match state.lock().await.cors_origins.clone() {
+        Some(origins) => AllowOrigin::list(origins),
+        None => AllowOrigin::any(),
+    };
     let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::OPTIONS])
         .allow_headers(Any)
         .allow_credentials(false);
 
+    // File endpoints need the API key when one is configured
+    let protected = Router::new()
+        .route("/compress", post(compress_file_endpoint))
+        .route("/decompress", post(decompress_file_endpoint))
+        .route("/files/:file_id", delete(delete_file))
+        .route("/ws/compress", get(compress_socket_endpoint))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_api_key));
+
+    // Everything but /health is rate limited
     Router::new()
-        .route("/health", get(health_check))
         .route("/status", get(server_status))
-        .route("/compress", post(compress_file_endpoint))
+        .route("/metrics", get(metrics))
+        .merge(protected)
+        .route("/files", get(list_files))
         .route("/files/:file_id", get(download_file))
+        .route_layer(middleware::from_fn_with_state(state.clone(), rate_limit))
+        .route("/health", get(health_check))
         .layer(cors)
         .with_state(state)
 }
 
+/// Counts requests while they are being handled
+async fn count_in_flight(State(in_flight): State<Arc<AtomicUsize>>, request: Request, next: Next) -> Response {
+    let _guard = InFlightGuard::new(in_flight);
+    next.run(request).await
+}
+
+/// Holds one count in the in-flight gauge, released on drop so a request whose
+/// future is cancelled (e.g. the client disconnected) is not counted forever
+struct InFlightGuard(Arc<AtomicUsize>);
+
+impl InFlightGuard {
+    fn new(in_flight: Arc<AtomicUsize>) -> Self {
+        in_flight.fetch_add(1, Ordering::SeqCst);
+        Self(in_flight)
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Resolves on Ctrl-C, or SIGTERM on Unix
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("❌ Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                error!("❌ Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// Serves the app until `signal` resolves, then stops accepting connections and waits up to
+/// `drain_timeout` for in-flight requests before returning
+async fn serve_until(
+    listener: tokio::net::TcpListener,
+    state: SharedState,
+    signal: impl Future<Output = ()> + Send + 'static,
+    drain_timeout: Duration,
+) -> Result<()> {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let app = create_router(state).await.layer(middleware::from_fn_with_state(in_flight.clone(), count_in_flight));
+    let draining = Arc::new(tokio::sync::Notify::new());
+    let shutdown = {
+        let draining = draining.clone();
+        async move {
+            signal.await;
+            info!("🛑 shutting down, draining {} requests", in_flight.load(Ordering::SeqCst));
+            draining.notify_one();
+        }
+    };
+    // Client addresses are needed for per-IP rate limiting
+    let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).with_graceful_shutdown(shutdown);
+    tokio::select! {
+        served = server => served?,
+        _ = async {
+            draining.notified().await;
+            tokio::time::sleep(drain_timeout).await;
+        } => warn!("⚠️ Requests still running after {:?}; exiting anyway", drain_timeout),
+    }
+    info!("👋 Server stopped");
+    Ok(())
+}
+
+/// Environment variable selecting the log format; `json` emits one JSON object per line
+const LOG_FORMAT_ENV_VAR: &str = "LOG_FORMAT";
+
+/// Whether a `LOG_FORMAT` value asks for JSON lines
+fn is_json_log_format(format: Option<&str>) -> bool {
+    format.is_some_and(|format| format.eq_ignore_ascii_case("json"))
+}
+
+/// Installs the tracing subscriber: JSON lines when `json` is set, human-readable otherwise
+fn init_tracing(json: bool) {
+    let installed = if json {
+        tracing_subscriber::fmt().json().try_init()
+    } else {
+        tracing_subscriber::fmt().try_init()
+    };
+    if let Err(e) = installed {
+        eprintln!("Logging was already initialized: {}", e);
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Load environment variables
     dotenvy::dotenv().ok();
     
-    // Initialize tracing
-    tracing_subscriber::fmt::init();
+    init_tracing(is_json_log_format(std::env::var(LOG_FORMAT_ENV_VAR).ok().as_deref()));
     
     info!("🚀 Starting Stark Squeeze Server...");
     
     // Initialize server state and generate dictionary
     let state = initialize_server().await?;
     
-    // Create router
-    let app = create_router(state);
-    
     // Get port from environment variable (Render provides PORT, but we use SERVER_PORT)
     let port = std::env::var("PORT").or_else(|_| std::env::var("SERVER_PORT")).unwrap_or_else(|_| "8080".to_string());
     let addr = format!("0.0.0.0:{}", port);
@@ -420,9 +1299,607 @@ async fn main() -> Result<()> {
     info!("🌐 Server listening on http://{}", addr);
     info!("📚 Health check: http://{}/health", addr);
     info!("📊 Status: http://{}/status", addr);
+    info!("📈 Metrics: http://{}/metrics", addr);
     info!("📁 Compress files: POST http://{}/compress", addr);
+    info!("📂 Decompress files: POST http://{}/decompress", addr);
+    info!("🗂️ List files: GET http://{}/files", addr);
+    info!("🗑️ Delete files: DELETE http://{}/files/:id", addr);
     
-    axum::serve(listener, app).await?;
-    
-    Ok(())
-} 
\ No newline at end of file
+    let drain_timeout = Duration::from_secs(get_config().server.shutdown_timeout_secs);
+    serve_until(listener, state, shutdown_signal(), drain_timeout).await
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+    use stark_squeeze::storage::mock::{CallLog, MockStorage};
+    use tower::ServiceExt;
+
+    fn record(name: &str) -> FileRecord {
+        FileRecord {
+            id: name.to_string(),
+            file_name: name.to_string(),
+            file_type: Some("txt".to_string()),
+            original_size: Some(100),
+            compressed_size: Some(40),
+            compression_ratio: Some(60.0),
+            ipfs_cid: None,
+            file_url: None,
+            upload_timestamp: Some(0),
+            owner: None,
+        }
+    }
+
+    fn state_with_files(names: &[&str]) -> SharedState {
+        let mut state = AppState::new();
+        state.files = names.iter().map(|name| record(name)).collect();
+        Arc::new(Mutex::new(state))
+    }
+
+    async fn body_string(response: axum::response::Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_list_files_streams_one_object_per_line() {
+        let app = create_router(state_with_files(&["a.txt", "b.txt", "c.txt"])).await;
+        let response = app
+            .oneshot(
+                Request::get("/files")
+                    .header(header::ACCEPT, NDJSON_CONTENT_TYPE)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], NDJSON_CONTENT_TYPE);
+        let body = body_string(response).await;
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 3);
+        for (line, name) in lines.iter().zip(["a.txt", "b.txt", "c.txt"]) {
+            let row: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert!(row.is_object());
+            assert_eq!(row["file_name"], name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_files_defaults_to_array() {
+        let app = create_router(state_with_files(&["a.txt", "b.txt"])).await;
+        let response = app
+            .clone()
+            .oneshot(Request::get("/files").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let rows: Vec<FileRecord> = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!(rows.len(), 2);
+
+        let response = app
+            .oneshot(Request::get("/files?stream=true").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(body_string(response).await.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_json_log_format_initializes() {
+        assert!(is_json_log_format(Some("JSON")));
+        assert!(!is_json_log_format(Some("text")));
+        assert!(!is_json_log_format(None));
+        init_tracing(true);
+        info!(file_name = "a.txt", size = 3, "structured event");
+        // A second install is reported, not a panic
+        init_tracing(false);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_exposes_counters_in_prometheus_format() {
+        let state = state_with_files(&[]);
+        let result = CompressionResponse {
+            original_size: Some(1000),
+            compressed_size: Some(250),
+            ..CompressionResponse::failure("")
+        };
+        state.lock().await.record_compression("a.txt", &result, "anonymous");
+
+        let response = create_router(state).await
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROMETHEUS_CONTENT_TYPE);
+        let body = body_string(response).await;
+        for (name, kind) in [
+            ("stark_squeeze_files_processed_total", "counter"),
+            ("stark_squeeze_bytes_in_total", "counter"),
+            ("stark_squeeze_bytes_out_total", "counter"),
+            ("stark_squeeze_uptime_seconds", "gauge"),
+        ] {
+            assert!(body.contains(&format!("# TYPE {} {}\n", name, kind)), "{}", body);
+        }
+        assert!(body.contains("stark_squeeze_files_processed_total 1\n"));
+        assert!(body.contains("stark_squeeze_bytes_in_total 1000\n"));
+        assert!(body.contains("stark_squeeze_bytes_out_total 250\n"));
+    }
+
+    /// `upload_owner` for a request carrying `api_key`
+    fn key_owner(api_key: &'static str) -> String {
+        upload_owner(&HeaderMap::from_iter([(header::HeaderName::from_static(API_KEY_HEADER), HeaderValue::from_static(api_key))]), None)
+    }
+
+    #[tokio::test]
+    async fn test_delete_removes_owned_file_from_listing() {
+        let state = state_with_files(&["a.txt", "b.txt"]);
+        state.lock().await.files[0].owner = Some(key_owner("k1"));
+        let app = create_router(state).await;
+        let delete = |api_key: &'static str| Request::delete("/files/a.txt").header(API_KEY_HEADER, api_key).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(delete("k2")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = app.clone().oneshot(delete("k1")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.clone().oneshot(Request::get("/files").body(Body::empty()).unwrap()).await.unwrap();
+        let rows: Vec<FileRecord> = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!(rows.iter().map(|row| row.file_name.as_str()).collect::<Vec<_>>(), ["b.txt"]);
+
+        let response = app.oneshot(delete("k1")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_delete_resolves_the_caller_like_uploads() {
+        let state = state_with_files(&["a.txt", "b.txt"]);
+        {
+            let mut state_guard = state.lock().await;
+            state_guard.files[0].owner = Some(upload_owner(&HeaderMap::new(), Some("alice")));
+            state_guard.files[1].owner = Some(ANONYMOUS_OWNER.to_string());
+        }
+        let app = create_router(state.clone()).await;
+        let delete = |uri: &str| Request::delete(uri).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(delete("/files/a.txt?owner=bob")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = app.clone().oneshot(delete("/files/a.txt?owner=alice")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Anonymous uploads are shared by every anonymous caller, so none of them may delete
+        let response = app.clone().oneshot(delete("/files/b.txt")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // With a key configured, the key is required even from the owner
+        state.lock().await.api_key = Some("s3cret-key".to_string());
+        let app = create_router(state.clone()).await;
+        let response = app.oneshot(delete("/files/b.txt?owner=anonymous")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(state.lock().await.files.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_unpins_the_file_cid() {
+        let state = state_with_files(&["a.txt"]);
+        {
+            let mut state_guard = state.lock().await;
+            state_guard.files[0].owner = Some(key_owner("k1"));
+            state_guard.files[0].ipfs_cid = Some("QmTestCid".to_string());
+        }
+        let calls = CallLog::default();
+        let response = delete_file_with(&state, "a.txt", &key_owner("k1"), &MockStorage::new("", calls.clone())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(*calls.lock().unwrap(), ["unpin QmTestCid"]);
+        assert!(state.lock().await.files.is_empty());
+    }
+
+    /// Multipart POST to `uri` with `data` as the `file` field
+    fn multipart_request(uri: &str, file_name: &str, data: &[u8], api_key: Option<&str>) -> Request<Body> {
+        let boundary = "stark-squeeze-test-boundary";
+        let body = [
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+                boundary, file_name
+            )
+            .into_bytes(),
+            data.to_vec(),
+            format!("\r\n--{}--\r\n", boundary).into_bytes(),
+        ]
+        .concat();
+        let mut request = Request::post(uri)
+            .header(header::CONTENT_TYPE, format!("multipart/form-data; boundary={}", boundary));
+        if let Some(api_key) = api_key {
+            request = request.header(API_KEY_HEADER, api_key);
+        }
+        request.body(Body::from(body)).unwrap()
+    }
+
+    /// POSTs `data` to `/decompress` as the multipart `file` field
+    async fn post_decompress(file_name: &str, data: &[u8]) -> axum::response::Response {
+        create_router(state_with_files(&[])).await
+            .oneshot(multipart_request("/decompress", file_name, data, None))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_decompress_returns_original_bytes() {
+        let original: Vec<u8> = [vec![0u8; 300], b"stark squeeze".to_vec(), vec![0xff; 40]].concat();
+        let response = post_decompress("data.bin.txt", &compress_file(&original).unwrap()).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_DISPOSITION], "attachment; filename=\"data.bin\"");
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(bytes.to_vec(), original);
+    }
+
+    #[tokio::test]
+    async fn test_decompress_rejects_foreign_files() {
+        let response = post_decompress("notes.txt", b"just some text").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: CompressionResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert!(!body.success);
+        assert_eq!(body.error.as_deref(), Some("not a stark-squeeze file"));
+
+        let response = post_decompress("empty.txt", b"").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_file_endpoints_require_configured_api_key() {
+        let state = state_with_files(&[]);
+        state.lock().await.api_key = Some("s3cret-key".to_string());
+        let app = create_router(state).await;
+        let packed = compress_file(b"aaaaaaaaaaaaaaaa").unwrap();
+
+        // Missing key, on both protected endpoints
+        for uri in ["/compress", "/decompress"] {
+            let response = app.clone().oneshot(multipart_request(uri, "a.txt", &packed, None)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+            let body: CompressionResponse = serde_json::from_str(&body_string(response).await).unwrap();
+            assert_eq!(body.error.as_deref(), Some("Missing or invalid API key"));
+        }
+
+        // Wrong key, including one sharing a prefix
+        for key in ["wrong", "s3cret-key-2", "s3cret-ke"] {
+            let response = app.clone().oneshot(multipart_request("/decompress", "a.txt", &packed, Some(key))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{}", key);
+        }
+
+        // Correct key
+        let response = app
+            .clone()
+            .oneshot(multipart_request("/decompress", "a.txt", &packed, Some("s3cret-key")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Other endpoints stay open
+        let response = app.oneshot(Request::get("/health").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_rejects_bursts_except_health() {
+        let state = state_with_files(&[]);
+        state.lock().await.rate_limiter = Some(RateLimiter::new(1.0, 3));
+        let app = create_router(state).await;
+        let from = |path: &str, ip: [u8; 4]| {
+            let mut request = Request::get(path).body(Body::empty()).unwrap();
+            request.extensions_mut().insert(ConnectInfo(SocketAddr::from((ip, 4000))));
+            request
+        };
+
+        let mut statuses = Vec::new();
+        for _ in 0..10 {
+            let response = app.clone().oneshot(from("/status", [10, 0, 0, 1])).await.unwrap();
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                let retry_after: u64 = response.headers()[header::RETRY_AFTER].to_str().unwrap().parse().unwrap();
+                assert!(retry_after >= 1);
+            }
+            statuses.push(response.status());
+        }
+        assert_eq!(statuses.iter().filter(|&&status| status == StatusCode::OK).count(), 3);
+        assert_eq!(statuses.iter().filter(|&&status| status == StatusCode::TOO_MANY_REQUESTS).count(), 7);
+
+        // Other clients have their own bucket, and /health is never limited
+        let response = app.clone().oneshot(from("/status", [10, 0, 0, 2])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        for _ in 0..10 {
+            let response = app.clone().oneshot(from("/health", [10, 0, 0, 1])).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_exits_within_drain_timeout_after_signal() {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve_until(
+            listener,
+            state_with_files(&[]),
+            async {
+                stopped.await.ok();
+            },
+            Duration::from_millis(500),
+        ));
+
+        // A client stuck halfway through its request keeps a connection open
+        let mut client = tokio::net::TcpStream::connect(addr).await.unwrap();
+        client.write_all(b"GET /status HTTP/1.1\r\nHost: localhost\r\n").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        stop.send(()).unwrap();
+        let finished = tokio::time::timeout(Duration::from_secs(3), server).await;
+        assert!(finished.expect("server did not exit in time").unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_broken_pin_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("pins.sqlite");
+        let state = state_with_files(&[]);
+        state.lock().await.pin_cache = Some(Arc::new(PinCache::open(&db_path).unwrap()));
+        let app = create_router(state).await;
+
+        let response = app.clone().oneshot(Request::get("/health").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!((body["status"].as_str(), body["db"].as_str()), (Some("healthy"), Some("up")));
+
+        rusqlite::Connection::open(&db_path).unwrap().execute_batch("DROP TABLE pinned_files;").unwrap();
+        let response = app.oneshot(Request::get("/health").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!((body["status"].as_str(), body["db"].as_str()), (Some("degraded"), Some("down")));
+    }
+
+    #[tokio::test]
+    async fn test_cors_allows_only_listed_origins() {
+        assert!(parse_cors_origins("https://example.com/path").is_err());
+        assert!(parse_cors_origins("example.com").is_err());
+        assert!(parse_cors_origins("*").is_err());
+
+        let state = state_with_files(&[]);
+        state.lock().await.cors_origins = Some(parse_cors_origins("https://app.example.com, http://localhost:3000").unwrap());
+        let app = create_router(state).await;
+        let from = |origin: &'static str| Request::get("/status").header(header::ORIGIN, origin).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(from("https://app.example.com")).await.unwrap();
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://app.example.com");
+        let response = app.oneshot(from("https://evil.example.net")).await.unwrap();
+        assert!(response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_compress_enforces_daily_quota() {
+        let state = state_with_files(&[]);
+        let today = chrono::Utc::now().date_naive();
+        {
+            let mut state_guard = state.lock().await;
+            state_guard.max_uploads_per_day = Some(2);
+            // Two uploads already made today by this key
+            let owner = upload_owner(
+                &HeaderMap::from_iter([(header::HeaderName::from_static(API_KEY_HEADER), HeaderValue::from_static("k1"))]),
+                None,
+            );
+            assert!(state_guard.upload_quota.try_record(&owner, 2, today));
+            assert!(state_guard.upload_quota.try_record(&owner, 2, today));
+        }
+        let app = create_router(state.clone()).await;
+
+        let response = app.clone().oneshot(multipart_request("/compress", "a.txt", b"data", Some("k1"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let body: CompressionResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert!(body.error.unwrap().starts_with("Daily upload limit of 2 reached"));
+
+        // Owners are counted separately
+        let mut quota = UploadQuota::default();
+        assert!(quota.try_record("owner:alice", 1, today));
+        assert!(!quota.try_record("owner:alice", 1, today));
+        assert!(quota.try_record("owner:bob", 1, today));
+        // Counts reset once the UTC day changes
+        assert!(quota.try_record("owner:alice", 1, today.succ_opt().unwrap()));
+
+        assert_eq!(upload_owner(&HeaderMap::new(), Some(" alice ")), "owner:alice");
+        assert_eq!(upload_owner(&HeaderMap::new(), None), "anonymous");
+    }
+
+    #[tokio::test]
+    async fn test_compress_rejects_malformed_multipart() {
+        let response = create_router(state_with_files(&[])).await
+            .oneshot(
+                Request::post("/compress")
+                    .header(header::CONTENT_TYPE, "multipart/form-data; boundary=missing")
+                    .body(Body::from("--other\r\nnot a multipart body"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: CompressionResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert!(!body.success);
+        assert!(body.error.unwrap().starts_with("Invalid multipart body"));
+    }
+
+    #[tokio::test]
+    async fn test_expanding_input_pins_original() {
+        // xorshift bytes: no redundancy for the pipeline to exploit
+        let mut x = 0x2545_f491_u32;
+        let data: Vec<u8> = (0..512)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                x as u8
+            })
+            .collect();
+        let storage = MockStorage::new("bafy-test", CallLog::default());
+
+        let response = process_file_compression_with("noise.bin", &data, true, &storage, None, None).await.unwrap();
+
+        let pinned = storage.pinned.lock().unwrap().clone();
+        assert_eq!(pinned, vec![compress_file_with_codec(&data, Codec::None).unwrap()]);
+        assert_eq!(decompress_file(&pinned[0]).unwrap(), data);
+        assert_eq!(*storage.calls.lock().unwrap(), vec!["pin noise.bin".to_string()]);
+        assert_eq!(response.compression_beneficial, Some(false));
+        assert_eq!(response.compressed_size, Some(pinned[0].len()));
+        assert_eq!(response.compression_ratio, Some(0.0));
+        assert_eq!(compressed_by(data.len(), data.len()), 0);
+        assert_eq!(response.ipfs_cid.as_deref(), Some("bafy-test"));
+    }
+
+    #[tokio::test]
+    async fn test_decompress_restores_what_compress_pinned() {
+        let app = create_router(state_with_files(&[])).await;
+        let binary: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+        let text = b"pinned bytes come back from /decompress unchanged\n".repeat(32);
+        for (abort_on_expansion, data) in [(true, binary.clone()), (false, binary), (true, text)] {
+            let storage = MockStorage::new("bafy-test", CallLog::default());
+            process_file_compression_with("data.bin", &data, abort_on_expansion, &storage, None, None).await.unwrap();
+            let pinned = storage.pinned.lock().unwrap().pop().unwrap();
+
+            let response = app.clone().oneshot(multipart_request("/decompress", "data.bin", &pinned, None)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            assert_eq!(body.to_vec(), data);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_count_drops_when_a_request_is_cancelled() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route("/slow", get(std::future::pending::<&'static str>))
+            .layer(middleware::from_fn_with_state(in_flight.clone(), count_in_flight));
+
+        let request = tokio::spawn(app.oneshot(Request::get("/slow").body(Body::empty()).unwrap()));
+        while in_flight.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        request.abort();
+        assert!(request.await.unwrap_err().is_cancelled());
+        assert_eq!(in_flight.load(Ordering::SeqCst), 0);
+    }
+
+    /// Serves `compress_over_socket` on a local port
+    async fn spawn_socket_server(storage: MockStorage) -> std::net::SocketAddr {
+        let app = Router::new()
+            .route(
+                "/ws/compress",
+                get(move |State(state): State<SharedState>, headers: HeaderMap, ws: WebSocketUpgrade| async move {
+                    ws.on_upgrade(move |socket| compress_over_socket(socket, state, headers, storage))
+                }),
+            )
+            .with_state(state_with_files(&[]));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        addr
+    }
+
+    /// Reads text messages until the server closes, returning the final `CompressionResponse`
+    async fn socket_result(
+        client: &mut tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
+    ) -> Option<CompressionResponse> {
+        use futures_util::StreamExt;
+        use tokio_tungstenite::tungstenite::Message as ClientMessage;
+
+        while let Some(Ok(message)) = client.next().await {
+            if let ClientMessage::Text(text) = message {
+                if let Ok(response) = serde_json::from_str::<CompressionResponse>(&text) {
+                    return Some(response);
+                }
+            }
+        }
+        None
+    }
+
+    #[tokio::test]
+    async fn test_websocket_compress_streams_progress_then_result() {
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message as ClientMessage;
+
+        let calls = CallLog::default();
+        let addr = spawn_socket_server(MockStorage::new("QmSocket", calls.clone())).await;
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws/compress", addr)).await.unwrap();
+        let data = b"stream me to the server, please. ".repeat(64);
+        let start = serde_json::json!({ "file_name": "notes.txt", "size": data.len() });
+        client.send(ClientMessage::Text(start.to_string())).await.unwrap();
+        for chunk in data.chunks(1000) {
+            client.send(ClientMessage::Binary(chunk.to_vec())).await.unwrap();
+        }
+
+        let mut stages = Vec::new();
+        let mut result = None;
+        while let Some(message) = client.next().await {
+            let text = match message.unwrap() {
+                ClientMessage::Text(text) => text,
+                ClientMessage::Close(_) => break,
+                _ => continue,
+            };
+            let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+            if value.get("stage").is_some() {
+                let event: CompressionProgress = serde_json::from_value(value).unwrap();
+                assert!(event.bytes_done <= event.bytes_total);
+                stages.push(event.stage);
+            } else {
+                result = Some(serde_json::from_value::<CompressionResponse>(value).unwrap());
+            }
+        }
+
+        for stage in ["upload", "compression", "ipfs_pinning"] {
+            assert!(stages.iter().any(|s| s == stage), "no {} event in {:?}", stage, stages);
+        }
+        let result = result.expect("no final result");
+        assert!(result.success);
+        assert_eq!(result.ipfs_cid.as_deref(), Some("QmSocket"));
+        assert_eq!(*calls.lock().unwrap(), vec!["pin notes.txt".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_websocket_compress_rejects_oversized_uploads() {
+        use futures_util::SinkExt;
+        use tokio_tungstenite::tungstenite::Message as ClientMessage;
+
+        let calls = CallLog::default();
+        let addr = spawn_socket_server(MockStorage::new("QmUnused", calls.clone())).await;
+        let url = format!("ws://{}/ws/compress", addr);
+
+        // An announced size above the limit is refused before any bytes are read
+        let (mut client, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        let start = serde_json::json!({ "file_name": "huge.bin", "size": u64::MAX });
+        client.send(ClientMessage::Text(start.to_string())).await.unwrap();
+        let result = socket_result(&mut client).await.expect("no result");
+        assert!(!result.success);
+        assert!(result.error.unwrap_or_default().contains("limited to"));
+
+        // Sending past the announced size stops the transfer
+        let (mut client, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        let start = serde_json::json!({ "file_name": "liar.txt", "size": 10 });
+        client.send(ClientMessage::Text(start.to_string())).await.unwrap();
+        client.send(ClientMessage::Binary(vec![b'x'; 20])).await.unwrap();
+        let result = socket_result(&mut client).await.expect("no result");
+        assert!(!result.success);
+        assert!(result.error.unwrap_or_default().contains("more than the announced 10 bytes"));
+        assert!(calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_identical_upload_is_pinned_once() {
+        let storage = MockStorage::new("bafy-dedup", CallLog::default());
+        let cache = PinCache::open_in_memory().unwrap();
+        let data = b"same bytes, uploaded twice".to_vec();
+
+        let first = process_file_compression_with("a.txt", &data, true, &storage, Some(&cache), None).await.unwrap();
+        let second = process_file_compression_with("b.txt", &data, true, &storage, Some(&cache), None).await.unwrap();
+
+        assert_eq!(*storage.calls.lock().unwrap(), vec!["pin a.txt".to_string()]);
+        assert_eq!(first.ipfs_cid.as_deref(), Some("bafy-dedup"));
+        assert_eq!(second.ipfs_cid, first.ipfs_cid);
+    }
+}
@@ -0,0 +1,152 @@
+// Sidecar Module
+// Per-file compression parameters read from an optional `<file>.ssqconf` JSON
+// file next to the input, overriding the global config for that file only
+
+use serde::Deserialize;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::compression::Codec;
+use crate::config::CompressionConfig;
+
+/// Contents of a `.ssqconf` sidecar; every field is optional
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CompressionSidecar {
+    /// Codec `compress` writes with, overriding `compression_method`
+    pub codec: Option<Codec>,
+    pub chunk_size: Option<usize>,
+}
+
+/// Compression parameters after applying a sidecar over the global config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressionParams {
+    pub codec: Codec,
+    /// Fixed chunk size, or `None` to pick one from the content
+    pub chunk_size: Option<usize>,
+    /// The sidecar that was applied, if any
+    pub sidecar: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub enum SidecarError {
+    IoError(PathBuf, std::io::Error),
+    ParseError(PathBuf, serde_json::Error),
+    InvalidValue(PathBuf, String),
+}
+
+impl fmt::Display for SidecarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SidecarError::IoError(path, e) => write!(f, "Could not read sidecar {}: {}", path.display(), e),
+            SidecarError::ParseError(path, e) => write!(f, "Invalid sidecar {}: {}", path.display(), e),
+            SidecarError::InvalidValue(path, msg) => write!(f, "Invalid sidecar {}: {}", path.display(), msg),
+        }
+    }
+}
+
+impl Error for SidecarError {}
+
+/// Sidecar location for `input` (e.g. `photo.png.ssqconf`)
+pub fn sidecar_path_for(input: &Path) -> PathBuf {
+    let mut path = input.as_os_str().to_os_string();
+    path.push(".ssqconf");
+    PathBuf::from(path)
+}
+
+/// Reads the sidecar for `input`, or `None` when there isn't one
+pub fn load_sidecar(input: &Path) -> Result<Option<CompressionSidecar>, SidecarError> {
+    let path = sidecar_path_for(input);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(SidecarError::IoError(path, e)),
+    };
+    let sidecar: CompressionSidecar =
+        serde_json::from_str(&content).map_err(|e| SidecarError::ParseError(path.clone(), e))?;
+    if sidecar.chunk_size == Some(0) {
+        return Err(SidecarError::InvalidValue(path, "chunk_size must be greater than zero".to_string()));
+    }
+    Ok(Some(sidecar))
+}
+
+impl CompressionParams {
+    /// The global config's parameters, for input that has no sidecar (such as stdin)
+    pub fn from_config(config: &CompressionConfig) -> Self {
+        Self {
+            codec: config.compression_method.parse().unwrap_or(Codec::Rle),
+            chunk_size: None,
+            sidecar: None,
+        }
+    }
+}
+
+/// Resolves the parameters for `input`: its sidecar's values where set, the global config otherwise
+pub fn compression_params_for(input: &Path, config: &CompressionConfig) -> Result<CompressionParams, SidecarError> {
+    let defaults = CompressionParams::from_config(config);
+    let Some(sidecar) = load_sidecar(input)? else {
+        return Ok(defaults);
+    };
+    Ok(CompressionParams {
+        codec: sidecar.codec.unwrap_or(defaults.codec),
+        chunk_size: sidecar.chunk_size,
+        sidecar: Some(sidecar_path_for(input)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compression::{compress_bytes, configured_chunk_size, decompress_bytes, CompressOptions};
+    use crate::config::get_config;
+
+    #[test]
+    fn test_sidecar_applies_to_its_file_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let tuned = dir.path().join("tuned.txt");
+        let plain = dir.path().join("plain.txt");
+        let data = b"abcd".repeat(64);
+        fs::write(&tuned, &data).unwrap();
+        fs::write(&plain, &data).unwrap();
+        fs::write(sidecar_path_for(&tuned), r#"{"codec": "dictionary", "chunk_size": 4}"#).unwrap();
+
+        let config = &get_config().compression;
+        let params = compression_params_for(&tuned, config).unwrap();
+        assert_eq!(params.codec, Codec::Dictionary);
+        assert_eq!(params.chunk_size, Some(4));
+        assert_eq!(params.sidecar, Some(dir.path().join("tuned.txt.ssqconf")));
+
+        let defaults = compression_params_for(&plain, config).unwrap();
+        assert_eq!(defaults.codec, Codec::configured());
+        assert_eq!(defaults.chunk_size, None);
+        assert_eq!(defaults.sidecar, None);
+
+        // The two files end up in different codecs, the way `compress` writes them
+        let compress = |params: &CompressionParams| {
+            let chunk_size = params.chunk_size.unwrap_or_else(configured_chunk_size);
+            compress_bytes(&data, CompressOptions { codec: params.codec, chunk_size, gzip: false }).unwrap()
+        };
+        let tuned_output = compress(&params);
+        let plain_output = compress(&defaults);
+        assert_eq!(tuned_output.codec, Codec::Dictionary);
+        assert_ne!(tuned_output.codec, plain_output.codec);
+        assert_eq!(decompress_bytes(&tuned_output.compressed).unwrap(), data);
+    }
+
+    #[test]
+    fn test_invalid_sidecars_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input.bin");
+        fs::write(sidecar_path_for(&input), r#"{"codec": "zstd"}"#).unwrap();
+        assert!(matches!(load_sidecar(&input), Err(SidecarError::ParseError(..))));
+        // The backend and level the codec replaced are no longer accepted
+        fs::write(sidecar_path_for(&input), r#"{"backend": "chunk-mapping"}"#).unwrap();
+        assert!(matches!(load_sidecar(&input), Err(SidecarError::ParseError(..))));
+        fs::write(sidecar_path_for(&input), r#"{"chunk_size": 0}"#).unwrap();
+        assert!(matches!(load_sidecar(&input), Err(SidecarError::InvalidValue(..))));
+        fs::write(sidecar_path_for(&input), r#"{"chunk": 4}"#).unwrap();
+        assert!(matches!(load_sidecar(&input), Err(SidecarError::ParseError(..))));
+    }
+}
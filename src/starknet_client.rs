@@ -1,20 +1,29 @@
-use crate::utils::short_string_to_felt;
+use crate::utils::{felt_to_short_string, felts_to_string, short_string_to_felt, string_to_felts};
 use starknet::accounts::Call;
 use starknet::accounts::{Account, SingleOwnerAccount, ConnectedAccount};
-use starknet::core::types::{BlockId, BlockTag, FieldElement, FunctionCall};
+use starknet::core::types::{BlockId, BlockTag, EventFilter, ExecutionResult, FieldElement, FunctionCall, MaybePendingTransactionReceipt, PriceUnit, StarknetError};
 use starknet::core::utils::get_selector_from_name;
-use starknet::providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider};
+use starknet::providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider, ProviderError};
 use starknet::signers::{LocalWallet, SigningKey};
 use std::env;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
 use url::Url;
 use dotenvy::dotenv;
+use crate::output::status;
+
+/// Connects to the RPC node named by `RPC_URL`
+fn get_provider() -> Result<JsonRpcClient<HttpTransport>, Box<dyn std::error::Error + Send + Sync>> {
+    dotenv().ok(); // Load .env
+    let rpc_url = env::var("RPC_URL").map_err(|_| "RPC_URL not set in .env")?;
+    Ok(JsonRpcClient::new(HttpTransport::new(Url::parse(&rpc_url)?)))
+}
 
 /// Loads the StarkNet account from the environment.
 pub async fn get_account() -> Result<SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>, Box<dyn std::error::Error + Send + Sync>>
 {
-    dotenv().ok(); // Load .env
-    let rpc_url = env::var("RPC_URL").map_err(|_| "RPC_URL not set in .env")?;
-    let provider = JsonRpcClient::new(HttpTransport::new(Url::parse(&rpc_url)?));
+    let provider = get_provider()?;
 
     let private_key = env::var("PRIVATE_KEY").map_err(|_| "PRIVATE_KEY not set in .env")?;
     let private_key = FieldElement::from_hex_be(&private_key)
@@ -36,7 +45,393 @@ pub async fn get_account() -> Result<SingleOwnerAccount<JsonRpcClient<HttpTransp
     ))
 }
 
-/// Uploads compressed data metadata to the contract.
+/// Arguments of the `store_compression_mapping` entrypoint, with strings already encoded as felts
+#[derive(Debug, Clone, Default)]
+pub struct UploadCalldataParams {
+    /// The URI split into 31-byte short strings (see `string_to_felts`)
+    pub uri: Vec<FieldElement>,
+    pub file_format: FieldElement,
+    pub compressed_by: u8,
+    pub original_size: usize,
+    pub final_size: usize,
+    pub chunk_size: usize,
+    pub chunk_mappings: Vec<FieldElement>,
+    pub chunk_values: Vec<u8>,
+    pub byte_mappings: Vec<u8>,
+    pub byte_values: Vec<FieldElement>,
+    pub reconstruction_steps: Vec<FieldElement>,
+    pub metadata: Vec<FieldElement>,
+}
+
+impl UploadCalldataParams {
+    /// Parameters with `uri` and `file_format` encoded as felts and everything else empty
+    pub fn encode(uri: &str, file_format: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if uri.is_empty() {
+            return Err("URI must not be empty".into());
+        }
+        let file_format_felt = short_string_to_felt(file_format)
+            .map_err(|e| format!("short_string_to_felt failed for file_format '{}': {}", file_format, e))?;
+        Ok(Self { uri: string_to_felts(uri), file_format: file_format_felt, ..Default::default() })
+    }
+}
+
+/// Serializes upload parameters into calldata.
+/// The URI comes first as its felt count followed by its felts, then the scalars,
+/// then each array as its length followed by its elements.
+pub fn build_upload_calldata(params: &UploadCalldataParams) -> Vec<FieldElement> {
+    let mut calldata = vec![FieldElement::from(params.uri.len())]; // uri chunk count
+    calldata.extend(params.uri.iter().copied());                  // uri chunks
+    calldata.extend([
+        params.file_format,                          // file_format
+        FieldElement::from(params.compressed_by),    // compressed_by
+        FieldElement::from(params.original_size),    // original_size
+        FieldElement::from(params.final_size),       // final_size
+        FieldElement::from(params.chunk_size),       // chunk_size
+    ]);
+
+    // Add chunk_mappings array length and values
+    calldata.push(FieldElement::from(params.chunk_mappings.len()));
+    calldata.extend(params.chunk_mappings.iter().copied());
+
+    // Add chunk_values array length and values
+    calldata.push(FieldElement::from(params.chunk_values.len()));
+    calldata.extend(params.chunk_values.iter().map(|&v| FieldElement::from(v)));
+
+    // Add byte_mappings array length and values
+    calldata.push(FieldElement::from(params.byte_mappings.len()));
+    calldata.extend(params.byte_mappings.iter().map(|&v| FieldElement::from(v)));
+
+    // Add byte_values array length and values
+    calldata.push(FieldElement::from(params.byte_values.len()));
+    calldata.extend(params.byte_values.iter().copied());
+
+    // Add reconstruction_steps array length and values
+    calldata.push(FieldElement::from(params.reconstruction_steps.len()));
+    calldata.extend(params.reconstruction_steps.iter().copied());
+
+    // Add metadata array length and values
+    calldata.push(FieldElement::from(params.metadata.len()));
+    calldata.extend(params.metadata.iter().copied());
+
+    calldata
+}
+
+/// Builds the contract call for `entrypoint`, rejecting names that are not valid Cairo identifiers
+pub fn build_upload_call(
+    contract_address: FieldElement,
+    entrypoint: &str,
+    calldata: Vec<FieldElement>,
+) -> Result<Call, Box<dyn std::error::Error + Send + Sync>> {
+    let mut chars = entrypoint.chars();
+    let valid = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("Invalid contract entrypoint name '{}'", entrypoint).into());
+    }
+
+    Ok(Call {
+        to: contract_address,
+        selector: get_selector_from_name(entrypoint)?,
+        calldata,
+    })
+}
+
+/// Entrypoint receiving one piece of an upload too large for a single transaction
+pub const CHUNK_ENTRYPOINT: &str = "store_compression_mapping_chunk";
+/// Entrypoint called once every chunk of an upload has been stored
+pub const FINALIZE_ENTRYPOINT: &str = "finalize";
+
+/// Splits upload `calldata` into the calldata of successive chunk calls, each at most
+/// `max_felts` long. Every chunk carries the URI (length-prefixed), its index, the
+/// chunk total, then its slice of `calldata` (length-prefixed).
+pub fn plan_calldata_chunks(
+    uri: &[FieldElement],
+    calldata: &[FieldElement],
+    max_felts: usize,
+) -> Result<Vec<Vec<FieldElement>>, Box<dyn std::error::Error + Send + Sync>> {
+    // URI length and felts, index, total, slice length
+    let overhead = uri.len() + 4;
+    if max_felts <= overhead {
+        return Err(format!("max_calldata_felts ({}) leaves no room for data after {} felts of chunk header", max_felts, overhead).into());
+    }
+    let pieces: Vec<&[FieldElement]> = calldata.chunks(max_felts - overhead).collect();
+    let total = pieces.len();
+    Ok(pieces
+        .into_iter()
+        .enumerate()
+        .map(|(index, piece)| {
+            let mut chunk = vec![FieldElement::from(uri.len())];
+            chunk.extend_from_slice(uri);
+            chunk.extend([FieldElement::from(index), FieldElement::from(total), FieldElement::from(piece.len())]);
+            chunk.extend_from_slice(piece);
+            chunk
+        })
+        .collect())
+}
+
+/// Calldata of the `finalize` call closing a chunked upload
+pub fn build_finalize_calldata(uri: &[FieldElement], total: usize) -> Vec<FieldElement> {
+    let mut calldata = vec![FieldElement::from(uri.len())];
+    calldata.extend_from_slice(uri);
+    calldata.push(FieldElement::from(total));
+    calldata
+}
+
+/// Calls that store `calldata`: a single call to `entrypoint` when it fits in `max_felts`,
+/// otherwise one `store_compression_mapping_chunk` call per chunk followed by `finalize`
+pub fn build_upload_calls(
+    contract_address: FieldElement,
+    entrypoint: &str,
+    uri: &[FieldElement],
+    calldata: Vec<FieldElement>,
+    max_felts: usize,
+) -> Result<Vec<Call>, Box<dyn std::error::Error + Send + Sync>> {
+    if calldata.len() <= max_felts {
+        return Ok(vec![build_upload_call(contract_address, entrypoint, calldata)?]);
+    }
+    let chunks = plan_calldata_chunks(uri, &calldata, max_felts)?;
+    let total = chunks.len();
+    let mut calls = chunks
+        .into_iter()
+        .map(|chunk| build_upload_call(contract_address, CHUNK_ENTRYPOINT, chunk))
+        .collect::<Result<Vec<_>, _>>()?;
+    calls.push(build_upload_call(contract_address, FINALIZE_ENTRYPOINT, build_finalize_calldata(uri, total))?);
+    Ok(calls)
+}
+
+/// Fee and calldata size of an upload, estimated without sending anything
+#[derive(Debug, Clone, PartialEq)]
+pub struct UploadEstimate {
+    pub calldata_felts: usize,
+    /// `upload.starknet.max_calldata_felts` at the time of the estimate
+    pub max_calldata_felts: usize,
+    /// 1, or one per calldata chunk plus `finalize` when the calldata exceeds the cap
+    pub transactions: usize,
+    /// Total over every transaction
+    pub overall_fee: FieldElement,
+    /// `WEI` or `FRI`
+    pub fee_unit: String,
+}
+
+impl UploadEstimate {
+    pub fn exceeds_cap(&self) -> bool {
+        self.calldata_felts > self.max_calldata_felts
+    }
+}
+
+/// Reads the contract address from the environment
+fn contract_address() -> Result<FieldElement, Box<dyn std::error::Error + Send + Sync>> {
+    let contract_address = env::var("CONTRACT_ADDRESS").map_err(|_| "CONTRACT_ADDRESS not set in .env")?;
+    Ok(FieldElement::from_hex_be(&contract_address)?)
+}
+
+/// Estimates the fee of uploading `params` with `estimate_fee`; no transaction is sent
+pub async fn estimate_upload(params: &UploadCalldataParams) -> Result<UploadEstimate, Box<dyn std::error::Error + Send + Sync>> {
+    dotenv().ok();
+    let account = get_account().await?;
+    let starknet = &crate::config::get_config().upload.starknet;
+    let calldata = build_upload_calldata(params);
+    let calldata_felts = calldata.len();
+    let calls = build_upload_calls(contract_address()?, &starknet.entrypoint, &params.uri, calldata, starknet.max_calldata_felts)?;
+    let transactions = calls.len();
+
+    let mut overall_fee = FieldElement::ZERO;
+    let mut fee_unit = PriceUnit::Wei;
+    for call in calls {
+        let estimate = account.execute(vec![call]).estimate_fee().await?;
+        overall_fee += estimate.overall_fee;
+        fee_unit = estimate.unit;
+    }
+    Ok(UploadEstimate {
+        calldata_felts,
+        max_calldata_felts: starknet.max_calldata_felts,
+        transactions,
+        overall_fee,
+        fee_unit: match fee_unit {
+            PriceUnit::Wei => "WEI",
+            PriceUnit::Fri => "FRI",
+        }
+        .to_string(),
+    })
+}
+
+/// Upload metadata recorded on-chain by `store_compression_mapping`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnChainUpload {
+    pub uri: String,
+    pub file_format: String,
+    pub compressed_by: u8,
+    /// Size of the data that was compressed
+    pub original_size: u64,
+    /// Size of the compressed bytes that were pinned
+    pub final_size: u64,
+    /// IPFS CID of the pinned bytes, absent from events emitted before metadata was recorded
+    pub cid: Option<String>,
+    /// Whether the upload skipped ASCII conversion; unknown for events without metadata
+    pub raw: Option<bool>,
+    pub transaction_hash: FieldElement,
+}
+
+/// Bit of the metadata flags felt set for raw-mode uploads
+const METADATA_FLAG_RAW: u64 = 1;
+
+/// Encodes the `metadata` argument of an upload: a flags felt (bit 0 set for raw mode),
+/// then the IPFS CID as short-string felts
+pub fn encode_upload_metadata(cid: Option<&str>, raw: bool) -> Vec<FieldElement> {
+    let flags = if raw { METADATA_FLAG_RAW } else { 0 };
+    let mut metadata = vec![FieldElement::from(flags)];
+    metadata.extend(cid.map(string_to_felts).unwrap_or_default());
+    metadata
+}
+
+/// Inverse of `encode_upload_metadata`, returning the CID and the raw flag.
+/// Empty metadata decodes to neither.
+pub fn decode_upload_metadata(
+    metadata: &[FieldElement],
+) -> Result<(Option<String>, Option<bool>), Box<dyn std::error::Error + Send + Sync>> {
+    let Some((flags, cid)) = metadata.split_first() else {
+        return Ok((None, None));
+    };
+    let flags = u64::try_from(*flags).ok().filter(|flags| flags & !METADATA_FLAG_RAW == 0);
+    let flags = flags.ok_or_else(|| format!("Unknown metadata flags {}", metadata[0]))?;
+    let cid = if cid.is_empty() { None } else { Some(felts_to_string(cid)?) };
+    Ok((cid, Some(flags & METADATA_FLAG_RAW != 0)))
+}
+
+/// Event filter keys matching the `CompressionMappingStored` events of `uri`: the event
+/// selector, then the URI array as its length and felts
+pub fn mapping_event_keys(uri: &str) -> Result<Vec<Vec<FieldElement>>, Box<dyn std::error::Error + Send + Sync>> {
+    let uri_felts = string_to_felts(uri);
+    let mut keys = vec![vec![get_selector_from_name("CompressionMappingStored")?], vec![FieldElement::from(uri_felts.len())]];
+    keys.extend(uri_felts.into_iter().map(|felt| vec![felt]));
+    Ok(keys)
+}
+
+/// Decodes the data of a `CompressionMappingStored` event: file format, compressed_by,
+/// original size and final size, then the metadata array as its length and felts.
+/// Events from contracts that did not emit metadata end after the final size.
+pub fn parse_mapping_event(
+    uri: &str,
+    data: &[FieldElement],
+    transaction_hash: FieldElement,
+) -> Result<OnChainUpload, Box<dyn std::error::Error + Send + Sync>> {
+    let ([file_format, compressed_by, original_size, final_size], rest) = data.split_first_chunk::<4>().ok_or_else(|| {
+        format!("Expected at least 4 felts of event data for URI {}, got {}", uri, data.len())
+    })?;
+    let small = |felt: &FieldElement, name: &str| -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        u64::try_from(*felt).map_err(|_| format!("{} {} is out of range", name, felt).into())
+    };
+    let (cid, raw) = match rest.split_first() {
+        None => (None, None),
+        Some((len, metadata)) if small(len, "metadata length")? == metadata.len() as u64 => decode_upload_metadata(metadata)?,
+        Some(_) => return Err(format!("Event metadata for URI {} does not match its length", uri).into()),
+    };
+    Ok(OnChainUpload {
+        uri: uri.to_string(),
+        file_format: felt_to_short_string(*file_format)?,
+        compressed_by: u8::try_from(small(compressed_by, "compressed_by")?).map_err(|e| e.to_string())?,
+        original_size: small(original_size, "original_size")?,
+        final_size: small(final_size, "final_size")?,
+        cid,
+        raw,
+        transaction_hash,
+    })
+}
+
+/// Looks up the most recent upload of `uri` from the contract's `CompressionMappingStored`
+/// events
+pub async fn retrieve_data(uri: &str) -> Result<OnChainUpload, Box<dyn std::error::Error + Send + Sync>> {
+    if uri.is_empty() {
+        return Err("URI must not be empty".into());
+    }
+    let provider = get_provider()?;
+    let filter = EventFilter {
+        from_block: None,
+        to_block: Some(BlockId::Tag(BlockTag::Latest)),
+        address: Some(contract_address()?),
+        keys: Some(mapping_event_keys(uri)?),
+    };
+    let mut latest = None;
+    let mut continuation_token = None;
+    loop {
+        let page = provider.get_events(filter.clone(), continuation_token, 100).await?;
+        latest = page.events.into_iter().last().or(latest);
+        match page.continuation_token {
+            Some(token) => continuation_token = Some(token),
+            None => break,
+        }
+    }
+    let event = latest.ok_or_else(|| format!("No upload with URI {} found on-chain", uri))?;
+    parse_mapping_event(uri, &event.data, event.transaction_hash)
+}
+
+/// Where a sent transaction stands, as far as the node knows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxStatus {
+    /// No receipt yet
+    Pending,
+    Accepted,
+    Reverted { reason: String },
+}
+
+impl From<&MaybePendingTransactionReceipt> for TxStatus {
+    fn from(receipt: &MaybePendingTransactionReceipt) -> Self {
+        match receipt.execution_result() {
+            ExecutionResult::Succeeded => TxStatus::Accepted,
+            ExecutionResult::Reverted { reason } => TxStatus::Reverted { reason: reason.clone() },
+        }
+    }
+}
+
+/// Calls `poll` every `interval` until it reports the transaction accepted or reverted.
+/// Fails on a revert, a poll error, or when `timeout` passes first.
+pub async fn wait_for_transaction<P, Fut>(
+    tx_hash: FieldElement,
+    timeout: Duration,
+    interval: Duration,
+    mut poll: P,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+where
+    P: FnMut() -> Fut,
+    Fut: Future<Output = Result<TxStatus, Box<dyn std::error::Error + Send + Sync>>>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        match poll().await? {
+            TxStatus::Accepted => return Ok(()),
+            TxStatus::Reverted { reason } => {
+                return Err(format!("Transaction 0x{:x} reverted: {}", tx_hash, reason).into());
+            }
+            TxStatus::Pending => {}
+        }
+        if Instant::now() + interval > deadline {
+            return Err(format!(
+                "Transaction 0x{:x} not confirmed within {}s",
+                tx_hash,
+                timeout.as_secs()
+            )
+            .into());
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Fetches the receipt of `tx_hash`, treating an unknown hash as still pending
+async fn transaction_status<P: Provider + Sync>(
+    provider: &P,
+    tx_hash: FieldElement,
+) -> Result<TxStatus, Box<dyn std::error::Error + Send + Sync>> {
+    match provider.get_transaction_receipt(tx_hash).await {
+        Ok(receipt) => Ok(TxStatus::from(&receipt)),
+        Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound)) => Ok(TxStatus::Pending),
+        Err(e) => Err(format!("Failed to fetch receipt for 0x{:x}: {}", tx_hash, e).into()),
+    }
+}
+
+/// Uploads compressed data metadata to the contract, returning the transaction hash.
+/// With `wait_for_confirmation`, only returns once the transaction is accepted,
+/// and fails if it reverts or is not confirmed within the configured timeout.
+#[allow(clippy::too_many_arguments)]
 pub async fn upload_data(
     uri: &str,
     file_format: &str,
@@ -50,97 +445,59 @@ pub async fn upload_data(
     byte_values: Vec<FieldElement>,
     reconstruction_steps: Vec<FieldElement>,
     metadata: Vec<FieldElement>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    wait_for_confirmation: bool,
+) -> Result<FieldElement, Box<dyn std::error::Error + Send + Sync>> {
     dotenv().ok();
 
     let account = get_account().await?;
-    let contract_address = env::var("CONTRACT_ADDRESS").map_err(|_| "CONTRACT_ADDRESS not set in .env")?;
-    let contract_address = FieldElement::from_hex_be(&contract_address)?;
+    let contract_address = contract_address()?;
 
-    let uri_felt = match short_string_to_felt(uri) {
-        Ok(felt) => felt,
-        Err(e) => {
-            eprintln!("[short_string_to_felt ERROR] Failed string: '{}', error: {}", uri, e);
-            return Err(format!("short_string_to_felt failed for uri '{}': {}", uri, e).into());
-        }
-    };
-    let file_format_felt = match short_string_to_felt(file_format) {
-        Ok(felt) => felt,
-        Err(e) => {
-            eprintln!("[short_string_to_felt ERROR] Failed string: '{}', error: {}", file_format, e);
-            return Err(format!("short_string_to_felt failed for file_format '{}': {}", file_format, e).into());
-        }
+    let params = UploadCalldataParams {
+        compressed_by,
+        original_size,
+        final_size,
+        chunk_size,
+        chunk_mappings,
+        chunk_values,
+        byte_mappings,
+        byte_values,
+        reconstruction_steps,
+        metadata,
+        ..UploadCalldataParams::encode(uri, file_format)?
     };
-
-    // Store lengths before moving vectors
-    let chunk_mappings_len = chunk_mappings.len();
-    let chunk_values_len = chunk_values.len();
-    let byte_mappings_len = byte_mappings.len();
-    let byte_values_len = byte_values.len();
-    let reconstruction_steps_len = reconstruction_steps.len();
-    let metadata_len = metadata.len();
-
-    // Convert vectors to calldata format
-    let mut calldata = vec![
-        uri_felt,                                    // uri
-        file_format_felt,                            // file_format
-        FieldElement::from(compressed_by),           // compressed_by
-        FieldElement::from(original_size),           // original_size
-        FieldElement::from(final_size),              // final_size
-        FieldElement::from(chunk_size),              // chunk_size
-        FieldElement::from(chunk_mappings_len),      // chunk_mappings array length
-    ];
-    
-    // Add chunk_mappings
-    calldata.extend(chunk_mappings);
-    
-    // Add chunk_values array length and values
-    calldata.push(FieldElement::from(chunk_values_len));
-    calldata.extend(chunk_values.into_iter().map(FieldElement::from));
-    
-    // Add byte_mappings array length and values
-    calldata.push(FieldElement::from(byte_mappings_len));
-    calldata.extend(byte_mappings.into_iter().map(FieldElement::from));
-    
-    // Add byte_values array length and values
-    calldata.push(FieldElement::from(byte_values_len));
-    calldata.extend(byte_values);
-    
-    // Add reconstruction_steps array length and values
-    calldata.push(FieldElement::from(reconstruction_steps_len));
-    calldata.extend(reconstruction_steps);
-    
-    // Add metadata array length and values
-    calldata.push(FieldElement::from(metadata_len));
-    calldata.extend(metadata);
+    let calldata = build_upload_calldata(&params);
 
     // Debug: Print calldata structure
-    println!("[DEBUG] Calldata structure:");
-    println!("  uri: {}", uri_felt);
-    println!("  file_format: {}", file_format_felt);
-    println!("  compressed_by: {}", compressed_by);
-    println!("  original_size: {}", original_size);
-    println!("  final_size: {}", final_size);
-    println!("  chunk_size: {}", chunk_size);
-    println!("  chunk_mappings: {} items", chunk_mappings_len);
-    println!("  chunk_values: {} items", chunk_values_len);
-    println!("  byte_mappings: {} items", byte_mappings_len);
-    println!("  byte_values: {} items", byte_values_len);
-    println!("  reconstruction_steps: {} items", reconstruction_steps_len);
-    println!("  metadata: {} items", metadata_len);
-    println!("  Total calldata length: {}", calldata.len());
-
-    let call = Call {
-        to: contract_address,
-        selector: get_selector_from_name("store_compression_mapping")?,
-        calldata,
-    };
+    status!("[DEBUG] Calldata structure:");
+    status!("  uri: {} ({} felts)", uri, params.uri.len());
+    status!("  file_format: {}", params.file_format);
+    status!("  compressed_by: {}", compressed_by);
+    status!("  original_size: {}", original_size);
+    status!("  final_size: {}", final_size);
+    status!("  chunk_size: {}", chunk_size);
+    status!("  chunk_mappings: {} items", params.chunk_mappings.len());
+    status!("  chunk_values: {} items", params.chunk_values.len());
+    status!("  byte_mappings: {} items", params.byte_mappings.len());
+    status!("  byte_values: {} items", params.byte_values.len());
+    status!("  reconstruction_steps: {} items", params.reconstruction_steps.len());
+    status!("  metadata: {} items", params.metadata.len());
+    status!("  Total calldata length: {}", calldata.len());
+
+    let starknet = &crate::config::get_config().upload.starknet;
+    let entrypoint = &starknet.entrypoint;
+    let calldata_felts = calldata.len();
+    let mut calls = build_upload_calls(contract_address, entrypoint, &params.uri, calldata, starknet.max_calldata_felts)?;
+    if calls.len() > 1 {
+        status!("📦 Calldata of {} felts exceeds {}; sending {} chunks", calldata_felts, starknet.max_calldata_felts, calls.len() - 1);
+        return send_chunked(&account, calls, wait_for_confirmation).await;
+    }
+    let call = calls.remove(0);
 
     // Try to simulate the transaction first
     match account.provider().call(
         FunctionCall {
             contract_address,
-            entry_point_selector: get_selector_from_name("store_compression_mapping")?,
+            entry_point_selector: call.selector,
             calldata: call.calldata.clone(),
         },
         BlockId::Tag(BlockTag::Latest),
@@ -150,13 +507,280 @@ pub async fn upload_data(
             eprintln!("[CONTRACT ERROR] Full error details: {:?}", e);
             eprintln!("[CONTRACT ERROR] Error string: {}", e);
             if e.to_string().contains("Invalid message selector") {
-                return Err("Contract function 'store_compression_mapping' not found. Please verify the contract address and function name.".into());
+                return Err(format!("Contract function '{}' not found. Please verify the contract address and function name.", entrypoint).into());
             }
             return Err(format!("Transaction simulation failed: {}", e).into());
         }
     }
 
+    let tx_hash = send_call(&account, call, wait_for_confirmation).await?;
+    status!("✅ Upload successful! Transaction hash: 0x{:x}", tx_hash);
+    Ok(tx_hash)
+}
+
+type StarknetAccount = SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>;
+
+/// Sends `call` in its own transaction, optionally waiting until it is accepted
+async fn send_call(
+    account: &StarknetAccount,
+    call: Call,
+    wait_for_confirmation: bool,
+) -> Result<FieldElement, Box<dyn std::error::Error + Send + Sync>> {
     let tx = account.execute(vec![call]).send().await?;
-    println!("✅ Upload successful! Transaction hash: 0x{:x}", tx.transaction_hash);
-    Ok(())
+    status!("📨 Transaction sent: 0x{:x}", tx.transaction_hash);
+
+    if wait_for_confirmation {
+        let starknet = &crate::config::get_config().upload.starknet;
+        status!("⏳ Waiting for confirmation...");
+        wait_for_transaction(
+            tx.transaction_hash,
+            Duration::from_secs(starknet.confirmation_timeout_secs),
+            Duration::from_secs(starknet.confirmation_poll_interval_secs),
+            || transaction_status(account.provider(), tx.transaction_hash),
+        )
+        .await?;
+    }
+    Ok(tx.transaction_hash)
+}
+
+/// Sends the chunk calls of `build_upload_calls` one transaction each, then the closing
+/// `finalize`, returning the hash of the `finalize` transaction. A failure names the chunk
+/// that failed and how many were already stored.
+async fn send_chunked(
+    account: &StarknetAccount,
+    mut calls: Vec<Call>,
+    wait_for_confirmation: bool,
+) -> Result<FieldElement, Box<dyn std::error::Error + Send + Sync>> {
+    let finalize = calls.pop().ok_or("No calls to send")?;
+    let total = calls.len();
+    for (index, call) in calls.into_iter().enumerate() {
+        if let Err(e) = send_call(account, call, wait_for_confirmation).await {
+            return Err(format!("Calldata chunk {}/{} failed ({} already stored): {}", index + 1, total, index, e).into());
+        }
+        status!("  chunk {}/{} stored", index + 1, total);
+    }
+    let tx_hash = send_call(account, finalize, wait_for_confirmation)
+        .await
+        .map_err(|e| format!("Finalizing {} stored calldata chunks failed: {}", total, e))?;
+    status!("✅ Upload successful! Transaction hash: 0x{:x}", tx_hash);
+    Ok(tx_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn felt(v: u64) -> FieldElement {
+        FieldElement::from(v)
+    }
+
+    fn scalar_params() -> UploadCalldataParams {
+        UploadCalldataParams {
+            uri: vec![felt(0x1111)],
+            file_format: felt(0x2222),
+            compressed_by: 40,
+            original_size: 1000,
+            final_size: 600,
+            chunk_size: 8,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_calldata_with_empty_arrays() {
+        let calldata = build_upload_calldata(&scalar_params());
+        assert_eq!(
+            calldata,
+            vec![
+                felt(1), felt(0x1111), felt(0x2222), felt(40), felt(1000), felt(600), felt(8),
+                // Six empty arrays, each encoded as a zero length prefix
+                felt(0), felt(0), felt(0), felt(0), felt(0), felt(0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_calldata_with_single_element_arrays() {
+        let params = UploadCalldataParams {
+            chunk_mappings: vec![felt(7)],
+            chunk_values: vec![8],
+            byte_mappings: vec![9],
+            byte_values: vec![felt(10)],
+            reconstruction_steps: vec![felt(11)],
+            metadata: vec![felt(12)],
+            ..scalar_params()
+        };
+        let calldata = build_upload_calldata(&params);
+        assert_eq!(calldata.len(), 7 + 6 * 2);
+        assert_eq!(
+            &calldata[7..],
+            &[
+                felt(1), felt(7),
+                felt(1), felt(8),
+                felt(1), felt(9),
+                felt(1), felt(10),
+                felt(1), felt(11),
+                felt(1), felt(12),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_calldata_length_prefix_offsets() {
+        let params = UploadCalldataParams {
+            chunk_mappings: vec![felt(1), felt(2), felt(3)],
+            chunk_values: vec![],
+            byte_mappings: vec![4, 5],
+            byte_values: vec![felt(6)],
+            reconstruction_steps: vec![],
+            metadata: vec![felt(7), felt(8), felt(9), felt(10)],
+            ..scalar_params()
+        };
+        let calldata = build_upload_calldata(&params);
+
+        // Offsets of each length prefix: after the one-felt URI and 5 scalars, every array is len + elements
+        let chunk_mappings_at = 7;
+        let chunk_values_at = chunk_mappings_at + 1 + 3;
+        let byte_mappings_at = chunk_values_at + 1;
+        let byte_values_at = byte_mappings_at + 1 + 2;
+        let reconstruction_steps_at = byte_values_at + 1 + 1;
+        let metadata_at = reconstruction_steps_at + 1;
+
+        assert_eq!(calldata[chunk_mappings_at], felt(3));
+        assert_eq!(&calldata[chunk_mappings_at + 1..chunk_values_at], &[felt(1), felt(2), felt(3)]);
+        assert_eq!(calldata[chunk_values_at], felt(0));
+        assert_eq!(calldata[byte_mappings_at], felt(2));
+        assert_eq!(&calldata[byte_mappings_at + 1..byte_values_at], &[felt(4), felt(5)]);
+        assert_eq!(calldata[byte_values_at], felt(1));
+        assert_eq!(calldata[byte_values_at + 1], felt(6));
+        assert_eq!(calldata[reconstruction_steps_at], felt(0));
+        assert_eq!(calldata[metadata_at], felt(4));
+        assert_eq!(&calldata[metadata_at + 1..], &[felt(7), felt(8), felt(9), felt(10)]);
+        assert_eq!(calldata.len(), metadata_at + 1 + 4);
+    }
+
+    #[test]
+    fn test_long_uri_prefixed_by_chunk_count() {
+        let cid = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+        let params = UploadCalldataParams { uri: string_to_felts(cid), ..scalar_params() };
+        let calldata = build_upload_calldata(&params);
+        assert_eq!(calldata[0], felt(2));
+        assert_eq!(crate::utils::felts_to_string(&calldata[1..3]).unwrap(), cid);
+        assert_eq!(calldata[3], felt(0x2222));
+        assert_eq!(calldata.len(), 3 + 5 + 6);
+    }
+
+    type PollResult = std::future::Ready<Result<TxStatus, Box<dyn std::error::Error + Send + Sync>>>;
+
+    /// Poller returning `statuses` in order, counting calls
+    fn scripted(statuses: Vec<TxStatus>) -> (impl FnMut() -> PollResult, std::rc::Rc<std::cell::Cell<usize>>) {
+        let calls = std::rc::Rc::new(std::cell::Cell::new(0));
+        let counter = calls.clone();
+        let poll = move || {
+            let n = counter.get();
+            counter.set(n + 1);
+            std::future::ready(Ok(statuses.get(n).cloned().unwrap_or(TxStatus::Pending)))
+        };
+        (poll, calls)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_polls_until_accepted() {
+        let (poll, calls) = scripted(vec![TxStatus::Pending, TxStatus::Pending, TxStatus::Accepted]);
+        wait_for_transaction(felt(1), Duration::from_secs(120), Duration::from_secs(3), poll).await.unwrap();
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_reports_revert_and_timeout() {
+        let (poll, _) = scripted(vec![TxStatus::Pending, TxStatus::Reverted { reason: "Invalid compression percentage".into() }]);
+        let err = wait_for_transaction(felt(0xab), Duration::from_secs(120), Duration::from_secs(3), poll).await.unwrap_err();
+        assert!(err.to_string().contains("0xab reverted: Invalid compression percentage"), "{}", err);
+
+        let (poll, calls) = scripted(vec![]);
+        let err = wait_for_transaction(felt(0xab), Duration::from_secs(10), Duration::from_secs(3), poll).await.unwrap_err();
+        assert!(err.to_string().contains("not confirmed within 10s"), "{}", err);
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn test_calldata_chunks_stay_under_cap() {
+        let uri = vec![felt(0x1111)];
+        let calldata: Vec<FieldElement> = (0..23).map(felt).collect();
+
+        // 5 felts of header per chunk leave 5 data felts: 23 elements need 5 chunks
+        let chunks = plan_calldata_chunks(&uri, &calldata, 10).unwrap();
+        assert_eq!(chunks.len(), 5);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= 10));
+        assert_eq!(&chunks[4][..5], &[felt(1), felt(0x1111), felt(4), felt(5), felt(3)]);
+        let rejoined: Vec<FieldElement> = chunks.iter().flat_map(|chunk| chunk[5..].iter().copied()).collect();
+        assert_eq!(rejoined, calldata);
+
+        assert_eq!(plan_calldata_chunks(&uri, &calldata, 28).unwrap().len(), 1);
+        assert!(plan_calldata_chunks(&uri, &calldata, 5).is_err());
+        assert_eq!(build_finalize_calldata(&uri, 5), vec![felt(1), felt(0x1111), felt(5)]);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_small_upload_has_nonzero_fee() {
+        // Needs a funded account and deployed contract; only runs when RPC_URL is exported
+        if env::var("RPC_URL").is_err() {
+            return;
+        }
+        let params = UploadCalldataParams { chunk_size: 8, ..UploadCalldataParams::encode("0123456789abcdef", "txt").unwrap() };
+        let estimate = estimate_upload(&params).await.unwrap();
+        assert_ne!(estimate.overall_fee, FieldElement::ZERO);
+        assert_eq!(estimate.calldata_felts, build_upload_calldata(&params).len());
+        assert_eq!(estimate.transactions, 1);
+    }
+
+    #[test]
+    fn test_mapping_event_lookup_keys_and_data() {
+        let keys = mapping_event_keys("0123456789abcdef").unwrap();
+        assert_eq!(keys[0], vec![get_selector_from_name("CompressionMappingStored").unwrap()]);
+        assert_eq!(keys[1], vec![felt(1)]);
+        assert_eq!(keys[2], string_to_felts("0123456789abcdef"));
+
+        let data = [short_string_to_felt("txt").unwrap(), felt(40), felt(1000), felt(600)];
+        let upload = parse_mapping_event("0123456789abcdef", &data, felt(0x99)).unwrap();
+        assert_eq!(upload.file_format, "txt");
+        assert_eq!((upload.compressed_by, upload.original_size, upload.final_size), (40, 1000, 600));
+        assert_eq!((upload.cid, upload.raw), (None, None));
+        assert!(parse_mapping_event("0123456789abcdef", &data[..3], felt(0x99)).is_err());
+    }
+
+    #[test]
+    fn test_mapping_event_carries_the_cid() {
+        let cid = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+        let metadata = encode_upload_metadata(Some(cid), true);
+        assert_eq!(metadata.len(), 3);
+        let mut data = vec![short_string_to_felt("txt").unwrap(), felt(40), felt(1000), felt(600), FieldElement::from(metadata.len())];
+        data.extend(metadata);
+        let upload = parse_mapping_event("0123456789abcdef", &data, felt(0x99)).unwrap();
+        assert_eq!(upload.cid.as_deref(), Some(cid));
+        assert_eq!(upload.raw, Some(true));
+        assert!(parse_mapping_event("0123456789abcdef", &data[..data.len() - 1], felt(0x99)).is_err());
+
+        assert_eq!(decode_upload_metadata(&encode_upload_metadata(None, false)).unwrap(), (None, Some(false)));
+        assert_eq!(decode_upload_metadata(&[]).unwrap(), (None, None));
+        assert!(decode_upload_metadata(&[felt(2)]).is_err());
+        let data = [short_string_to_felt("txt").unwrap(), felt(40), felt(1000), felt(600), felt(0)];
+        assert_eq!(parse_mapping_event("0123456789abcdef", &data, felt(0x99)).unwrap().cid, None);
+    }
+
+    #[test]
+    fn test_custom_entrypoint_used_in_call() {
+        let call = build_upload_call(felt(0x1234), "store_mapping_v2", vec![felt(1)]).unwrap();
+        assert_eq!(call.selector, get_selector_from_name("store_mapping_v2").unwrap());
+        assert_ne!(call.selector, get_selector_from_name("store_compression_mapping").unwrap());
+        assert_eq!(call.to, felt(0x1234));
+        assert_eq!(call.calldata, vec![felt(1)]);
+    }
+
+    #[test]
+    fn test_invalid_entrypoint_rejected() {
+        for name in ["", "2fast", "store-mapping", "store mapping"] {
+            assert!(build_upload_call(felt(1), name, vec![]).is_err(), "{:?} should be rejected", name);
+        }
+    }
 }
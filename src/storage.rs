@@ -0,0 +1,189 @@
+// Storage Module
+// Traits for the two network steps of an upload, pinning the compressed bytes and
+// recording the upload on-chain, so the pipeline can run against mocks in tests.
+
+use std::future::Future;
+
+use starknet::core::types::FieldElement;
+
+use crate::ipfs_client::{pin_file_to_ipfs, pin_file_to_ipfs_with_progress, unpin_file_from_ipfs, IpfsError, PinProgress};
+use crate::starknet_client::{encode_upload_metadata, estimate_upload, upload_data, UploadCalldataParams, UploadEstimate};
+use crate::upload_manifest::UploadManifest;
+
+/// Error returned by a chain submission
+pub type ChainError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Somewhere to pin file bytes, returning a content identifier
+pub trait Storage {
+    fn pin(&self, data: Vec<u8>, file_name: String) -> impl Future<Output = Result<String, IpfsError>> + Send;
+
+    /// Like `pin`, calling `on_progress` with `(bytes_sent, total)` where the backend can
+    /// report it. The default reports nothing.
+    fn pin_with_progress(
+        &self,
+        data: Vec<u8>,
+        file_name: String,
+        on_progress: PinProgress,
+    ) -> impl Future<Output = Result<String, IpfsError>> + Send {
+        let _ = on_progress;
+        self.pin(data, file_name)
+    }
+
+    /// Removes the pin on `cid`, failing with `ALREADY_UNPINNED` when there is none
+    fn unpin(&self, cid: String) -> impl Future<Output = Result<(), IpfsError>> + Send;
+}
+
+/// Records an upload on-chain, returning the transaction hash
+pub trait ChainUploader {
+    fn submit(&self, manifest: &UploadManifest) -> impl Future<Output = Result<FieldElement, ChainError>> + Send;
+
+    /// Predicts the fee and calldata size of submitting `manifest`, without sending anything
+    fn estimate(&self, manifest: &UploadManifest) -> impl Future<Output = Result<UploadEstimate, ChainError>> + Send;
+}
+
+/// Calldata parameters submitted for `manifest`. Minimal arrays keep the calldata small;
+/// the full mapping lives off-chain.
+pub fn manifest_calldata_params(manifest: &UploadManifest) -> Result<UploadCalldataParams, ChainError> {
+    Ok(UploadCalldataParams {
+        compressed_by: manifest.compressed_by,
+        original_size: manifest.original_size,
+        final_size: manifest.compressed_size,
+        chunk_size: manifest.chunk_size,
+        chunk_mappings: vec![FieldElement::from(0u32)], // Placeholder
+        chunk_values: vec![0u8], // Placeholder
+        byte_mappings: vec![0u8], // Placeholder
+        byte_values: vec![FieldElement::from(0u32)], // Placeholder
+        reconstruction_steps: vec![FieldElement::from(0u32)], // Placeholder
+        metadata: encode_upload_metadata(manifest.ipfs_cid.as_deref(), manifest.raw),
+        ..UploadCalldataParams::encode(&manifest.uri, &manifest.file_type)?
+    })
+}
+
+/// Pins to IPFS through Pinata
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PinataStorage;
+
+impl Storage for PinataStorage {
+    async fn pin(&self, data: Vec<u8>, file_name: String) -> Result<String, IpfsError> {
+        pin_file_to_ipfs(&data, &file_name).await
+    }
+
+    async fn pin_with_progress(&self, data: Vec<u8>, file_name: String, on_progress: PinProgress) -> Result<String, IpfsError> {
+        pin_file_to_ipfs_with_progress(&data, &file_name, move |sent, total| on_progress(sent, total)).await
+    }
+
+    async fn unpin(&self, cid: String) -> Result<(), IpfsError> {
+        unpin_file_from_ipfs(&cid).await
+    }
+}
+
+/// Submits to the configured Starknet contract
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StarknetUploader;
+
+impl ChainUploader for StarknetUploader {
+    fn submit(&self, manifest: &UploadManifest) -> impl Future<Output = Result<FieldElement, ChainError>> + Send {
+        let manifest = manifest.clone();
+        async move {
+            let params = manifest_calldata_params(&manifest)?;
+            upload_data(
+                &manifest.uri,
+                &manifest.file_type,
+                params.compressed_by,
+                params.original_size,
+                params.final_size,
+                params.chunk_size,
+                params.chunk_mappings,
+                params.chunk_values,
+                params.byte_mappings,
+                params.byte_values,
+                params.reconstruction_steps,
+                params.metadata,
+                true,
+            )
+            .await
+        }
+    }
+
+    fn estimate(&self, manifest: &UploadManifest) -> impl Future<Output = Result<UploadEstimate, ChainError>> + Send {
+        let params = manifest_calldata_params(manifest);
+        async move { estimate_upload(&params?).await }
+    }
+}
+
+/// In-memory implementations for tests, recording every call in order
+pub mod mock {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Shared, ordered log of calls made to the mocks
+    pub type CallLog = Arc<Mutex<Vec<String>>>;
+
+    /// Storage that returns a fixed CID and keeps the pinned bytes
+    #[derive(Debug, Clone, Default)]
+    pub struct MockStorage {
+        pub cid: String,
+        pub calls: CallLog,
+        pub pinned: Arc<Mutex<Vec<Vec<u8>>>>,
+    }
+
+    impl MockStorage {
+        pub fn new(cid: &str, calls: CallLog) -> Self {
+            Self { cid: cid.to_string(), calls, pinned: Arc::default() }
+        }
+    }
+
+    impl Storage for MockStorage {
+        fn pin(&self, data: Vec<u8>, file_name: String) -> impl Future<Output = Result<String, IpfsError>> + Send {
+            self.calls.lock().unwrap().push(format!("pin {}", file_name));
+            self.pinned.lock().unwrap().push(data);
+            let cid = self.cid.clone();
+            async move { Ok(cid) }
+        }
+
+        fn unpin(&self, cid: String) -> impl Future<Output = Result<(), IpfsError>> + Send {
+            self.calls.lock().unwrap().push(format!("unpin {}", cid));
+            async move { Ok(()) }
+        }
+    }
+
+    /// Uploader that returns a fixed transaction hash and keeps the submitted manifests
+    #[derive(Debug, Clone, Default)]
+    pub struct MockChainUploader {
+        pub tx_hash: FieldElement,
+        pub calls: CallLog,
+        pub submitted: Arc<Mutex<Vec<UploadManifest>>>,
+    }
+
+    impl MockChainUploader {
+        pub fn new(tx_hash: FieldElement, calls: CallLog) -> Self {
+            Self { tx_hash, calls, submitted: Arc::default() }
+        }
+    }
+
+    impl ChainUploader for MockChainUploader {
+        fn submit(&self, manifest: &UploadManifest) -> impl Future<Output = Result<FieldElement, ChainError>> + Send {
+            self.calls.lock().unwrap().push(format!("submit {}", manifest.uri));
+            self.submitted.lock().unwrap().push(manifest.clone());
+            let tx_hash = self.tx_hash;
+            async move { Ok(tx_hash) }
+        }
+
+        /// Reports the real calldata size with a fee of 1 FRI per felt
+        fn estimate(&self, manifest: &UploadManifest) -> impl Future<Output = Result<UploadEstimate, ChainError>> + Send {
+            self.calls.lock().unwrap().push(format!("estimate {}", manifest.uri));
+            let estimate = manifest_calldata_params(manifest).map(|params| {
+                let calldata_felts = crate::starknet_client::build_upload_calldata(&params).len();
+                let max_calldata_felts = crate::config::get_config().upload.starknet.max_calldata_felts;
+                UploadEstimate {
+                    calldata_felts,
+                    max_calldata_felts,
+                    transactions: 1,
+                    overall_fee: FieldElement::from(calldata_felts),
+                    fee_unit: "FRI".to_string(),
+                }
+            });
+            async move { estimate }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+// Streaming Module
+// Compresses files chunk by chunk so memory use stays bounded by the read chunk
+// size rather than the file size.
+//
+// The output is a sequence of frames, one per input chunk: a little-endian u32
+// length followed by that chunk's `compress_file` output.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::ascii_converter::convert_to_printable_ascii;
+use crate::compression::{compress_file, decompress_file, CompressionError};
+use crate::config::get_config;
+
+/// Totals from a streaming run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamingStats {
+    /// Number of chunks read and written
+    pub chunks: usize,
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    /// Largest number of bytes held in memory for a single chunk, input and output together
+    pub peak_chunk_bytes: usize,
+}
+
+/// Compresses `input_path` into `output_path` in `performance.memory.file_read_chunk_size` chunks
+pub fn compress_file_streaming(
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+) -> Result<StreamingStats, CompressionError> {
+    let chunk_size = get_config().performance.memory.file_read_chunk_size;
+    compress_file_streaming_with(input_path, output_path, chunk_size)
+}
+
+/// Like `compress_file_streaming`, with an explicit chunk size
+pub fn compress_file_streaming_with(
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+    chunk_size: usize,
+) -> Result<StreamingStats, CompressionError> {
+    if chunk_size == 0 {
+        return Err(CompressionError::Custom("Chunk size must be greater than zero".to_string()));
+    }
+    let mut reader = BufReader::new(File::open(input_path)?);
+    let mut writer = BufWriter::new(File::create(output_path)?);
+    let mut buffer = vec![0u8; chunk_size];
+    let mut stats = StreamingStats::default();
+
+    loop {
+        let read = read_full(&mut reader, &mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        let (ascii, _) = convert_to_printable_ascii(&buffer[..read])
+            .map_err(|e| CompressionError::Custom(format!("ASCII conversion failed: {}", e)))?;
+        let packed = compress_file(&ascii)?;
+        let frame_len = u32::try_from(packed.len())
+            .map_err(|_| CompressionError::Custom(format!("Chunk of {} bytes is too large for a frame", packed.len())))?;
+        writer.write_all(&frame_len.to_le_bytes())?;
+        writer.write_all(&packed)?;
+
+        stats.chunks += 1;
+        stats.bytes_read += read as u64;
+        stats.bytes_written += 4 + packed.len() as u64;
+        stats.peak_chunk_bytes = stats.peak_chunk_bytes.max(buffer.len() + ascii.len() + packed.len());
+    }
+    writer.flush()?;
+    Ok(stats)
+}
+
+/// Reverses `compress_file_streaming`, one frame at a time. The ASCII conversion is
+/// not undone, so this restores the converted bytes.
+pub fn decompress_file_streaming(
+    input_path: impl AsRef<Path>,
+    output_path: impl AsRef<Path>,
+) -> Result<StreamingStats, CompressionError> {
+    let mut reader = BufReader::new(File::open(input_path)?);
+    let mut writer = BufWriter::new(File::create(output_path)?);
+    let mut stats = StreamingStats::default();
+    let mut len_bytes = [0u8; 4];
+
+    loop {
+        match read_full(&mut reader, &mut len_bytes)? {
+            0 => break,
+            4 => {}
+            _ => return Err(CompressionError::Custom("Truncated frame header".to_string())),
+        }
+        let frame_len = u32::from_le_bytes(len_bytes) as usize;
+        let mut packed = vec![0u8; frame_len];
+        if read_full(&mut reader, &mut packed)? != frame_len {
+            return Err(CompressionError::Custom(format!("Truncated frame {}", stats.chunks)));
+        }
+        let data = decompress_file(&packed)?;
+        writer.write_all(&data)?;
+
+        stats.chunks += 1;
+        stats.bytes_read += 4 + frame_len as u64;
+        stats.bytes_written += data.len() as u64;
+        stats.peak_chunk_bytes = stats.peak_chunk_bytes.max(packed.len() + data.len());
+    }
+    writer.flush()?;
+    Ok(stats)
+}
+
+/// Fills `buffer` unless the reader ends first, returning how many bytes were read
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streaming_keeps_memory_bounded_by_chunk_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("large.txt");
+        let compressed = dir.path().join("large.txt.ssq");
+        let restored = dir.path().join("restored.txt");
+
+        // 10 MB of printable text with runs, so the ASCII step is lossless
+        let size = 10 * 1024 * 1024;
+        let chunk_size = 64 * 1024;
+        let pattern = b"stark squeeze ----------------------- streaming ";
+        {
+            let mut writer = BufWriter::new(File::create(&input).unwrap());
+            let mut remaining = size;
+            while remaining > 0 {
+                let n = remaining.min(pattern.len());
+                writer.write_all(&pattern[..n]).unwrap();
+                remaining -= n;
+            }
+        }
+        assert_eq!(std::fs::metadata(&input).unwrap().len(), size as u64);
+
+        let stats = compress_file_streaming_with(&input, &compressed, chunk_size).unwrap();
+        assert_eq!(stats.chunks, size / chunk_size);
+        assert_eq!(stats.bytes_read, size as u64);
+        assert_eq!(stats.bytes_written, std::fs::metadata(&compressed).unwrap().len());
+        assert!(stats.bytes_written < stats.bytes_read);
+        // Read buffer, converted copy and packed output, each at most a chunk plus the headers
+        let headers = crate::compression::HEADER_LEN + 1;
+        assert!(stats.peak_chunk_bytes <= 3 * chunk_size + headers, "peak {}", stats.peak_chunk_bytes);
+
+        let restored_stats = decompress_file_streaming(&compressed, &restored).unwrap();
+        assert_eq!(restored_stats.chunks, stats.chunks);
+        assert_eq!(std::fs::read(&restored).unwrap(), std::fs::read(&input).unwrap());
+    }
+
+    #[test]
+    fn test_truncated_stream_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("in.txt");
+        let compressed = dir.path().join("in.txt.ssq");
+        std::fs::write(&input, b"aaaaaaaaaabbbbbbbbbb").unwrap();
+        compress_file_streaming_with(&input, &compressed, 8).unwrap();
+
+        let mut bytes = std::fs::read(&compressed).unwrap();
+        bytes.pop();
+        std::fs::write(&compressed, bytes).unwrap();
+        let result = decompress_file_streaming(&compressed, dir.path().join("out.txt"));
+        assert!(matches!(result, Err(CompressionError::Custom(_))));
+    }
+}
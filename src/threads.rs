@@ -0,0 +1,42 @@
+// Threads Module
+// Sizes the rayon thread pool behind the parallel conversion and hashing paths
+
+/// Worker count for a `--threads` value, where 0 means one per available core
+pub fn resolve_threads(requested: usize) -> usize {
+    if requested > 0 {
+        return requested;
+    }
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+/// Builds a standalone pool with `resolve_threads(requested)` workers
+pub fn build_thread_pool(requested: usize) -> Result<rayon::ThreadPool, String> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(resolve_threads(requested))
+        .build()
+        .map_err(|e| format!("Could not start {} worker threads: {}", resolve_threads(requested), e))
+}
+
+/// Sizes the global pool used by every parallel path. Must run before any parallel
+/// work starts; returns the number of workers
+pub fn init_global_thread_pool(requested: usize) -> Result<usize, String> {
+    let threads = resolve_threads(requested);
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build_global()
+        .map_err(|e| format!("Could not start {} worker threads: {}", threads, e))?;
+    Ok(threads)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_threads_means_all_cores() {
+        let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        assert_eq!(resolve_threads(0), cores);
+        assert_eq!(resolve_threads(3), 3);
+        assert_eq!(build_thread_pool(2).unwrap().current_num_threads(), 2);
+    }
+}
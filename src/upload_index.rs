@@ -0,0 +1,121 @@
+// Upload Index Module
+// Optional local SQLite record of CLI uploads, so CIDs and transaction hashes
+// can be looked up after they have scrolled past in the terminal
+
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+/// One recorded upload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadRecord {
+    pub file_name: String,
+    /// Short hash used as the upload URI
+    pub hash: String,
+    pub cid: Option<String>,
+    pub tx_hash: String,
+    pub original_size: u64,
+    pub compressed_size: u64,
+    /// RFC 3339 upload time
+    pub uploaded_at: String,
+}
+
+/// Handle to the local upload index database
+pub struct UploadIndex {
+    conn: Connection,
+}
+
+impl UploadIndex {
+    /// Opens the index at `path`, creating the file and table if needed
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<UploadIndex> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS uploads (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                file_name TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                cid TEXT,
+                tx_hash TEXT NOT NULL,
+                original_size INTEGER NOT NULL,
+                compressed_size INTEGER NOT NULL,
+                uploaded_at TEXT NOT NULL
+            );",
+        )?;
+        Ok(UploadIndex { conn })
+    }
+
+    /// Appends an upload record
+    pub fn insert(&self, record: &UploadRecord) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO uploads (file_name, hash, cid, tx_hash, original_size, compressed_size, uploaded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                record.file_name,
+                record.hash,
+                record.cid,
+                record.tx_hash,
+                record.original_size as i64,
+                record.compressed_size as i64,
+                record.uploaded_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Lists uploads, newest first. `query` matches a substring of the file name, hash, CID or tx hash.
+    pub fn search(&self, query: Option<&str>) -> rusqlite::Result<Vec<UploadRecord>> {
+        let pattern = format!("%{}%", query.unwrap_or(""));
+        let mut stmt = self.conn.prepare(
+            "SELECT file_name, hash, cid, tx_hash, original_size, compressed_size, uploaded_at
+             FROM uploads
+             WHERE file_name LIKE ?1 OR hash LIKE ?1 OR IFNULL(cid, '') LIKE ?1 OR tx_hash LIKE ?1
+             ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map(params![pattern], |row| {
+            Ok(UploadRecord {
+                file_name: row.get(0)?,
+                hash: row.get(1)?,
+                cid: row.get(2)?,
+                tx_hash: row.get(3)?,
+                original_size: row.get::<_, i64>(4)? as u64,
+                compressed_size: row.get::<_, i64>(5)? as u64,
+                uploaded_at: row.get(6)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(file_name: &str, cid: Option<&str>) -> UploadRecord {
+        UploadRecord {
+            file_name: file_name.to_string(),
+            hash: format!("hash-{}", file_name),
+            cid: cid.map(str::to_string),
+            tx_hash: "0xabc".to_string(),
+            original_size: 100,
+            compressed_size: 40,
+            uploaded_at: "2025-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_inserted_upload_is_queryable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("uploads.sqlite");
+        let index = UploadIndex::open(&path).unwrap();
+        index.insert(&record("photo.png", Some("QmPhoto"))).unwrap();
+        index.insert(&record("notes.txt", None)).unwrap();
+
+        // Reopen to make sure records are persisted
+        let index = UploadIndex::open(&path).unwrap();
+        let all = index.search(None).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0], record("notes.txt", None));
+
+        assert_eq!(index.search(Some("QmPhoto")).unwrap(), vec![record("photo.png", Some("QmPhoto"))]);
+        assert!(index.search(Some("missing")).unwrap().is_empty());
+    }
+}
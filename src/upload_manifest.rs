@@ -0,0 +1,197 @@
+// Upload Manifest Module
+// Records the state of an upload between IPFS pinning and Starknet submission
+// so an interrupted run can be finished without recompressing or re-pinning
+
+use serde::{Serialize, Deserialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UploadStage {
+    /// Compressed data is pinned (or pinning was attempted); nothing is on-chain yet
+    Pinned,
+    /// Metadata has been submitted to Starknet; the upload is complete
+    Submitted,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UploadManifest {
+    pub file_path: String,
+    pub uri: String,
+    pub file_type: String,
+    pub hash: String,
+    pub ipfs_cid: Option<String>,
+    pub compressed_by: u8,
+    pub original_size: usize,
+    pub compressed_size: usize,
+    pub chunk_size: usize,
+    /// The file was compressed without ASCII conversion
+    #[serde(default)]
+    pub raw: bool,
+    pub stage: UploadStage,
+    pub created_at: String,
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+    SerializationError(serde_json::Error),
+    IoError(std::io::Error),
+    AlreadySubmitted(String),
+    SubmissionFailed(String),
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ManifestError::SerializationError(e) => write!(f, "Serialization error: {}", e),
+            ManifestError::IoError(e) => write!(f, "IO error: {}", e),
+            ManifestError::AlreadySubmitted(uri) => write!(f, "Upload {} was already submitted to Starknet", uri),
+            ManifestError::SubmissionFailed(msg) => write!(f, "Starknet submission failed: {}", msg),
+        }
+    }
+}
+
+impl Error for ManifestError {}
+
+impl From<serde_json::Error> for ManifestError {
+    fn from(err: serde_json::Error) -> Self {
+        ManifestError::SerializationError(err)
+    }
+}
+
+impl From<std::io::Error> for ManifestError {
+    fn from(err: std::io::Error) -> Self {
+        ManifestError::IoError(err)
+    }
+}
+
+/// Returns the manifest path used for an uploaded file (e.g. `file.png.upload.json`)
+pub fn manifest_path_for(file_path: &Path) -> PathBuf {
+    let mut manifest_path = file_path.as_os_str().to_os_string();
+    manifest_path.push(".upload.json");
+    PathBuf::from(manifest_path)
+}
+
+/// Saves an upload manifest to a JSON file
+pub fn save_manifest(manifest: &UploadManifest, file_path: impl AsRef<Path>) -> Result<(), ManifestError> {
+    let json_content = serde_json::to_string_pretty(manifest)?;
+    fs::write(file_path, json_content)?;
+    Ok(())
+}
+
+/// Marks an upload as submitted by removing its manifest, so only interrupted uploads
+/// leave one behind. Falls back to rewriting it with `UploadStage::Submitted` when it
+/// can't be removed, which still stops it being resumed twice.
+pub fn complete_manifest(manifest: &mut UploadManifest, file_path: impl AsRef<Path>) -> Result<(), ManifestError> {
+    manifest.stage = UploadStage::Submitted;
+    match fs::remove_file(file_path.as_ref()) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(_) => save_manifest(manifest, file_path),
+    }
+}
+
+/// Loads an upload manifest from a JSON file
+pub fn load_manifest(file_path: impl AsRef<Path>) -> Result<UploadManifest, ManifestError> {
+    let content = fs::read_to_string(file_path)?;
+    let manifest: UploadManifest = serde_json::from_str(&content)?;
+    Ok(manifest)
+}
+
+/// Completes an interrupted upload by running only the Starknet submission step.
+///
+/// `submit` performs the on-chain call for the recorded metadata; compression and
+/// pinning are never repeated. On success the manifest is removed (see
+/// `complete_manifest`) so the same upload cannot be submitted twice.
+pub async fn resume_upload<F, Fut>(manifest_path: &Path, submit: F) -> Result<UploadManifest, ManifestError>
+where
+    F: FnOnce(UploadManifest) -> Fut,
+    Fut: Future<Output = Result<(), Box<dyn Error + Send + Sync>>>,
+{
+    let mut manifest = load_manifest(manifest_path)?;
+
+    if manifest.stage == UploadStage::Submitted {
+        return Err(ManifestError::AlreadySubmitted(manifest.uri));
+    }
+
+    submit(manifest.clone())
+        .await
+        .map_err(|e| ManifestError::SubmissionFailed(e.to_string()))?;
+
+    complete_manifest(&mut manifest, manifest_path)?;
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn pinned_manifest() -> UploadManifest {
+        UploadManifest {
+            file_path: "report.pdf".to_string(),
+            uri: "0123456789abcdef".to_string(),
+            file_type: "pdf".to_string(),
+            hash: "0123456789abcdef0123456789abcdef".to_string(),
+            ipfs_cid: Some("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_string()),
+            compressed_by: 40,
+            original_size: 1000,
+            compressed_size: 600,
+            chunk_size: 8,
+            raw: false,
+            stage: UploadStage::Pinned,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_submits_recorded_metadata_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.pdf.upload.json");
+        save_manifest(&pinned_manifest(), path.to_str().unwrap()).unwrap();
+
+        let submitted = Arc::new(Mutex::new(Vec::new()));
+        let recorder = submitted.clone();
+        let result = resume_upload(&path, |manifest| async move {
+            recorder.lock().unwrap().push(manifest);
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        let calls = submitted.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        // The submission uses the CID and sizes recorded at pin time rather than re-pinning
+        assert_eq!(calls[0].ipfs_cid, pinned_manifest().ipfs_cid);
+        assert_eq!(calls[0].uri, "0123456789abcdef");
+        assert_eq!(calls[0].compressed_size, 600);
+        assert_eq!(result.stage, UploadStage::Submitted);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_resume_rejects_submitted_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("done.upload.json");
+        let mut manifest = pinned_manifest();
+        manifest.stage = UploadStage::Submitted;
+        save_manifest(&manifest, path.to_str().unwrap()).unwrap();
+
+        let result = resume_upload(&path, |_| async { panic!("submit must not be called") }).await;
+        assert!(matches!(result, Err(ManifestError::AlreadySubmitted(_))));
+    }
+
+    #[tokio::test]
+    async fn test_failed_submission_keeps_pinned_stage() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("retry.upload.json");
+        save_manifest(&pinned_manifest(), path.to_str().unwrap()).unwrap();
+
+        let result = resume_upload(&path, |_| async { Err("rpc unavailable".into()) }).await;
+        assert!(matches!(result, Err(ManifestError::SubmissionFailed(_))));
+        assert_eq!(load_manifest(path.to_str().unwrap()).unwrap().stage, UploadStage::Pinned);
+    }
+}
@@ -14,17 +14,98 @@ pub fn short_string_to_felt(text: &str) -> Result<FieldElement, Box<dyn std::err
     // Convert to lowercase to ensure consistency
     let text = text.to_lowercase();
     
-    // Convert to bytes and create a felt from the first 31 bytes
-    let bytes = text.as_bytes();
-    let mut buf = [0u8; 31];
-    let len = bytes.len().min(31);
-    buf[..len].copy_from_slice(&bytes[..len]);
-    
-    // Convert to a number and then to FieldElement
-    let mut num = 0u128;
-    for &byte in buf.iter() {
-        num = (num << 8) | (byte as u128);
+    // Encode as a Cairo short string: the bytes read as a big-endian integer
+    FieldElement::from_byte_slice_be(text.as_bytes())
+        .map_err(|e| format!("Failed to encode short string: {}", e).into())
+}
+
+/// Decodes a Cairo short string felt back into text
+pub fn felt_to_short_string(felt: FieldElement) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let bytes = felt.to_bytes_be();
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    Ok(String::from_utf8(bytes[start..].to_vec())?)
+}
+
+/// Bytes that fit in one Cairo short string
+const SHORT_STRING_MAX_BYTES: usize = 31;
+
+/// Splits text of any length into 31-byte Cairo short strings, one felt per chunk.
+/// Unlike `short_string_to_felt`, the text is kept as-is (case and punctuation included).
+/// A chunk boundary may fall inside a multi-byte character; `felts_to_string` rejoins
+/// the bytes before decoding.
+pub fn string_to_felts(text: &str) -> Vec<FieldElement> {
+    text.as_bytes()
+        .chunks(SHORT_STRING_MAX_BYTES)
+        .map(|chunk| FieldElement::from_byte_slice_be(chunk).expect("31 bytes always fit in a felt"))
+        .collect()
+}
+
+/// Inverse of `string_to_felts`
+pub fn felts_to_string(felts: &[FieldElement]) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let mut bytes = Vec::with_capacity(felts.len() * SHORT_STRING_MAX_BYTES);
+    for felt in felts {
+        let be = felt.to_bytes_be();
+        let start = be.iter().position(|&b| b != 0).unwrap_or(be.len());
+        if be.len() - start > SHORT_STRING_MAX_BYTES {
+            return Err("Felt does not hold a short string".into());
+        }
+        bytes.extend_from_slice(&be[start..]);
     }
-    
-    Ok(FieldElement::from(num))
-} 
\ No newline at end of file
+    Ok(String::from_utf8(bytes)?)
+}
+
+/// Guesses a file type from its leading magic bytes, for files without an extension.
+/// Recognizes PNG, JPEG, PDF, gzip and ZIP, and falls back to `"bin"`.
+pub fn detect_file_type(data: &[u8]) -> String {
+    const SIGNATURES: [(&[u8], &str); 5] = [
+        (b"\x89PNG\r\n\x1a\n", "png"),
+        (b"\xff\xd8\xff", "jpg"),
+        (b"%PDF-", "pdf"),
+        (b"\x1f\x8b", "gz"),
+        (b"PK\x03\x04", "zip"),
+    ];
+    SIGNATURES
+        .iter()
+        .find(|(magic, _)| data.starts_with(magic))
+        .map_or("bin", |(_, file_type)| file_type)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_file_type() {
+        assert_eq!(detect_file_type(b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR"), "png");
+        assert_eq!(detect_file_type(b"%PDF-1.7\n%\xe2\xe3\xcf\xd3"), "pdf");
+        assert_eq!(detect_file_type(&[0x1f, 0x8b, 0x08, 0x00]), "gz");
+        assert_eq!(detect_file_type(b"PK\x03\x04\x14\x00"), "zip");
+        assert_eq!(detect_file_type(b"\x89PN"), "bin");
+        assert_eq!(detect_file_type(b""), "bin");
+        // Every detected type is usable as the file_format felt
+        assert!(short_string_to_felt(&detect_file_type(b"\xff\xd8\xff\xe0")).is_ok());
+    }
+
+    #[test]
+    fn test_cid_splits_into_two_felts() {
+        let cid = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+        assert_eq!(cid.len(), 46);
+        let felts = string_to_felts(cid);
+        assert_eq!(felts.len(), 2);
+        assert_eq!(felt_to_short_string(felts[0]).unwrap(), &cid[..31]);
+        assert_eq!(felts_to_string(&felts).unwrap(), cid);
+    }
+
+    #[test]
+    fn test_long_path_round_trips() {
+        let path = "ipfs/QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/report/summary.pdf";
+        assert_eq!(path.len(), 70);
+        let felts = string_to_felts(path);
+        assert_eq!(felts.len(), 3);
+        assert_eq!(felts_to_string(&felts).unwrap(), path);
+
+        assert!(string_to_felts("").is_empty());
+        assert_eq!(felts_to_string(&string_to_felts("short")).unwrap(), "short");
+    }
+}